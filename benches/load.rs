@@ -0,0 +1,107 @@
+//! Load time for a large generated catalog.
+//!
+//! Writes a catalog of `FILES` files (each holding a group, a system and a few
+//! components that reference them) to a temporary directory, then measures
+//! discovery alone, a full load without the cache — read, parse, validate,
+//! duplicates and relations — and a load from a warm cache. Run with
+//! `cargo bench --bench load`.
+
+use bsv::cache::Cache;
+use bsv::parser::{discover_catalog_files, load_catalog_with, Discovery};
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::fs;
+use std::hint::black_box;
+use std::path::{Path, PathBuf};
+
+/// Catalog files to generate, spread over `FILES / 100` directories.
+const FILES: usize = 2_000;
+
+/// Components per file, besides its group and system.
+const COMPONENTS_PER_FILE: usize = 4;
+
+fn file_content(i: usize) -> String {
+    let mut content = format!(
+        "\
+apiVersion: backstage.io/v1alpha1
+kind: Group
+metadata:
+  name: team-{i}
+spec:
+  type: team
+  children: []
+---
+apiVersion: backstage.io/v1alpha1
+kind: System
+metadata:
+  name: system-{i}
+spec:
+  owner: team-{i}
+"
+    );
+    for c in 0..COMPONENTS_PER_FILE {
+        // Depend on a component in the previous file, so relations span files.
+        let dependency = i.saturating_sub(1);
+        content.push_str(&format!(
+            "\
+---
+apiVersion: backstage.io/v1alpha1
+kind: Component
+metadata:
+  name: service-{i}-{c}
+  description: Generated service {c} of team {i}
+  tags: [generated, bench]
+spec:
+  type: service
+  lifecycle: production
+  owner: team-{i}
+  system: system-{i}
+  dependsOn:
+    - component:service-{dependency}-{c}
+"
+        ));
+    }
+    content
+}
+
+fn generate(root: &Path) {
+    let _ = fs::remove_dir_all(root);
+    for i in 0..FILES {
+        let dir = root
+            .join(format!("area-{}", i / 100))
+            .join(format!("service-{i}"));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("catalog-info.yaml"), file_content(i)).unwrap();
+    }
+}
+
+/// Load the catalog at `root`, returning how many entities it holds.
+fn load(root: &Path, cache: Option<&mut Cache>) -> usize {
+    let (entities, _) = load_catalog_with(root, &Discovery::default(), cache).unwrap();
+    entities.len()
+}
+
+fn bench_load(c: &mut Criterion) {
+    let root: PathBuf = std::env::temp_dir().join(format!("bsv-bench-{}", std::process::id()));
+    generate(&root);
+    let expected = FILES * (COMPONENTS_PER_FILE + 2);
+    assert_eq!(load(&root, None), expected);
+    let cache_path = root.with_extension("cache.json");
+    load(&root, Some(&mut Cache::at(cache_path.clone())));
+
+    let mut group = c.benchmark_group(format!("large catalog ({expected} entities)"));
+    group.sample_size(10);
+    group.bench_function("discover", |b| {
+        b.iter(|| discover_catalog_files(black_box(&root)))
+    });
+    group.bench_function("load_catalog", |b| b.iter(|| load(black_box(&root), None)));
+    group.bench_function("load_catalog (cached)", |b| {
+        b.iter(|| load(black_box(&root), Some(&mut Cache::at(cache_path.clone()))))
+    });
+    group.finish();
+
+    let _ = fs::remove_dir_all(&root);
+    let _ = fs::remove_file(&cache_path);
+}
+
+criterion_group!(benches, bench_load);
+criterion_main!(benches);
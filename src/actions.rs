@@ -0,0 +1,254 @@
+//! Every TUI action in one registry.
+//!
+//! [`ACTIONS`] lists each [`Action`] with its palette command name and
+//! description; key bindings come from the [`crate::keymap`]. The help
+//! overlay renders from both, and the command
+//! palette (`:` or Ctrl-P) filters it fuzzily and runs the chosen entry —
+//! with a parameter for commands such as `:goto component:default/foo`,
+//! `:filter owner:team-a` or `:export dot`. Running an action is
+//! [`App::perform`](crate::app::App::perform).
+
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use once_cell::sync::Lazy;
+
+/// Something the user can do in the TUI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    ToggleFocus,
+    MoveUp,
+    MoveDown,
+    PageUp,
+    PageDown,
+    First,
+    Last,
+    Collapse,
+    Expand,
+    Confirm,
+    ExpandAll,
+    CollapseAll,
+    Search,
+    Filter,
+    SavedSearches,
+    ClearFilter,
+    Goto,
+    ToggleGraph,
+    JumpToRelated,
+    ToggleRaw,
+    OpenDocs,
+    OpenEditor,
+    EditFields,
+    NewEntity,
+    Reload,
+    AcknowledgeChanges,
+    NextError,
+    PrevError,
+    Export,
+    CommandPalette,
+    Help,
+    Back,
+    Quit,
+}
+
+/// Registry entry for an [`Action`].
+#[derive(Debug)]
+pub struct ActionInfo {
+    pub action: Action,
+    /// Command name in the palette, e.g. `goto`.
+    pub command: &'static str,
+    pub description: &'static str,
+    /// Placeholder for the parameter the command takes, e.g. `REF`.
+    pub param: Option<&'static str>,
+}
+
+const fn entry(action: Action, command: &'static str, description: &'static str) -> ActionInfo {
+    ActionInfo {
+        action,
+        command,
+        description,
+        param: None,
+    }
+}
+
+const fn with_param(
+    action: Action,
+    command: &'static str,
+    param: &'static str,
+    description: &'static str,
+) -> ActionInfo {
+    ActionInfo {
+        action,
+        command,
+        description,
+        param: Some(param),
+    }
+}
+
+/// Every action, in help-overlay order.
+pub const ACTIONS: &[ActionInfo] = &[
+    entry(
+        Action::ToggleFocus,
+        "focus",
+        "Switch focus between tree and detail panel",
+    ),
+    entry(Action::MoveUp, "up", "Move selection up (or scroll)"),
+    entry(Action::MoveDown, "down", "Move selection down (or scroll)"),
+    entry(Action::PageUp, "page-up", "Move / scroll up by a page"),
+    entry(
+        Action::PageDown,
+        "page-down",
+        "Move / scroll down by a page",
+    ),
+    entry(Action::First, "first", "Jump to first (or top)"),
+    entry(Action::Last, "last", "Jump to last (or bottom)"),
+    entry(
+        Action::Collapse,
+        "collapse",
+        "Collapse node / return focus to tree",
+    ),
+    entry(Action::Expand, "expand", "Expand node"),
+    entry(
+        Action::Confirm,
+        "confirm",
+        "Expand node; in the graph, jump to the related entity",
+    ),
+    entry(Action::ExpandAll, "expand-all", "Expand all nodes"),
+    entry(Action::CollapseAll, "collapse-all", "Collapse all nodes"),
+    entry(
+        Action::Search,
+        "search",
+        "Fuzzy search (ranked; Enter jumps to the result)",
+    ),
+    with_param(
+        Action::Filter,
+        "filter",
+        "QUERY",
+        "Keep only entities matching a query while navigating",
+    ),
+    entry(
+        Action::SavedSearches,
+        "saved",
+        "Saved searches: apply one, or a to save the filter",
+    ),
+    entry(Action::ClearFilter, "unfilter", "Clear the filter"),
+    with_param(
+        Action::Goto,
+        "goto",
+        "REF",
+        "Select an entity by reference, e.g. component:default/foo",
+    ),
+    entry(Action::ToggleGraph, "graph", "Toggle relationship graph"),
+    entry(
+        Action::JumpToRelated,
+        "jump",
+        "Jump to the highlighted related entity (Tab to focus graph)",
+    ),
+    entry(Action::ToggleRaw, "raw", "Toggle raw YAML view"),
+    entry(
+        Action::OpenDocs,
+        "docs",
+        "Open documentation browser (when available)",
+    ),
+    entry(
+        Action::OpenEditor,
+        "open",
+        "Open the entity's source file in $VISUAL / $EDITOR",
+    ),
+    entry(
+        Action::EditFields,
+        "edit",
+        "Edit owner, lifecycle, system, tags, description, links",
+    ),
+    entry(
+        Action::NewEntity,
+        "new",
+        "Create a new entity with the wizard",
+    ),
+    entry(Action::Reload, "reload", "Reload catalog from disk"),
+    entry(
+        Action::AcknowledgeChanges,
+        "ack",
+        "Clear the marks on entities added or modified by reloads",
+    ),
+    entry(
+        Action::NextError,
+        "next-error",
+        "Jump to next entity with validation errors",
+    ),
+    entry(
+        Action::PrevError,
+        "prev-error",
+        "Jump to previous entity with validation errors",
+    ),
+    with_param(
+        Action::Export,
+        "export",
+        "dot|json [PATH]",
+        "Write the catalog (or the filtered tree) as Graphviz or JSON",
+    ),
+    entry(Action::CommandPalette, "palette", "Command palette"),
+    entry(Action::Help, "help", "Toggle this help"),
+    entry(
+        Action::Back,
+        "back",
+        "Clear search / close / return focus to tree",
+    ),
+    entry(Action::Quit, "quit", "Quit"),
+];
+
+static MATCHER: Lazy<SkimMatcherV2> = Lazy::new(|| SkimMatcherV2::default().ignore_case());
+
+/// The registry entry for `action`.
+pub fn info(action: Action) -> &'static ActionInfo {
+    ACTIONS
+        .iter()
+        .find(|info| info.action == action)
+        .expect("every action is registered")
+}
+
+/// The entry whose command name is exactly `command`.
+pub fn find(command: &str) -> Option<&'static ActionInfo> {
+    ACTIONS.iter().find(|info| info.command == command)
+}
+
+/// Palette entries for `input`, best first: its first word fuzzily matched
+/// against command names (and, less strongly, descriptions). Every entry, in
+/// registry order, for empty input.
+pub fn search(input: &str) -> Vec<&'static ActionInfo> {
+    let Some(word) = input.split_whitespace().next() else {
+        return ACTIONS.iter().collect();
+    };
+    let mut scored: Vec<(i64, usize, &ActionInfo)> = ACTIONS
+        .iter()
+        .enumerate()
+        .filter_map(|(i, info)| {
+            let command = MATCHER.fuzzy_match(info.command, word).map(|s| s * 2);
+            let description = MATCHER.fuzzy_match(info.description, word);
+            let score = command.max(description)?;
+            Some((score, i, info))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    scored.into_iter().map(|(_, _, info)| info).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_ranks_command_names_first() {
+        assert_eq!(search("").len(), ACTIONS.len());
+        assert_eq!(search("goto component:default/foo")[0].action, Action::Goto);
+        assert_eq!(search("exp")[0].action, Action::Expand);
+        assert_eq!(search("xprt")[0].action, Action::Export);
+        // Descriptions match too: "yaml" finds the raw view.
+        assert_eq!(search("yaml")[0].action, Action::ToggleRaw);
+        assert!(search("zzzz").is_empty());
+
+        let mut commands: Vec<_> = ACTIONS.iter().map(|info| info.command).collect();
+        commands.sort_unstable();
+        commands.dedup();
+        assert_eq!(commands.len(), ACTIONS.len(), "command names are unique");
+    }
+}
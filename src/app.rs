@@ -41,11 +41,11 @@
 //! app.search_input('e');
 //! app.search_input('r');
 //!
-//! // Get filtered results
+//! // Get ranked results (best match first)
 //! let visible = app.visible_nodes();
 //! println!("Found {} matches", visible.len());
 //!
-//! // Confirm search (exits input mode but keeps filter)
+//! // Confirm search: jump to the selected result in the tree
 //! app.confirm_search();
 //! # Ok::<(), anyhow::Error>(())
 //! ```
@@ -103,19 +103,52 @@
 //! # Key Types
 //!
 //! - [`App`] - Main application state container
-//! - [`InputMode`] - Current input mode (Normal, Search, DocsBrowser)
-
+//! - [`InputMode`] - Current input mode (Normal, Search, DocsBrowser, Edit, NewEntity,
+//!   SavedSearches, Palette)
+//! - [`ActiveFilter`] - Query that keeps the tree narrowed while navigating
+//! - [`CommandPalette`] - `:` prompt running any [`Action`] by name
+
+use crate::actions::{self, Action, ActionInfo};
+use crate::cache::Cache;
+use crate::catalog::LoadedCatalog;
+use crate::config::{self, Config, SavedSearch, PROJECT_CONFIG};
+use crate::diff::{CatalogDiff, Change};
 use crate::docs::{parse_docs_refs, DocsBrowser, DocsRef};
-use crate::entity::{EntityIndex, EntityWithSource};
+use crate::edit::EditForm;
+use crate::entity::{EntityRef, EntityWithSource};
 use crate::graph::RelationshipGraph;
-use crate::parser::load_catalog;
-use crate::tree::{EntityTree, TreeNode, TreeState};
-use anyhow::Result;
+use crate::keymap::Keymap;
+use crate::parser::Discovery;
+use crate::query::Query;
+use crate::remote::load_from_api;
+use crate::report;
+use crate::scaffold::{choices, write_entity, Wizard};
+use crate::tree::{EntityTree, SearchHit, TreeNode, TreeState};
+use crate::ui::theme::Theme;
+use crate::yaml_edit::set_field;
+use anyhow::{bail, Context, Result};
+use std::borrow::Cow;
 use std::cell::RefCell;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
+/// Status shown when editing is attempted on a catalog loaded from an API.
+const READ_ONLY: &str = "read-only: loaded from API";
+
+/// The entities served by the catalog API at `url`, as a catalog without files.
+fn load_api_catalog(url: &str) -> Result<LoadedCatalog> {
+    let (entities, warnings) = load_from_api(url)?;
+    Ok(LoadedCatalog::from_entities(entities, warnings))
+}
+
+/// Which tree nodes were expanded and selected, by [`node_identity`].
+struct TreeView {
+    expanded: HashSet<String>,
+    selected: Option<String>,
+}
+
 /// A stable identity for a tree node that survives a rebuild (node ids are
 /// reassigned when the catalog changes). Entities use their canonical ref;
 /// categories use their label.
@@ -130,6 +163,51 @@ pub enum InputMode {
     Normal,
     Search,
     DocsBrowser,
+    Edit,
+    NewEntity,
+    SavedSearches,
+    Palette,
+}
+
+/// A query applied to the tree while navigating: only matching entities (and
+/// the categories holding them) are listed until it is cleared.
+pub struct ActiveFilter {
+    /// Saved search name, or the query itself for an ad-hoc filter.
+    pub label: String,
+    pub query: String,
+    parsed: Query,
+}
+
+impl ActiveFilter {
+    fn new(label: &str, query: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            query: query.to_string(),
+            // Like search, an unparsable query filters by its raw text.
+            parsed: Query::parse(query).unwrap_or_else(|_| Query::text(query)),
+        }
+    }
+}
+
+/// Command palette: a command name, optionally followed by its parameter.
+#[derive(Default)]
+pub struct CommandPalette {
+    pub input: String,
+    /// Index into the matches for the input ([`App::palette_matches`]).
+    pub selected: usize,
+    /// Why the last command failed.
+    pub error: Option<String>,
+}
+
+/// Saved-search picker popup.
+#[derive(Default)]
+pub struct SearchPicker {
+    /// Index into [`App::saved_searches`].
+    pub selected: usize,
+    /// Name being typed to save the active filter, while prompting.
+    pub naming: Option<String>,
+    /// Why the last save failed.
+    pub error: Option<String>,
 }
 
 /// Which pane currently receives navigation keys.
@@ -149,8 +227,9 @@ pub struct App {
     pub entity_count: usize,
     pub search_query: String,
     pub search_active: bool,
-    pub entity_index: EntityIndex,
-    pub entities: Vec<EntityWithSource>,
+    /// Every loaded entity and their index; tree nodes refer into its store
+    /// rather than copy.
+    pub catalog: LoadedCatalog,
     pub show_graph: bool,
     pub show_raw: bool,
     pub focus: Focus,
@@ -164,10 +243,48 @@ pub struct App {
     /// Whether the keyboard-shortcut help overlay is showing.
     pub show_help: bool,
     pub docs_browser: Option<DocsBrowser>,
+    /// Field editor shown in the details panel while editing the selection.
+    pub edit_form: Option<EditForm>,
+    /// "New entity" wizard, shown as a popup while open.
+    pub new_wizard: Option<Wizard>,
+    /// Named queries from the project and user configuration files.
+    pub saved_searches: Vec<SavedSearch>,
+    /// Filter narrowing the tree, kept across navigation and reloads.
+    pub filter: Option<ActiveFilter>,
+    /// Saved-search picker, shown as a popup while open.
+    pub search_picker: Option<SearchPicker>,
+    /// Where newly saved searches are appended (the user's config file).
+    pub user_config: Option<PathBuf>,
+    /// Effective key bindings (preset plus `[keys]` overrides).
+    pub keymap: Keymap,
+    /// Colors the views draw with (defaults plus `[theme]` overrides).
+    pub theme: Theme,
+    /// Command palette, shown as a popup while open.
+    pub palette: Option<CommandPalette>,
+    /// One-line outcome of the last command, shown in the footer until the
+    /// next key press.
+    pub status: Option<String>,
+    /// Entities that reloads added or modified, by canonical ref, marked in
+    /// the tree until [`App::acknowledge_changes`].
+    pub changes: HashMap<String, Change>,
+    /// Source location the event loop should open in the user's editor. Set by
+    /// [`App::request_edit`]; the loop owns the terminal, so it performs the
+    /// suspend/launch/resume and then takes the request.
+    edit_request: Option<(PathBuf, usize)>,
     /// Lazily-built relationship graph for the selected entity, keyed by its
     /// node id so it is reused across frames instead of rebuilt every draw.
     relationship_cache: RefCell<Option<(usize, Rc<RelationshipGraph>)>>,
     root_path: PathBuf,
+    /// Catalog API the entities were fetched from (`--from-api`), reloaded in
+    /// place of `root_path` when set.
+    api_url: Option<String>,
+    /// How catalog files are discovered under `root_path`.
+    discovery: Discovery,
+    /// Cache of parsed files, kept up to date as files are read again.
+    cache: Option<Cache>,
+    /// Warnings from the configuration (not from catalog files), kept across
+    /// reloads of changed files.
+    config_warnings: Vec<String>,
 }
 
 impl App {
@@ -175,10 +292,51 @@ impl App {
     ///
     /// Root categories are expanded by default for immediate visibility.
     pub fn new(root: &Path) -> Result<Self> {
-        let (entities, load_warnings) = load_catalog(root)?;
-        let entity_count = entities.len();
-        let entity_index = EntityIndex::build(&entities);
-        let tree = EntityTree::build(&entities);
+        let (config, mut warnings) = Config::load(root);
+        let (discovery, discovery_warnings) = Discovery::from_config(&config.discovery);
+        warnings.extend(discovery_warnings);
+        let mut cache = Cache::open(root, &config.cache);
+        let catalog = LoadedCatalog::load(root, &discovery, cache.as_mut())?;
+        let mut app = Self::with_catalog(catalog, warnings, config, root, None);
+        app.discovery = discovery;
+        app.cache = cache;
+        Ok(app)
+    }
+
+    /// Create an app from the entities served by a Backstage catalog API.
+    /// New entities are written under the current directory.
+    pub fn from_api(url: &str) -> Result<Self> {
+        let root = Path::new(".");
+        let (config, warnings) = Config::load(root);
+        let catalog = load_api_catalog(url)?;
+        Ok(Self::with_catalog(
+            catalog,
+            warnings,
+            config,
+            root,
+            Some(url.to_string()),
+        ))
+    }
+
+    fn with_catalog(
+        catalog: LoadedCatalog,
+        config_warnings: Vec<String>,
+        config: Config,
+        root: &Path,
+        api_url: Option<String>,
+    ) -> Self {
+        let entity_count = catalog.entities.len();
+        let tree = EntityTree::build(Rc::clone(&catalog.entities));
+        let (keymap, keymap_warnings) = Keymap::from_config(&config.keys);
+        let mut config_warnings = config_warnings;
+        config_warnings.extend(keymap_warnings);
+        let (theme, theme_warnings) = Theme::from_config(&config.theme);
+        config_warnings.extend(theme_warnings);
+        let load_warnings = config_warnings
+            .iter()
+            .cloned()
+            .chain(catalog.all_warnings())
+            .collect();
 
         let mut tree_state = TreeState::new();
         // Expand root categories by default
@@ -186,15 +344,14 @@ impl App {
             tree_state.expanded.insert(root_id);
         }
 
-        Ok(Self {
+        Self {
             tree,
             tree_state,
             should_quit: false,
             entity_count,
             search_query: String::new(),
             search_active: false,
-            entity_index,
-            entities,
+            catalog,
             show_graph: false,
             show_raw: false,
             focus: Focus::Tree,
@@ -203,12 +360,28 @@ impl App {
             load_warnings,
             show_help: false,
             docs_browser: None,
+            edit_form: None,
+            new_wizard: None,
+            saved_searches: config.searches,
+            filter: None,
+            search_picker: None,
+            user_config: config::user_config_path(),
+            keymap,
+            theme,
+            palette: None,
+            status: None,
+            changes: HashMap::new(),
+            edit_request: None,
             relationship_cache: RefCell::new(None),
             root_path: root.to_path_buf(),
-        })
+            api_url,
+            discovery: Discovery::default(),
+            cache: None,
+            config_warnings,
+        }
     }
 
-    /// Reload all entities from disk, preserving the user's view as much as
+    /// Reload all entities from disk (or the catalog API), preserving the user's view as much as
     /// possible.
     ///
     /// Expansion state and the current selection are restored by stable identity
@@ -216,55 +389,677 @@ impl App {
     /// keeps both manual reload (`r`) and automatic file-watch reloads from being
     /// disruptive.
     pub fn reload(&mut self) {
-        match load_catalog(&self.root_path) {
-            Ok((entities, warnings)) => {
-                // Snapshot expansion + selection by identity before ids change.
-                let expanded: HashSet<String> = self
-                    .tree
-                    .nodes
+        let (config, mut warnings) = Config::load(&self.root_path);
+        let loaded = match &self.api_url {
+            Some(url) => load_api_catalog(url),
+            None => {
+                let (discovery, discovery_warnings) = Discovery::from_config(&config.discovery);
+                warnings.extend(discovery_warnings);
+                self.discovery = discovery;
+                self.cache = Cache::open(&self.root_path, &config.cache);
+                LoadedCatalog::load(&self.root_path, &self.discovery, self.cache.as_mut())
+            }
+        };
+        match loaded {
+            Ok(catalog) => {
+                let (keymap, keymap_warnings) = Keymap::from_config(&config.keys);
+                self.saved_searches = config.searches;
+                self.keymap = keymap;
+                warnings.extend(keymap_warnings);
+                let (theme, theme_warnings) = Theme::from_config(&config.theme);
+                self.theme = theme;
+                warnings.extend(theme_warnings);
+                self.config_warnings = warnings;
+                self.set_catalog(catalog);
+            }
+            Err(e) => {
+                // Keep the current catalog but make the failure visible.
+                self.load_warnings = vec![format!("Reload failed: {e}")];
+            }
+        }
+    }
+
+    /// Bring the catalog up to date after the files in `changed` were created,
+    /// modified or deleted (as reported by the file watcher), parsing again
+    /// only those files rather than the whole catalog.
+    ///
+    /// A change to the project configuration, or a catalog fetched from an
+    /// API, still reloads everything (see [`App::reload`]). The view is kept
+    /// the same way.
+    pub fn reload_paths(&mut self, changed: &[PathBuf]) {
+        let config_changed = changed
+            .iter()
+            .any(|path| path.file_name().is_some_and(|name| name == PROJECT_CONFIG));
+        if self.api_url.is_some() || config_changed {
+            return self.reload();
+        }
+        // Drop the tree's handles on the store so the catalog updates it in
+        // place rather than copying it; the view is restored afterwards.
+        let view = self.tree_view();
+        self.tree = EntityTree::default();
+        let updated = self.catalog.update(
+            &self.root_path,
+            &self.discovery,
+            self.cache.as_mut(),
+            changed,
+        );
+        match updated {
+            Ok(diff) => self.note_changes(&diff),
+            Err(e) => self.load_warnings = vec![format!("Reload failed: {e}")],
+        }
+        self.rebuild_tree_with(view);
+    }
+
+    /// Show `catalog` in place of the current entities, keeping the view.
+    fn set_catalog(&mut self, catalog: LoadedCatalog) {
+        let diff = CatalogDiff::between(&self.catalog.entities, &catalog.entities);
+        self.catalog = catalog;
+        self.note_changes(&diff);
+        self.rebuild_tree();
+    }
+
+    /// Sum up what a reload changed in the status line and show the catalog's
+    /// warnings. Added and modified entities are marked until acknowledged;
+    /// removed ones lose their marks.
+    fn note_changes(&mut self, diff: &CatalogDiff) {
+        for reference in &diff.removed {
+            self.changes.remove(reference);
+        }
+        for (reference, change) in diff.changes() {
+            // An entity added since the last acknowledgement stays "added".
+            self.changes.entry(reference.to_string()).or_insert(change);
+        }
+        self.status = Some(format!("Reloaded: {}", diff.summary()));
+
+        self.load_warnings = self
+            .config_warnings
+            .iter()
+            .cloned()
+            .chain(self.catalog.all_warnings())
+            .collect();
+        self.entity_count = self.catalog.entities.len();
+    }
+
+    /// Clear the marks of entities changed by reloads.
+    pub fn acknowledge_changes(&mut self) {
+        self.changes.clear();
+    }
+
+    /// How the reloads since the last acknowledgement changed `ews`, if they
+    /// did.
+    pub fn change_of(&self, ews: &EntityWithSource) -> Option<Change> {
+        if self.changes.is_empty() {
+            return None;
+        }
+        self.changes.get(&ews.entity.ref_key()).copied()
+    }
+
+    /// Rebuild the tree from the entities passing the filter, restoring
+    /// expansion and selection by stable identity (node ids are reassigned).
+    fn rebuild_tree(&mut self) {
+        let view = self.tree_view();
+        self.rebuild_tree_with(view);
+    }
+
+    /// The expanded and selected nodes, by identity, to restore after the
+    /// tree is rebuilt.
+    fn tree_view(&self) -> TreeView {
+        let expanded = self
+            .tree
+            .nodes
+            .iter()
+            .filter(|n| self.tree_state.is_expanded(n.id))
+            .map(node_identity)
+            .collect();
+        let selected = self
+            .tree
+            .get_node(self.tree_state.selected)
+            .map(node_identity);
+        TreeView { expanded, selected }
+    }
+
+    /// Rebuild the tree as [`App::rebuild_tree`] does, restoring `view`.
+    fn rebuild_tree_with(&mut self, view: TreeView) {
+        let TreeView { expanded, selected } = view;
+        let entities = Rc::clone(&self.catalog.entities);
+        self.tree = match &self.filter {
+            Some(filter) => EntityTree::build_matching(entities, |ews| filter.parsed.matches(ews)),
+            None => EntityTree::build(entities),
+        };
+
+        // Restore expansion + selection against the rebuilt tree.
+        let mut state = TreeState::new();
+        for node in &self.tree.nodes {
+            if expanded.contains(&node_identity(node)) {
+                state.expanded.insert(node.id);
+            }
+        }
+        if state.expanded.is_empty() {
+            for &root_id in &self.tree.root_children {
+                state.expanded.insert(root_id);
+            }
+        }
+        let restored = selected
+            .and_then(|sel| self.tree.nodes.iter().find(|n| node_identity(n) == sel))
+            .map(|node| node.id);
+        state.selected = restored
+            .or_else(|| self.tree.root_children.first().copied())
+            .unwrap_or(0);
+        self.tree_state = state;
+
+        self.detail_scroll = 0;
+        self.graph_selection = 0;
+        self.relationship_cache = RefCell::new(None);
+    }
+
+    /// Keep only entities matching `query` in the tree, with every category
+    /// expanded so the matches are in view.
+    pub fn set_filter(&mut self, label: &str, query: &str) {
+        if query.trim().is_empty() {
+            return self.clear_filter();
+        }
+        self.filter = Some(ActiveFilter::new(label, query));
+        self.rebuild_tree();
+        self.tree_state.expand_all(&self.tree);
+    }
+
+    /// Show the whole catalog again, keeping the selection.
+    pub fn clear_filter(&mut self) {
+        if self.filter.take().is_some() {
+            self.rebuild_tree();
+        }
+    }
+
+    /// The entities in the tree: all of them unless filtered.
+    pub fn shown_entities(&self) -> Cow<'_, [EntityWithSource]> {
+        match &self.filter {
+            Some(filter) => Cow::Owned(
+                self.catalog
+                    .entities
                     .iter()
-                    .filter(|n| self.tree_state.is_expanded(n.id))
-                    .map(node_identity)
-                    .collect();
-                let selected = self
-                    .tree
-                    .get_node(self.tree_state.selected)
-                    .map(node_identity);
-
-                self.entity_count = entities.len();
-                self.entity_index = EntityIndex::build(&entities);
-                self.tree = EntityTree::build(&entities);
-                self.entities = entities;
-
-                // Restore expansion + selection against the rebuilt tree.
-                let mut state = TreeState::new();
-                for node in &self.tree.nodes {
-                    if expanded.contains(&node_identity(node)) {
-                        state.expanded.insert(node.id);
-                    }
-                }
-                if state.expanded.is_empty() {
-                    for &root_id in &self.tree.root_children {
-                        state.expanded.insert(root_id);
-                    }
+                    .filter(|ews| filter.parsed.matches(ews))
+                    .cloned()
+                    .collect(),
+            ),
+            None => Cow::Borrowed(&self.catalog.entities),
+        }
+    }
+
+    /// Number of entities in the tree (all of them unless filtered).
+    pub fn shown_count(&self) -> usize {
+        match &self.filter {
+            Some(filter) => self
+                .catalog
+                .entities
+                .iter()
+                .filter(|ews| filter.parsed.matches(ews))
+                .count(),
+            None => self.entity_count,
+        }
+    }
+
+    /// Run `action`, with `param` for the commands that take one (see
+    /// [`actions::ACTIONS`]). `page` is the page size for paging.
+    pub fn perform(&mut self, action: Action, param: &str, page: usize) -> Result<()> {
+        match action {
+            Action::ToggleFocus => self.toggle_focus(),
+            Action::MoveUp => self.move_up(),
+            Action::MoveDown => self.move_down(),
+            Action::PageUp => self.page_up(page),
+            Action::PageDown => self.page_down(page),
+            Action::First => self.move_home(),
+            Action::Last => self.move_end(),
+            Action::Collapse => self.collapse(),
+            Action::Expand => self.toggle_expand(),
+            Action::Confirm if self.is_detail_focused() => {
+                if self.show_graph {
+                    self.jump_to_related();
                 }
-                if let Some(sel) = selected {
-                    if let Some(node) = self.tree.nodes.iter().find(|n| node_identity(n) == sel) {
-                        state.selected = node.id;
+            }
+            Action::Confirm => self.toggle_expand(),
+            Action::ExpandAll => self.expand_all(),
+            Action::CollapseAll => self.collapse_all(),
+            Action::Search => self.start_search(),
+            Action::Filter if param.trim().is_empty() => self.filter_by_search(),
+            Action::Filter => self.set_filter(param.trim(), param.trim()),
+            Action::SavedSearches => self.open_search_picker(),
+            Action::ClearFilter => self.clear_filter(),
+            Action::Goto => self.goto(param)?,
+            Action::ToggleGraph => self.toggle_graph(),
+            Action::JumpToRelated => {
+                self.jump_to_related();
+            }
+            Action::ToggleRaw => self.toggle_raw(),
+            Action::OpenDocs => self.open_docs(),
+            Action::OpenEditor => self.request_edit(),
+            Action::EditFields => self.start_edit(),
+            Action::NewEntity => self.start_new_entity(),
+            Action::Reload => self.reload(),
+            Action::AcknowledgeChanges => self.acknowledge_changes(),
+            Action::NextError => self.next_error(),
+            Action::PrevError => self.prev_error(),
+            Action::Export => {
+                let (count, path) = self.export(param)?;
+                self.status = Some(format!("Exported {count} entities to {}", path.display()));
+            }
+            Action::CommandPalette => self.open_palette(),
+            Action::Help => self.toggle_help(),
+            Action::Back => self.focus_tree_and_clear_search(),
+            Action::Quit => self.quit(),
+        }
+        Ok(())
+    }
+
+    /// Select the entity `reference` names (`component:default/foo`, or a
+    /// bare name of any kind), revealing it in the tree.
+    pub fn goto(&mut self, reference: &str) -> Result<()> {
+        let reference = reference.trim();
+        if reference.is_empty() {
+            bail!("goto needs an entity reference");
+        }
+        let wanted = EntityRef::parse(reference, "component");
+        let canonical = wanted.canonical();
+        let names = |ews: &EntityWithSource| {
+            ews.entity.ref_key().eq_ignore_ascii_case(&canonical)
+                || (wanted.kind_inferred
+                    && ews.entity.metadata.name.eq_ignore_ascii_case(&wanted.name)
+                    && (wanted.namespace_inferred
+                        || ews.entity.ref_key().ends_with(&format!(
+                            ":{}/{}",
+                            wanted.namespace, ews.entity.metadata.name
+                        ))))
+        };
+        let target = self
+            .tree
+            .nodes
+            .iter()
+            .find(|n| n.entity.as_deref().is_some_and(names))
+            .map(|n| n.id);
+        match target {
+            Some(id) => {
+                self.reveal_node(id);
+                Ok(())
+            }
+            None if self.catalog.entities.iter().any(names) => {
+                bail!("{reference} is hidden by the filter (F clears it)")
+            }
+            None => bail!("No entity {reference}"),
+        }
+    }
+
+    /// Write the entities in the tree as `dot` (Graphviz) or `json` to the
+    /// path after the format, `catalog.<format>` by default. Returns how many
+    /// entities were written, and where.
+    pub fn export(&self, args: &str) -> Result<(usize, PathBuf)> {
+        let mut words = args.split_whitespace();
+        let format = words.next().unwrap_or_default().to_lowercase();
+        let path = words
+            .next()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(format!("catalog.{format}")));
+        let entities = self.shown_entities();
+        let mut out = Vec::new();
+        match format.as_str() {
+            "dot" => report::write_dot(&entities, &mut out)?,
+            "json" => report::write_json(&entities, &mut out)?,
+            "" => bail!("export needs a format: dot or json"),
+            other => bail!("Unknown export format '{other}' (expected dot or json)"),
+        }
+        fs::write(&path, out).with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok((entities.len(), path))
+    }
+
+    /// Open the command palette.
+    pub fn open_palette(&mut self) {
+        self.palette = Some(CommandPalette::default());
+    }
+
+    pub fn close_palette(&mut self) {
+        self.palette = None;
+    }
+
+    /// Actions matching the palette input, best first.
+    pub fn palette_matches(&self) -> Vec<&'static ActionInfo> {
+        self.palette
+            .as_ref()
+            .map_or_else(Vec::new, |p| actions::search(&p.input))
+    }
+
+    pub fn palette_input(&mut self, c: char) {
+        if let Some(palette) = &mut self.palette {
+            palette.input.push(c);
+            palette.selected = 0;
+            palette.error = None;
+        }
+    }
+
+    pub fn palette_backspace(&mut self) {
+        if let Some(palette) = &mut self.palette {
+            palette.input.pop();
+            palette.selected = 0;
+            palette.error = None;
+        }
+    }
+
+    pub fn palette_move_up(&mut self) {
+        if let Some(palette) = &mut self.palette {
+            palette.selected = palette.selected.saturating_sub(1);
+        }
+    }
+
+    pub fn palette_move_down(&mut self) {
+        let last = self.palette_matches().len().saturating_sub(1);
+        if let Some(palette) = &mut self.palette {
+            palette.selected = (palette.selected + 1).min(last);
+        }
+    }
+
+    /// Replace the typed command with the selected one's name (Tab), ready
+    /// for a parameter.
+    pub fn palette_complete(&mut self) {
+        let matches = self.palette_matches();
+        let selected = self.palette.as_ref().map_or(0, |p| p.selected);
+        if let Some(info) = matches.get(selected) {
+            self.palette_complete_with(info);
+        }
+    }
+
+    /// Run the command typed in the palette — an exact command name, else the
+    /// selected match — with the rest of the input as its parameter. A
+    /// command that needs a parameter and has none is completed instead; a
+    /// failing one leaves the palette open with the error.
+    pub fn palette_confirm(&mut self, page: usize) {
+        let Some(palette) = &self.palette else {
+            return;
+        };
+        let input = palette.input.trim().to_string();
+        let (word, param) = input
+            .split_once(char::is_whitespace)
+            .map_or((input.as_str(), ""), |(w, p)| (w, p.trim()));
+        let info =
+            actions::find(word).or_else(|| actions::search(&input).get(palette.selected).copied());
+        let Some(info) = info else {
+            return;
+        };
+        if info.param.is_some() && param.is_empty() {
+            return self.palette_complete_with(info);
+        }
+        self.palette = None;
+        if let Err(e) = self.perform(info.action, param, page) {
+            self.palette = Some(CommandPalette {
+                input,
+                selected: 0,
+                error: Some(format!("{e:#}")),
+            });
+        }
+    }
+
+    fn palette_complete_with(&mut self, info: &ActionInfo) {
+        if let Some(palette) = &mut self.palette {
+            palette.input = format!("{} ", info.command);
+            palette.selected = 0;
+        }
+    }
+
+    /// Open the saved-search picker.
+    pub fn open_search_picker(&mut self) {
+        self.search_picker = Some(SearchPicker::default());
+    }
+
+    pub fn close_search_picker(&mut self) {
+        self.search_picker = None;
+    }
+
+    pub fn picker_move_up(&mut self) {
+        if let Some(picker) = &mut self.search_picker {
+            picker.selected = picker.selected.saturating_sub(1);
+        }
+    }
+
+    pub fn picker_move_down(&mut self) {
+        let last = self.saved_searches.len().saturating_sub(1);
+        if let Some(picker) = &mut self.search_picker {
+            picker.selected = (picker.selected + 1).min(last);
+        }
+    }
+
+    /// Apply the highlighted saved search as the filter and close the picker.
+    pub fn apply_saved_search(&mut self) {
+        let Some(picker) = &self.search_picker else {
+            return;
+        };
+        if let Some(search) = self.saved_searches.get(picker.selected).cloned() {
+            self.search_picker = None;
+            self.set_filter(&search.name, &search.query);
+        }
+    }
+
+    /// Start naming the active filter to save it. No-op without a filter.
+    pub fn start_saving_filter(&mut self) {
+        if self.filter.is_none() {
+            return;
+        }
+        if let Some(picker) = &mut self.search_picker {
+            picker.naming = Some(String::new());
+            picker.error = None;
+        }
+    }
+
+    /// Save the active filter under the typed name to the user's config file.
+    /// On failure the picker stays open with the error.
+    pub fn save_filter(&mut self) {
+        let (Some(picker), Some(filter)) = (&mut self.search_picker, &mut self.filter) else {
+            return;
+        };
+        let name = picker
+            .naming
+            .as_deref()
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+        if name.is_empty() {
+            return;
+        }
+        let search = SavedSearch {
+            name,
+            query: filter.query.clone(),
+        };
+        let saved = match &self.user_config {
+            Some(path) => config::append_search(path, &search),
+            None => Err(anyhow::anyhow!("No config directory (set $HOME)")),
+        };
+        match saved {
+            Ok(()) => {
+                filter.label = search.name.clone();
+                picker.naming = None;
+                picker.error = None;
+                picker.selected = self.saved_searches.len();
+                self.saved_searches.push(search);
+            }
+            Err(e) => picker.error = Some(format!("{e:#}")),
+        }
+    }
+
+    /// Open the field editor for the selected entity. No-op on categories,
+    /// and on a catalog loaded from an API (there is no file to write to).
+    pub fn start_edit(&mut self) {
+        if self.is_read_only() {
+            return;
+        }
+        if self.selected_entity().is_some() {
+            self.edit_form = Some(EditForm::new());
+            self.focus = Focus::Detail;
+        }
+    }
+
+    /// Close the field editor (Esc): first leave the field being edited, then
+    /// the form itself.
+    pub fn close_edit(&mut self) {
+        match &mut self.edit_form {
+            Some(form) if form.is_editing() => form.cancel(),
+            _ => {
+                self.edit_form = None;
+                self.focus = Focus::Tree;
+            }
+        }
+    }
+
+    /// Open the highlighted field for editing, or save it if already editing.
+    pub fn edit_enter(&mut self) {
+        let Some(form) = &self.edit_form else {
+            return;
+        };
+        if form.is_editing() {
+            let result = self.apply_edit();
+            let errors = self
+                .selected_entity()
+                .map_or(0, |ews| ews.validation_errors.len());
+            if let Some(form) = &mut self.edit_form {
+                let field = form.field().label().to_lowercase();
+                form.message = Some(match result {
+                    Ok(()) if errors > 0 => {
+                        format!("Saved {field} — entity now has {errors} validation error(s)")
                     }
-                }
-                self.tree_state = state;
+                    Ok(()) => format!("Saved {field}"),
+                    Err(e) => format!("Not saved: {e:#}"),
+                });
+            }
+        } else {
+            let current = self
+                .selected_entity()
+                .map(|ews| form.field().current_text(&ews.entity))
+                .unwrap_or_default();
+            if let Some(form) = &mut self.edit_form {
+                form.begin(current);
+            }
+        }
+    }
+
+    /// Write the field being edited back into the selected entity's source
+    /// file, preserving the rest of the file, then reload so the change is
+    /// revalidated and shown.
+    pub fn apply_edit(&mut self) -> Result<()> {
+        let (Some(form), Some(ews)) = (&self.edit_form, self.selected_entity()) else {
+            return Ok(());
+        };
+        let Some(text) = &form.input else {
+            return Ok(());
+        };
+        if self.api_url.is_some() {
+            bail!("{READ_ONLY}");
+        }
+        let field = form.field();
+        let (section, key) = field.location();
+        let value = field.parse_text(text, &ews.entity);
+        let path = ews.source_file.clone();
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read file: {}", path.display()))?;
+        let edited = set_field(&content, ews.line, section, key, &value)?;
+        fs::write(&path, edited)
+            .with_context(|| format!("Failed to write file: {}", path.display()))?;
+
+        if let Some(form) = &mut self.edit_form {
+            form.cancel();
+        }
+        self.reload();
+        Ok(())
+    }
+
+    /// Open the "new entity" wizard.
+    pub fn start_new_entity(&mut self) {
+        self.new_wizard = Some(Wizard::new());
+    }
 
-                self.detail_scroll = 0;
-                self.graph_selection = 0;
-                self.load_warnings = warnings;
-                self.relationship_cache = RefCell::new(None);
+    /// Go back one wizard step (Esc), closing the wizard from the first step.
+    pub fn new_entity_back(&mut self) {
+        if let Some(wizard) = &mut self.new_wizard {
+            if !wizard.back() {
+                self.new_wizard = None;
             }
+        }
+    }
+
+    /// Choices for the wizard's current step, filtered by what was typed.
+    pub fn new_entity_choices(&self) -> Vec<String> {
+        let Some(wizard) = &self.new_wizard else {
+            return Vec::new();
+        };
+        choices(
+            wizard.current(),
+            &wizard.draft,
+            &self.catalog.entities,
+            &self.catalog.index,
+            &self.root_path,
+            &wizard.typed,
+        )
+    }
+
+    /// Insert the next choice for the current wizard step (Tab).
+    pub fn new_entity_complete(&mut self) {
+        let candidates = self.new_entity_choices();
+        if let Some(wizard) = &mut self.new_wizard {
+            wizard.complete(&candidates);
+        }
+    }
+
+    /// Accept the current wizard step (Enter). After the last step the entity
+    /// is validated, written, and selected in the reloaded tree.
+    pub fn new_entity_enter(&mut self) {
+        let candidates = self.new_entity_choices();
+        let Some(wizard) = &mut self.new_wizard else {
+            return;
+        };
+        match wizard.accept(&candidates) {
+            Ok(false) => return,
+            Ok(true) => {}
             Err(e) => {
-                // Keep the current catalog but make the failure visible.
-                self.load_warnings = vec![format!("Reload failed: {e}")];
+                wizard.errors = vec![e.to_string()];
+                return;
+            }
+        }
+        if !wizard.check(&self.catalog.index) {
+            return;
+        }
+
+        let draft = wizard.draft.clone();
+        let written = draft
+            .to_yaml()
+            .and_then(|yaml| write_entity(Path::new(&draft.target), &yaml));
+        match written {
+            Ok(_) => {
+                self.new_wizard = None;
+                self.reload();
+                self.select_entity_by_ref(&draft.ref_key());
             }
+            Err(e) => wizard.errors = vec![format!("Not written: {e:#}")],
+        }
+    }
+
+    /// Ask the event loop to open the selected entity's source file in the
+    /// user's editor, positioned at the entity's document. No-op on categories,
+    /// and on a catalog loaded from an API (its sources are URLs, not files).
+    pub fn request_edit(&mut self) {
+        if self.is_read_only() {
+            return;
         }
+        self.edit_request = self
+            .selected_entity()
+            .map(|ews| (ews.source_file.clone(), ews.line));
+    }
+
+    /// Whether the catalog was loaded from an API and so can't be edited;
+    /// if so, says so in the footer.
+    fn is_read_only(&mut self) -> bool {
+        if self.api_url.is_some() {
+            self.status = Some(READ_ONLY.to_string());
+        }
+        self.api_url.is_some()
+    }
+
+    /// Take a pending editor request (file and 1-based line), if any.
+    pub fn take_edit_request(&mut self) -> Option<(PathBuf, usize)> {
+        self.edit_request.take()
     }
 
     /// Toggle the keyboard-shortcut help overlay.
@@ -375,7 +1170,11 @@ impl App {
         }
 
         let entity = self.selected_entity()?;
-        let graph = Rc::new(RelationshipGraph::build(entity, &self.entities));
+        let graph = Rc::new(RelationshipGraph::build_indexed(
+            entity,
+            &self.catalog.entities,
+            &self.catalog.index,
+        ));
         *self.relationship_cache.borrow_mut() = Some((selected, Rc::clone(&graph)));
         Some(graph)
     }
@@ -468,9 +1267,14 @@ impl App {
             .collect()
     }
 
-    /// Number of entities with validation errors.
+    /// Number of entities with validation errors. An entity shown at several
+    /// places in the tree (e.g. a user in two groups) is counted once.
     pub fn error_count(&self) -> usize {
-        self.error_node_ids().len()
+        self.catalog
+            .entities
+            .iter()
+            .filter(|ews| !ews.validation_errors.is_empty())
+            .count()
     }
 
     /// Select the next entity (in display order) that has validation errors,
@@ -513,13 +1317,29 @@ impl App {
         self.reveal_node(target);
     }
 
-    /// Get visible nodes filtered by search query if active.
+    /// Why the search query does not parse, if it doesn't; the tree is then
+    /// filtered by the raw text instead.
+    pub fn search_error(&self) -> Option<String> {
+        Query::parse(&self.search_query)
+            .err()
+            .map(|e| e.to_string())
+    }
+
+    /// Ranked search results, best first (empty without a query).
+    pub fn search_hits(&self) -> Vec<SearchHit<'_>> {
+        if self.search_query.trim().is_empty() {
+            return Vec::new();
+        }
+        self.tree.search(&self.search_query)
+    }
+
+    /// The rows of the tree panel: the expanded tree, or while there is a
+    /// search query, the ranked results.
     pub fn visible_nodes(&self) -> Vec<&TreeNode> {
-        let nodes = self.tree.visible_nodes(&self.tree_state);
-        if self.search_query.is_empty() {
-            nodes
+        if self.search_query.trim().is_empty() {
+            self.tree.visible_nodes(&self.tree_state)
         } else {
-            EntityTree::filter_by_search(nodes, &self.search_query)
+            self.search_hits().into_iter().map(|hit| hit.node).collect()
         }
     }
 
@@ -666,7 +1486,7 @@ impl App {
     pub fn selected_entity(&self) -> Option<&EntityWithSource> {
         self.tree
             .get_node(self.tree_state.selected)
-            .and_then(|n| n.entity.as_ref())
+            .and_then(|n| n.entity.as_deref())
     }
 
     pub fn quit(&mut self) {
@@ -682,15 +1502,25 @@ impl App {
         self.search_query.clear();
     }
 
+    /// Leave search, keeping the query applied as the tree filter.
+    pub fn filter_by_search(&mut self) {
+        let query = std::mem::take(&mut self.search_query);
+        self.search_active = false;
+        self.set_filter(&query, &query);
+    }
+
+    /// Leave search and jump to the selected result in the tree, expanding
+    /// its ancestors. With no results the tree is left as it was.
     pub fn confirm_search(&mut self) {
         self.search_active = false;
-        // Keep query active but exit input mode
-        // Select first visible match if current selection is not visible
-        let visible = self.visible_nodes();
-        if !visible.iter().any(|n| n.id == self.tree_state.selected) {
-            if let Some(first) = visible.first() {
-                self.tree_state.selected = first.id;
-            }
+        let target = self
+            .search_hits()
+            .iter()
+            .map(|hit| hit.node.id)
+            .find(|&id| id == self.tree_state.selected);
+        self.search_query.clear();
+        if let Some(id) = target {
+            self.reveal_node(id);
         }
     }
 
@@ -701,14 +1531,13 @@ impl App {
 
     pub fn search_backspace(&mut self) {
         self.search_query.pop();
+        self.update_selection_for_search();
     }
 
+    /// Select the best match as the query changes.
     fn update_selection_for_search(&mut self) {
-        let visible = self.visible_nodes();
-        if !visible.iter().any(|n| n.id == self.tree_state.selected) {
-            if let Some(first) = visible.first() {
-                self.tree_state.selected = first.id;
-            }
+        if let Some(first) = self.visible_nodes().first() {
+            self.tree_state.selected = first.id;
         }
     }
 
@@ -721,6 +1550,14 @@ impl App {
             InputMode::Search
         } else if self.is_docs_active() {
             InputMode::DocsBrowser
+        } else if self.edit_form.is_some() {
+            InputMode::Edit
+        } else if self.new_wizard.is_some() {
+            InputMode::NewEntity
+        } else if self.palette.is_some() {
+            InputMode::Palette
+        } else if self.search_picker.is_some() {
+            InputMode::SavedSearches
         } else {
             InputMode::Normal
         }
@@ -730,6 +1567,7 @@ impl App {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::TempDir;
     use std::path::Path;
 
     fn test_app() -> App {
@@ -759,6 +1597,136 @@ mod tests {
         assert_eq!(app.tree_state.selected, first);
     }
 
+    #[test]
+    fn fuzzy_search_ranks_and_enter_jumps_to_the_result() {
+        let mut app = test_app();
+        app.start_search();
+        for c in "pmt-gtw".chars() {
+            app.search_input(c);
+        }
+        // The collapsed tree is searched and the best match is selected.
+        let best = app.selected_entity().unwrap();
+        assert_eq!(best.entity.metadata.name, "payment-gateway");
+        let target = app.tree_state.selected;
+
+        app.confirm_search();
+        assert!(app.search_query.is_empty());
+        assert!(matches!(app.input_mode(), InputMode::Normal));
+        assert_eq!(app.tree_state.selected, target);
+        assert!(
+            app.visible_nodes().iter().any(|n| n.id == target),
+            "ancestors are expanded so the result is visible"
+        );
+    }
+
+    #[test]
+    fn filter_persists_while_navigating_and_saves_as_a_search() {
+        let mut app = test_app();
+        let config = TempDir::new("app-config");
+        app.user_config = Some(config.join("config.toml"));
+
+        app.start_search();
+        for c in "kind:api".chars() {
+            app.search_input(c);
+        }
+        app.filter_by_search();
+        assert!(matches!(app.input_mode(), InputMode::Normal));
+        assert!(app.search_query.is_empty());
+        assert_eq!(app.shown_count(), 20);
+
+        // Only APIs are listed, and navigation keeps it that way.
+        let only_apis = |app: &App| {
+            app.visible_nodes()
+                .iter()
+                .filter_map(|n| n.entity.as_ref())
+                .all(|ews| ews.entity.kind == crate::entity::EntityKind::Api)
+        };
+        app.move_end();
+        app.collapse_all();
+        app.expand_all();
+        assert!(only_apis(&app));
+        app.reload();
+        assert!(only_apis(&app));
+
+        app.open_search_picker();
+        app.start_saving_filter();
+        for c in "All APIs".chars() {
+            app.search_picker
+                .as_mut()
+                .unwrap()
+                .naming
+                .as_mut()
+                .unwrap()
+                .push(c);
+        }
+        app.save_filter();
+        assert_eq!(app.filter.as_ref().unwrap().label, "All APIs");
+        assert_eq!(app.saved_searches.last().unwrap().query, "kind:api");
+        let saved = Config::read(&config.join("config.toml")).unwrap();
+        assert_eq!(
+            saved.searches,
+            vec![app.saved_searches.last().unwrap().clone()]
+        );
+
+        app.clear_filter();
+        assert_eq!(app.shown_count(), app.entity_count);
+        assert!(!only_apis(&app));
+
+        // Applying the saved search from the picker restores the filter.
+        app.picker_move_down();
+        app.apply_saved_search();
+        assert!(app.search_picker.is_none());
+        assert_eq!(app.shown_count(), 20);
+    }
+
+    #[test]
+    fn palette_runs_commands_with_parameters() {
+        let mut app = test_app();
+        let type_in = |app: &mut App, text: &str| {
+            app.open_palette();
+            for c in text.chars() {
+                app.palette_input(c);
+            }
+            app.palette_confirm(10);
+        };
+
+        type_in(&mut app, "goto component:default/payment-gateway");
+        assert!(app.palette.is_none());
+        assert_eq!(
+            app.selected_entity().unwrap().entity.ref_key(),
+            "component:default/payment-gateway"
+        );
+
+        // A fuzzy command name runs the best match; a missing parameter
+        // completes the command instead of running it.
+        type_in(&mut app, "gt");
+        assert_eq!(app.palette.as_ref().unwrap().input, "goto ");
+        app.palette_confirm(10);
+        assert!(app.palette.is_some());
+
+        type_in(&mut app, "filter kind:api");
+        assert_eq!(app.shown_count(), 20);
+        type_in(&mut app, "goto payment-gateway");
+        let error = app.palette.as_ref().unwrap().error.clone().unwrap();
+        assert!(error.contains("hidden by the filter"), "{error}");
+        app.close_palette();
+
+        let dir = TempDir::new("export");
+        let out = dir.join("graph.dot");
+        type_in(&mut app, &format!("export dot {}", out.display()));
+        assert!(app.palette.is_none());
+        assert_eq!(
+            app.status.as_deref(),
+            Some(format!("Exported 20 entities to {}", out.display()).as_str())
+        );
+        assert!(fs::read_to_string(&out)
+            .unwrap()
+            .starts_with("digraph catalog {"));
+
+        type_in(&mut app, "export svg");
+        assert!(app.palette.as_ref().unwrap().error.is_some());
+    }
+
     #[test]
     fn focus_toggles_between_tree_and_detail() {
         let mut app = test_app();
@@ -863,6 +1831,96 @@ mod tests {
         assert_eq!(expanded_after, expanded_before, "expansion preserved");
     }
 
+    #[test]
+    fn reload_paths_patches_changed_files_and_keeps_the_selection() {
+        let dir = TempDir::new("reload-paths");
+        fs::create_dir_all(dir.join("svc")).unwrap();
+        dir.disable_cache();
+        let group = dir.join("catalog-info.yaml");
+        let svc = dir.join("svc").join("catalog-info.yaml");
+        fs::write(&group, "apiVersion: backstage.io/v1alpha1\nkind: Group\nmetadata:\n  name: team-a\nspec:\n  type: team\n  children: []\n").unwrap();
+        fs::write(&svc, "apiVersion: backstage.io/v1alpha1\nkind: Component\nmetadata:\n  name: svc\nspec:\n  type: service\n  owner: team-a\n  lifecycle: production\n").unwrap();
+        let mut app = App::new(&dir).expect("load temp catalog");
+        assert!(app.select_entity_by_ref("group:default/team-a"));
+        let group_name = app.catalog.entities[0].entity.metadata.name.as_ptr();
+
+        // An edit that no longer parses is a warning; a new file is picked up.
+        fs::write(&svc, "kind: 42\n").unwrap();
+        let other = dir.join("svc").join("catalog-info.yml");
+        fs::write(&other, "apiVersion: backstage.io/v1alpha1\nkind: Component\nmetadata:\n  name: other\nspec:\n  type: service\n  owner: team-a\n  lifecycle: production\n").unwrap();
+        app.reload_paths(&[svc.clone(), other]);
+        assert_eq!(app.entity_count, 2);
+        assert_eq!(app.load_warnings.len(), 1, "{:?}", app.load_warnings);
+        assert!(!app
+            .catalog
+            .index
+            .contains(&EntityRef::parse("svc", "component")));
+        assert!(app
+            .catalog
+            .index
+            .contains(&EntityRef::parse("other", "component")));
+        let ews = app.selected_entity().expect("selection kept");
+        assert_eq!(ews.entity.metadata.name, "team-a");
+        assert_eq!(
+            ews.entity.metadata.name.as_ptr(),
+            group_name,
+            "the unchanged group was copied"
+        );
+
+        // Deleting the file drops its warning.
+        fs::remove_file(&svc).unwrap();
+        app.reload_paths(&[svc]);
+        assert_eq!(app.entity_count, 2);
+        assert!(app.load_warnings.is_empty(), "{:?}", app.load_warnings);
+    }
+
+    #[test]
+    fn reloads_sum_up_changes_and_mark_them_until_acknowledged() {
+        let dir = TempDir::new("reload-diff");
+        dir.disable_cache();
+        let file = dir.join("catalog-info.yaml");
+        let group = "apiVersion: backstage.io/v1alpha1\nkind: Group\nmetadata:\n  name: team-a\nspec:\n  type: team\n  children: []\n";
+        let svc = "---\napiVersion: backstage.io/v1alpha1\nkind: Component\nmetadata:\n  name: svc\nspec:\n  type: service\n  owner: team-a\n";
+        fs::write(&file, format!("{group}{svc}")).unwrap();
+        let mut app = App::new(&dir).expect("load temp catalog");
+        assert!(app.changes.is_empty());
+
+        // svc gains its lifecycle (fixing an error) and an API appears.
+        let api = "---\napiVersion: backstage.io/v1alpha1\nkind: API\nmetadata:\n  name: api\nspec:\n  type: openapi\n  lifecycle: production\n  owner: team-a\n  definition: x\n";
+        fs::write(&file, format!("{group}{svc}  lifecycle: production\n{api}")).unwrap();
+        app.reload_paths(std::slice::from_ref(&file));
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Reloaded: 1 added, 1 modified; 0 new errors, 1 fixed")
+        );
+        let change = |app: &App, reference: &str| {
+            let ews = app
+                .catalog
+                .entities
+                .iter()
+                .find(|e| e.entity.ref_key() == reference)
+                .unwrap();
+            app.change_of(ews)
+        };
+        assert_eq!(change(&app, "api:default/api"), Some(Change::Added));
+        assert_eq!(
+            change(&app, "component:default/svc"),
+            Some(Change::Modified)
+        );
+        assert_eq!(change(&app, "group:default/team-a"), None);
+
+        // Marks add up across reloads until acknowledged; removal drops them.
+        fs::write(&file, group).unwrap();
+        app.reload();
+        assert_eq!(app.status.as_deref(), Some("Reloaded: 2 removed"));
+        assert!(app.changes.is_empty());
+        fs::write(&file, format!("{group}{api}")).unwrap();
+        app.reload();
+        assert_eq!(app.changes.len(), 1);
+        app.perform(Action::AcknowledgeChanges, "", 10).unwrap();
+        assert!(app.changes.is_empty());
+    }
+
     #[test]
     fn collapse_all_resets_to_categories() {
         let mut app = test_app();
@@ -1001,6 +2059,116 @@ mod tests {
         );
     }
 
+    #[test]
+    fn edit_request_targets_selected_entity_line() {
+        let mut app = test_app();
+        // A category is selected initially: nothing to edit.
+        app.request_edit();
+        assert!(app.take_edit_request().is_none());
+
+        app.expand_all();
+        assert!(select_next_entity(&mut app), "select an entity");
+        let ews = app.selected_entity().unwrap();
+        let expected = (ews.source_file.clone(), ews.line);
+        assert!(expected.1 > 1, "entity is not the first line of the file");
+
+        app.request_edit();
+        assert_eq!(app.take_edit_request(), Some(expected));
+        assert!(app.take_edit_request().is_none(), "request is consumed");
+    }
+
+    #[test]
+    fn entities_loaded_from_an_api_are_read_only() {
+        let mut app = test_app();
+        app.api_url = Some("http://localhost:7007/api/catalog".to_string());
+        assert!(app.select_entity_by_ref("component:default/payment-gateway"));
+
+        app.start_edit();
+        assert!(app.edit_form.is_none());
+        assert_eq!(app.status.as_deref(), Some(READ_ONLY));
+        app.status = None;
+        app.request_edit();
+        assert!(app.take_edit_request().is_none());
+        assert_eq!(app.status.as_deref(), Some(READ_ONLY));
+    }
+
+    #[test]
+    fn edit_writes_field_back_and_reloads() {
+        let dir = TempDir::new("edit-test");
+        dir.disable_cache();
+        let file = dir.join("catalog-info.yaml");
+        fs::write(
+            &file,
+            "# owned by payments\n---\napiVersion: backstage.io/v1alpha1\nkind: Group\nmetadata:\n  name: team-b\nspec:\n  type: team\n  children: []\n---\napiVersion: backstage.io/v1alpha1\nkind: Component\nmetadata:\n  name: svc\nspec:\n  type: service # k8s\n  owner: team-a\n  lifecycle: production\n",
+        )
+        .unwrap();
+
+        let mut app = App::new(&dir).expect("load temp catalog");
+        assert!(app.select_entity_by_ref("component:default/svc"));
+        app.start_edit();
+        assert!(app.is_detail_focused());
+
+        // Owner is the first field; open it, complete from the index, save.
+        app.edit_enter();
+        let form = app.edit_form.as_mut().unwrap();
+        form.input = Some(String::new());
+        form.typed = "team-b".to_string();
+        form.complete(&app.catalog.index);
+        app.edit_enter();
+
+        let content = fs::read_to_string(&file).unwrap();
+        assert!(content.contains("  type: service # k8s\n  owner: team-b\n"));
+        assert!(content.starts_with("# owned by payments\n"));
+        let ews = app.selected_entity().expect("selection kept after reload");
+        assert_eq!(ews.entity.owner().as_deref(), Some("team-b"));
+        let form = app.edit_form.as_ref().unwrap();
+        assert!(!form.is_editing());
+        assert_eq!(form.message.as_deref(), Some("Saved owner"));
+
+        app.close_edit();
+        assert!(app.edit_form.is_none());
+    }
+
+    #[test]
+    fn new_entity_wizard_writes_and_selects_the_entity() {
+        let dir = TempDir::new("new-test");
+        dir.disable_cache();
+        let file = dir.join("catalog-info.yaml");
+        fs::write(
+            &file,
+            "apiVersion: backstage.io/v1alpha1\nkind: Group\nmetadata:\n  name: team-a\nspec:\n  type: team\n  children: []\n",
+        )
+        .unwrap();
+
+        let mut app = App::new(&dir).expect("load temp catalog");
+        app.start_new_entity();
+        assert!(matches!(app.input_mode(), InputMode::NewEntity));
+
+        // Kind: Domain (typed, then completed), name, owner: first choice,
+        // file: the existing catalog file via Tab.
+        for c in "dom".chars() {
+            app.new_wizard.as_mut().unwrap().input_char(c);
+        }
+        app.new_entity_complete();
+        app.new_entity_enter();
+        for c in "payments".chars() {
+            app.new_wizard.as_mut().unwrap().input_char(c);
+        }
+        app.new_entity_enter();
+        app.new_entity_enter();
+        assert_eq!(app.new_wizard.as_ref().unwrap().draft.owner, "team-a");
+        app.new_entity_complete();
+        app.new_entity_enter();
+
+        assert!(app.new_wizard.is_none(), "wizard closes after writing");
+        let content = fs::read_to_string(&file).unwrap();
+        assert!(content
+            .contains("children: []\n---\napiVersion: backstage.io/v1alpha1\nkind: Domain\n"));
+        let ews = app.selected_entity().expect("new entity selected");
+        assert_eq!(ews.entity.ref_key(), "domain:default/payments");
+        assert!(ews.validation_errors.is_empty());
+    }
+
     #[test]
     fn toggle_help_flips_flag() {
         let mut app = test_app();
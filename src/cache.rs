@@ -0,0 +1,282 @@
+//! On-disk cache of parsed and validated catalog files.
+//!
+//! Validating every entity against the JSON Schema dominates load time, yet
+//! between two runs most files are unchanged. A [`Cache`] keeps, per catalog
+//! file, the hash of its content with the entities parsed from it, their
+//! validation errors and the file's parse warnings; a file whose content
+//! still hashes the same is not parsed again. Entries only hold for the
+//! [`CACHE_FORMAT`], bsv version and embedded schema that wrote them — any
+//! other cache is discarded.
+//!
+//! Each catalog root has its own cache file in `$XDG_CACHE_HOME/bsv/` (or
+//! `~/.cache/bsv/`), named after the root's path. The `[cache]` configuration
+//! table can move the directory or turn caching off:
+//!
+//! ```toml
+//! [cache]
+//! enabled = false
+//! dir = "/tmp/bsv-cache"
+//! ```
+
+use crate::config::CacheConfig;
+use crate::entity::{Entity, EntityWithSource, ValidationError};
+use crate::validator::SCHEMA_STR;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Cached results for one catalog file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedFile {
+    hash: String,
+    entities: Vec<CachedEntity>,
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedEntity {
+    entity: Entity,
+    line: usize,
+    validation_errors: Vec<ValidationError>,
+}
+
+/// The cache file's contents.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheData {
+    /// [`cache_key`] of the bsv that wrote the entries.
+    key: String,
+    files: HashMap<PathBuf, CachedFile>,
+}
+
+/// Parsed and validated catalog files, by path and content hash.
+#[derive(Debug)]
+pub struct Cache {
+    path: PathBuf,
+    data: CacheData,
+    /// Whether entries changed since the cache was read.
+    dirty: bool,
+}
+
+impl Cache {
+    /// The cache for the catalog at `root`, or `None` if `config` turns
+    /// caching off or there is no cache directory.
+    pub fn open(root: &Path, config: &CacheConfig) -> Option<Self> {
+        if config.enabled == Some(false) {
+            return None;
+        }
+        let dir = config.dir.clone().or_else(default_cache_dir)?;
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let name = blake3::hash(root.to_string_lossy().as_bytes()).to_hex();
+        Some(Self::at(dir.join(format!("{}.json", &name[..16]))))
+    }
+
+    /// The cache stored at `path`, empty if it doesn't exist, can't be read
+    /// or was written by another bsv version or schema.
+    pub fn at(path: PathBuf) -> Self {
+        let data = fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<CacheData>(&bytes).ok())
+            .filter(|data| data.key == cache_key())
+            .unwrap_or_else(|| CacheData {
+                key: cache_key(),
+                files: HashMap::new(),
+            });
+        Self {
+            path,
+            data,
+            dirty: false,
+        }
+    }
+
+    /// Where the cache is stored.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The entities and warnings cached for the file at `path`, if its
+    /// content still has `hash` (see [`content_hash`]).
+    pub fn get(&self, path: &Path, hash: &str) -> Option<(Vec<EntityWithSource>, Vec<String>)> {
+        let cached = self.data.files.get(path).filter(|c| c.hash == hash)?;
+        let entities = cached
+            .entities
+            .iter()
+            .map(|c| {
+                EntityWithSource::new(c.entity.clone(), path.to_path_buf())
+                    .with_line(c.line)
+                    .with_validation_errors(c.validation_errors.clone())
+            })
+            .collect();
+        Some((entities, cached.warnings.clone()))
+    }
+
+    /// Record what parsing the file at `path`, whose content has `hash`,
+    /// produced. Entities that can't be stored faithfully (e.g. with
+    /// non-string mapping keys) leave the file uncached.
+    pub fn insert(
+        &mut self,
+        path: &Path,
+        hash: String,
+        entities: &[EntityWithSource],
+        warnings: &[String],
+    ) {
+        let cached = CachedFile {
+            hash,
+            entities: entities
+                .iter()
+                .map(|e| CachedEntity {
+                    entity: e.entity.clone(),
+                    line: e.line,
+                    validation_errors: e.validation_errors.clone(),
+                })
+                .collect(),
+            warnings: warnings.to_vec(),
+        };
+        if serde_json::to_vec(&cached).is_err() {
+            self.dirty |= self.data.files.remove(path).is_some();
+            return;
+        }
+        self.data.files.insert(path.to_path_buf(), cached);
+        self.dirty = true;
+    }
+
+    /// Forget the file at `path` (deleted).
+    pub fn remove(&mut self, path: &Path) {
+        self.dirty |= self.data.files.remove(path).is_some();
+    }
+
+    /// Forget the files not in `paths` (deleted, or no longer discovered).
+    pub fn retain(&mut self, paths: &[PathBuf]) {
+        let keep: HashSet<&PathBuf> = paths.iter().collect();
+        let before = self.data.files.len();
+        self.data.files.retain(|path, _| keep.contains(path));
+        self.dirty |= self.data.files.len() != before;
+    }
+
+    /// Write the cache if it changed, creating its directory if needed.
+    pub fn save(&mut self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let json = serde_json::to_vec(&self.data)?;
+        // Write then rename, so a concurrent run never reads half a cache.
+        let partial = self
+            .path
+            .with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&partial, json)
+            .and_then(|()| fs::rename(&partial, &self.path))
+            .with_context(|| format!("Failed to write cache {}", self.path.display()))?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+/// The hash of a catalog file's content that cache entries are keyed by.
+pub fn content_hash(content: &str) -> String {
+    blake3::hash(content.as_bytes()).to_hex().to_string()
+}
+
+/// Version of what the cache stores: bump it whenever the shape of the
+/// entries or the parsing and validation that fill them changes, so caches
+/// written before the change are discarded even within one bsv version.
+pub const CACHE_FORMAT: u32 = 1;
+
+/// Identifies what produced cache entries: the [`CACHE_FORMAT`], the bsv
+/// version (parsing rules) and the embedded schema (validation).
+fn cache_key() -> String {
+    let schema = blake3::hash(SCHEMA_STR.as_bytes()).to_hex();
+    format!(
+        "{CACHE_FORMAT} {} {}",
+        env!("CARGO_PKG_VERSION"),
+        &schema[..16]
+    )
+}
+
+/// The user's cache directory for bsv, if a cache or home directory is known.
+pub fn default_cache_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))?;
+    Some(base.join("bsv"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{load_catalog_with, Discovery};
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn unchanged_files_load_from_the_cache() {
+        let dir = TempDir::new("cache");
+        let root = dir.join("catalog");
+        fs::create_dir_all(root.join("b")).unwrap();
+        let a = root.join("catalog-info.yaml");
+        let b = root.join("b").join("catalog-info.yaml");
+        fs::write(&a, "apiVersion: backstage.io/v1alpha1\nkind: Component\nmetadata:\n  name: svc\nspec:\n  owner: team-a\n").unwrap();
+        fs::write(&b, "apiVersion: backstage.io/v1alpha1\nkind: Group\nmetadata:\n  name: team-a\nspec:\n  type: team\n  children: []\n---\nkind: 42\n").unwrap();
+        let cache_path = dir.join("cache").join("catalog.json");
+
+        let mut cache = Cache::at(cache_path.clone());
+        let (fresh, fresh_warnings) =
+            load_catalog_with(&root, &Discovery::default(), Some(&mut cache)).unwrap();
+        assert!(cache_path.is_file());
+
+        // A cache read back serves the same entities, errors and warnings.
+        let cache = Cache::at(cache_path.clone());
+        let hash = content_hash(&fs::read_to_string(&a).unwrap());
+        let (cached, _) = cache.get(&a, &hash).unwrap();
+        assert_eq!(cached[0].entity.metadata.name, "svc");
+        assert!(!cached[0].validation_errors.is_empty(), "svc lacks a type");
+        assert!(cache.get(&a, "stale").is_none());
+
+        let mut cache = Cache::at(cache_path.clone());
+        let (loaded, warnings) =
+            load_catalog_with(&root, &Discovery::default(), Some(&mut cache)).unwrap();
+        assert_eq!(warnings, fresh_warnings);
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        let summary = |entities: &[EntityWithSource]| {
+            entities
+                .iter()
+                .map(|e| {
+                    (
+                        e.entity.ref_key(),
+                        e.line,
+                        e.validation_errors.len(),
+                        e.relations.len(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(summary(&loaded), summary(&fresh));
+
+        // Deleted files drop out; a cache from another version is ignored.
+        fs::remove_file(&b).unwrap();
+        let mut cache = Cache::at(cache_path.clone());
+        load_catalog_with(&root, &Discovery::default(), Some(&mut cache)).unwrap();
+        let cache = Cache::at(cache_path.clone());
+        assert_eq!(cache.data.files.len(), 1);
+        assert!(Cache::at(cache_path.clone()).get(&a, &hash).is_some());
+        let written_by = |key: &str| {
+            let mut data: serde_json::Value =
+                serde_json::from_slice(&fs::read(&cache_path).unwrap()).unwrap();
+            data["key"] = key.into();
+            fs::write(&cache_path, data.to_string()).unwrap();
+            Cache::at(cache_path.clone())
+        };
+        assert!(written_by("0.0.0 old").get(&a, &hash).is_none());
+        let older_format = cache_key().replacen(
+            &format!("{CACHE_FORMAT} "),
+            &format!("{} ", CACHE_FORMAT - 1),
+            1,
+        );
+        assert!(written_by(&older_format).get(&a, &hash).is_none());
+    }
+}
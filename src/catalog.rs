@@ -0,0 +1,644 @@
+//! A catalog loaded file by file and kept up to date as files change.
+//!
+//! [`LoadedCatalog`] holds every entity in one [`EntityStore`], grouped by
+//! file in discovery order, along with the [`EntityIndex`] of their refs. It
+//! also remembers where each ref is declared and what each entity's
+//! references link it to, so [`LoadedCatalog::update`] can swap in the
+//! entities of the files that changed and relate only the entities those
+//! files touch, instead of reading and relating the whole catalog again.
+
+use crate::cache::{content_hash, Cache};
+use crate::diff::CatalogDiff;
+use crate::entity::{EntityIndex, EntityStore, EntityWithSource};
+use crate::parser::{
+    discover_catalog_files_with, parse_multi_document_yaml, Discovery, IGNORE_FILE,
+};
+use crate::relations::{Links, Relation};
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Where an entity sits: its file and its position among the file's entities.
+type Location = (PathBuf, usize);
+
+/// One file's share of a [`LoadedCatalog`].
+#[derive(Debug, Default)]
+struct CatalogFile {
+    /// Where the file's entities start in the store.
+    start: usize,
+    /// What each of the file's entities links to, in order.
+    links: Vec<Links>,
+    /// The warnings reading the file produced.
+    warnings: Vec<String>,
+}
+
+/// A catalog loaded file by file, so it can be brought up to date by reading
+/// again only the files that changed (see [`LoadedCatalog::update`]).
+#[derive(Debug, Default)]
+pub struct LoadedCatalog {
+    /// Every entity, grouped by file in discovery order, with duplicates and
+    /// relations marked.
+    pub entities: EntityStore,
+    /// The refs of [`Self::entities`].
+    pub index: EntityIndex,
+    /// Warnings not about any one file (failing to save the cache).
+    pub warnings: Vec<String>,
+    /// The catalog's files, in discovery order. Every discovered file has an
+    /// entry, even one without entities.
+    files: BTreeMap<PathBuf, CatalogFile>,
+    /// Where each ref is declared.
+    declared: HashMap<String, Vec<Location>>,
+    /// The reverse relations each ref is given by references to it, with
+    /// repeats, whether or not the ref is declared.
+    incoming: HashMap<String, Vec<Relation>>,
+    /// The entities whose references consulted each ref (see
+    /// [`Links::consulted`]).
+    consulting: HashMap<String, Vec<Location>>,
+}
+
+impl LoadedCatalog {
+    /// Load the catalog at `root`, a directory or a single file, as
+    /// [`crate::parser::load_catalog_with`] does.
+    pub fn load(root: &Path, discovery: &Discovery, cache: Option<&mut Cache>) -> Result<Self> {
+        let mut catalog = Self::default();
+        if root.is_file() {
+            catalog.reread_root(root)?;
+        } else {
+            catalog.refresh(root, discovery, cache, |_| true);
+        }
+        Ok(catalog)
+    }
+
+    /// A catalog of `entities` that were not read from catalog files (e.g.
+    /// fetched from a catalog API), grouped by their sources.
+    pub fn from_entities(entities: Vec<EntityWithSource>, warnings: Vec<String>) -> Self {
+        let mut by_source: BTreeMap<PathBuf, Option<LoadedFile>> = BTreeMap::new();
+        for ews in entities {
+            let file = by_source
+                .entry(ews.source_file.clone())
+                .or_insert_with(|| Some(LoadedFile::default()));
+            if let Some(file) = file {
+                file.entities.push(ews);
+            }
+        }
+        let mut catalog = Self::default();
+        catalog.replace(by_source);
+        catalog.warnings = warnings;
+        catalog
+    }
+
+    /// Bring the catalog loaded from `root` up to date after the files in
+    /// `changed` (as reported by a file watcher) were created, modified or
+    /// deleted, and say what that changed.
+    ///
+    /// Changed catalog files are read again and deleted ones dropped; the
+    /// other files' entities are kept as they are, and only the entities that
+    /// declare, reference or share a ref with the changed ones are related
+    /// again. Discovery runs again only when a new catalog file, a directory
+    /// or an ignore file is reported, as those can change which files belong
+    /// to the catalog.
+    ///
+    /// The store is updated in place when nothing else holds it; drop other
+    /// handles to it (such as an [`crate::tree::EntityTree`] built from it)
+    /// first, or it is copied.
+    pub fn update(
+        &mut self,
+        root: &Path,
+        discovery: &Discovery,
+        mut cache: Option<&mut Cache>,
+        changed: &[PathBuf],
+    ) -> Result<CatalogDiff> {
+        if !root.is_dir() {
+            return self.reread_root(root);
+        }
+        let mut reread: BTreeSet<PathBuf> = BTreeSet::new();
+        let mut gone: BTreeSet<PathBuf> = BTreeSet::new();
+        let mut rediscover = false;
+        for path in changed.iter().filter_map(|path| under_root(root, path)) {
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            let is_ignore_file = path
+                .file_name()
+                .is_some_and(|name| name == ".gitignore" || name == IGNORE_FILE);
+            if self.files.contains_key(&path) {
+                if path.is_file() {
+                    reread.insert(path);
+                } else {
+                    gone.insert(path);
+                }
+            } else if is_ignore_file
+                || path.is_dir()
+                || (path.is_file() && discovery.is_catalog_file(relative))
+            {
+                rediscover = true;
+            } else if !path.exists() {
+                // A deleted directory takes its catalog files with it.
+                gone.extend(
+                    self.files
+                        .keys()
+                        .filter(|file| file.starts_with(&path))
+                        .cloned(),
+                );
+            }
+        }
+        if rediscover {
+            return Ok(self.refresh(root, discovery, cache, |path| reread.contains(path)));
+        }
+        if reread.is_empty() && gone.is_empty() {
+            return Ok(CatalogDiff::default());
+        }
+
+        let mut replaced = read_files(reread, cache.as_deref_mut());
+        replaced.extend(gone.into_iter().map(|path| (path, None)));
+        self.warnings.clear();
+        if let Some(cache) = cache {
+            for (path, file) in &replaced {
+                if file.is_none() {
+                    cache.remove(path);
+                }
+            }
+            self.save_cache(cache);
+        }
+        Ok(self.replace(replaced))
+    }
+
+    /// Every warning: each file's, in path order, then the others.
+    pub fn all_warnings(&self) -> Vec<String> {
+        self.files
+            .values()
+            .flat_map(|file| &file.warnings)
+            .chain(&self.warnings)
+            .cloned()
+            .collect()
+    }
+
+    /// The catalog's entities, copied only if something else still holds
+    /// the store.
+    pub fn into_entities(self) -> Vec<EntityWithSource> {
+        Rc::try_unwrap(self.entities).unwrap_or_else(|shared| (*shared).clone())
+    }
+
+    /// Read the single catalog file `root` again.
+    fn reread_root(&mut self, root: &Path) -> Result<CatalogDiff> {
+        let content = fs::read_to_string(root)
+            .with_context(|| format!("Failed to read file: {}", root.display()))?;
+        let mut file = LoadedFile::default();
+        file.entities = parse_multi_document_yaml(&content, root, &mut file.warnings);
+        Ok(self.replace(BTreeMap::from([(root.to_path_buf(), Some(file))])))
+    }
+
+    /// Discover the catalog files under the directory `root`, reading those
+    /// not loaded yet and those `reread` picks, dropping those no longer
+    /// discovered, and keeping the rest.
+    fn refresh(
+        &mut self,
+        root: &Path,
+        discovery: &Discovery,
+        mut cache: Option<&mut Cache>,
+        reread: impl Fn(&Path) -> bool,
+    ) -> CatalogDiff {
+        let files = discover_catalog_files_with(root, discovery);
+        let to_read = files
+            .iter()
+            .filter(|path| !self.files.contains_key(*path) || reread(path))
+            .cloned()
+            .collect();
+        let mut replaced = read_files(to_read, cache.as_deref_mut());
+        // Discovery returns the files sorted.
+        replaced.extend(
+            self.files
+                .keys()
+                .filter(|path| files.binary_search(path).is_err())
+                .map(|path| (path.clone(), None)),
+        );
+        self.warnings.clear();
+        if let Some(cache) = cache {
+            cache.retain(&files);
+            self.save_cache(cache);
+        }
+        self.replace(replaced)
+    }
+
+    fn save_cache(&mut self, cache: &mut Cache) {
+        if let Err(e) = cache.save() {
+            self.warnings.push(format!("{e:#}"));
+        }
+    }
+
+    /// Put the entities of each file in `replaced` in place of the file's
+    /// current ones (`None` drops the file), then relate the entities that
+    /// affects. Unchanged files' entities are moved, never copied.
+    fn replace(&mut self, replaced: BTreeMap<PathBuf, Option<LoadedFile>>) -> CatalogDiff {
+        // Refs whose declarations change, with whether they were declared.
+        let mut touched: HashMap<String, bool> = HashMap::new();
+        let old_store = std::mem::take(Rc::make_mut(&mut self.entities));
+        let mut old_entities = old_store.into_iter();
+        let mut store = Vec::new();
+        let mut removed = Vec::new();
+        let mut added: Vec<Location> = Vec::new();
+
+        // Both maps are in path order, as is the store: merge them.
+        let mut old_files = std::mem::take(&mut self.files).into_iter().peekable();
+        let mut replaced = replaced.into_iter().peekable();
+        loop {
+            let keep = match (old_files.peek(), replaced.peek()) {
+                (None, None) => break,
+                (Some((old, _)), Some((new, _))) => old < new,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+            };
+            if keep {
+                let Some((path, mut file)) = old_files.next() else {
+                    break;
+                };
+                file.start = store.len();
+                store.extend(old_entities.by_ref().take(file.links.len()));
+                self.files.insert(path, file);
+                continue;
+            }
+            let Some((path, loaded)) = replaced.next() else {
+                break;
+            };
+            if old_files.peek().is_some_and(|(old, _)| *old == path) {
+                if let Some((_, previous)) = old_files.next() {
+                    let entities = old_entities.by_ref().take(previous.links.len());
+                    for (offset, (ews, links)) in entities.zip(previous.links).enumerate() {
+                        let key = ews.entity.ref_key();
+                        touched
+                            .entry(key.clone())
+                            .or_insert_with(|| self.index.contains_key(&key));
+                        self.index.remove(&key);
+                        remove_location(&mut self.declared, &key, &path, offset);
+                        // Its targets lose the reverse relations it gave them.
+                        for (target, _) in &links.reverse {
+                            touched
+                                .entry(target.clone())
+                                .or_insert_with(|| self.index.contains_key(target));
+                        }
+                        self.withdraw(&path, offset, links);
+                        removed.push(ews);
+                    }
+                }
+            }
+            let Some(loaded) = loaded else {
+                continue;
+            };
+            let start = store.len();
+            for (offset, ews) in loaded.entities.iter().enumerate() {
+                let key = ews.entity.ref_key();
+                touched
+                    .entry(key.clone())
+                    .or_insert_with(|| self.index.contains_key(&key));
+                self.index.insert(key.clone());
+                self.declared
+                    .entry(key)
+                    .or_default()
+                    .push((path.clone(), offset));
+                added.push((path.clone(), offset));
+            }
+            let file = CatalogFile {
+                start,
+                links: vec![Links::default(); loaded.entities.len()],
+                warnings: loaded.warnings,
+            };
+            store.extend(loaded.entities);
+            self.files.insert(path, file);
+        }
+
+        // Link the new entities, and those whose references may resolve
+        // elsewhere now that a ref they consulted came or went.
+        let mut relink = added.clone();
+        let mut relinked: HashSet<&Location> = added.iter().collect();
+        for (key, was_declared) in &touched {
+            if *was_declared != self.index.contains_key(key) {
+                for location in self.consulting.get(key).into_iter().flatten() {
+                    if relinked.insert(location) {
+                        relink.push(location.clone());
+                    }
+                }
+            }
+        }
+        let mut refresh: HashSet<String> = touched.into_keys().collect();
+        for (path, offset) in relink {
+            let Some(file) = self.files.get_mut(&path) else {
+                continue;
+            };
+            let previous = std::mem::take(&mut file.links[offset]);
+            let ews = &store[file.start + offset];
+            let links = Links::of(ews, &self.index);
+            refresh.insert(ews.entity.ref_key());
+            refresh.extend(previous.reverse.iter().map(|(target, _)| target.clone()));
+            self.withdraw(&path, offset, previous);
+            for (target, relation) in &links.reverse {
+                self.incoming
+                    .entry(target.clone())
+                    .or_default()
+                    .push(relation.clone());
+                refresh.insert(target.clone());
+            }
+            for key in &links.consulted {
+                self.consulting
+                    .entry(key.clone())
+                    .or_default()
+                    .push((path.clone(), offset));
+            }
+            if let Some(file) = self.files.get_mut(&path) {
+                file.links[offset] = links;
+            }
+        }
+
+        for key in &refresh {
+            self.relate(&mut store, key);
+        }
+        let new: Vec<&EntityWithSource> = added
+            .iter()
+            .filter_map(|(path, offset)| {
+                let file = self.files.get(path)?;
+                store.get(file.start + offset)
+            })
+            .collect();
+        let diff = CatalogDiff::replacing(&removed, &new, &self.index);
+        self.entities = Rc::new(store);
+        diff
+    }
+
+    /// Take back the reverse relations and lookups `links` made for the
+    /// entity at `offset` in `path`.
+    fn withdraw(&mut self, path: &Path, offset: usize, links: Links) {
+        for (target, relation) in links.reverse {
+            if let Some(relations) = self.incoming.get_mut(&target) {
+                if let Some(i) = relations.iter().position(|r| *r == relation) {
+                    relations.swap_remove(i);
+                }
+                if relations.is_empty() {
+                    self.incoming.remove(&target);
+                }
+            }
+        }
+        for key in links.consulted {
+            remove_location(&mut self.consulting, &key, path, offset);
+        }
+    }
+
+    /// Set the relations and duplicates of the entities declaring `key`.
+    fn relate(&self, store: &mut [EntityWithSource], key: &str) {
+        let Some(locations) = self.declared.get(key) else {
+            return;
+        };
+        // Each declaration's position in the store with its links, in
+        // catalog order.
+        let mut declarations: Vec<(usize, &Links)> = locations
+            .iter()
+            .filter_map(|(path, offset)| {
+                let file = self.files.get(path)?;
+                Some((file.start + offset, file.links.get(*offset)?))
+            })
+            .collect();
+        declarations.sort_unstable_by_key(|&(i, _)| i);
+        let incoming = self.incoming.get(key).map_or(&[][..], Vec::as_slice);
+        for &(i, links) in &declarations {
+            let mut relations: Vec<Relation> =
+                links.forward.iter().chain(incoming).cloned().collect();
+            relations.sort();
+            relations.dedup();
+            let duplicates = declarations
+                .iter()
+                .filter(|&&(j, _)| j != i)
+                .map(|&(j, _)| (store[j].source_file.clone(), store[j].line))
+                .collect();
+            store[i].relations = relations;
+            store[i].duplicates = duplicates;
+        }
+    }
+}
+
+/// Remove the location `(path, offset)` from those of `key`.
+fn remove_location(
+    map: &mut HashMap<String, Vec<Location>>,
+    key: &str,
+    path: &Path,
+    offset: usize,
+) {
+    if let Some(locations) = map.get_mut(key) {
+        locations.retain(|(p, o)| !(p == path && *o == offset));
+        if locations.is_empty() {
+            map.remove(key);
+        }
+    }
+}
+
+/// `path`, reported by a file watcher (typically absolute), as discovery names
+/// it: joined onto `root` as given. `None` if it is not under `root`.
+fn under_root(root: &Path, path: &Path) -> Option<PathBuf> {
+    let candidates = [
+        Some(root.to_path_buf()),
+        std::path::absolute(root).ok(),
+        root.canonicalize().ok(),
+    ];
+    candidates
+        .into_iter()
+        .flatten()
+        .find_map(|base| path.strip_prefix(&base).ok().map(|rel| root.join(rel)))
+}
+
+/// What loading one catalog file produced.
+#[derive(Debug, Default)]
+struct LoadedFile {
+    entities: Vec<EntityWithSource>,
+    warnings: Vec<String>,
+    /// The content hash, when the file was parsed rather than taken from the
+    /// cache (so the cache should learn it).
+    parsed_hash: Option<String>,
+}
+
+/// Read `paths` in parallel, from `cache` where their content is unchanged,
+/// teaching the cache the files that had to be parsed.
+fn read_files(
+    paths: BTreeSet<PathBuf>,
+    mut cache: Option<&mut Cache>,
+) -> BTreeMap<PathBuf, Option<LoadedFile>> {
+    let paths: Vec<PathBuf> = paths.into_iter().collect();
+    let cached = cache.as_deref();
+    let loaded: Vec<LoadedFile> = paths
+        .par_iter()
+        .map(|path| load_file(path, cached))
+        .collect();
+    paths
+        .into_iter()
+        .zip(loaded)
+        .map(|(path, mut loaded)| {
+            if let (Some(cache), Some(hash)) = (cache.as_deref_mut(), loaded.parsed_hash.take()) {
+                cache.insert(&path, hash, &loaded.entities, &loaded.warnings);
+            }
+            (path, Some(loaded))
+        })
+        .collect()
+}
+
+/// Read one catalog file, from `cache` when its content is unchanged.
+fn load_file(path: &Path, cache: Option<&Cache>) -> LoadedFile {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            return LoadedFile {
+                warnings: vec![format!("Failed to read {}: {e}", path.display())],
+                ..LoadedFile::default()
+            }
+        }
+    };
+    let hash = cache.map(|_| content_hash(&content));
+    if let (Some(cache), Some(hash)) = (cache, &hash) {
+        if let Some((entities, warnings)) = cache.get(path, hash) {
+            return LoadedFile {
+                entities,
+                warnings,
+                parsed_hash: None,
+            };
+        }
+    }
+    let mut warnings = Vec::new();
+    let entities = parse_multi_document_yaml(&content, path, &mut warnings);
+    LoadedFile {
+        entities,
+        warnings,
+        parsed_hash: hash,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn update_rereads_changed_files() {
+        let root = TempDir::new("update");
+        let entity = |kind: &str, name: &str| {
+            format!("apiVersion: backstage.io/v1alpha1\nkind: {kind}\nmetadata:\n  name: {name}\nspec:\n  type: service\n  lifecycle: production\n  owner: team-a\n")
+        };
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write("a/catalog-info.yaml", &entity("Component", "svc-a"));
+        write("b/catalog-info.yaml", &entity("Component", "svc-b"));
+        write("c/catalog-info.yaml", "kind: 42\n");
+        let mut catalog = LoadedCatalog::load(&root, &Discovery::default(), None).unwrap();
+        let names = |catalog: &LoadedCatalog| {
+            catalog
+                .entities
+                .iter()
+                .map(|e| e.entity.metadata.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&catalog), ["svc-a", "svc-b"]);
+        assert_eq!(catalog.all_warnings().len(), 1);
+
+        // An edit reported for `b` is read; an unreported edit to `a` is not.
+        write("a/catalog-info.yaml", &entity("Component", "stale"));
+        write("b/catalog-info.yaml", &entity("Component", "svc-a"));
+        // New and deleted files are picked up too.
+        write("d/catalog-info.yaml", &entity("Component", "svc-d"));
+        fs::remove_file(root.join("c/catalog-info.yaml")).unwrap();
+        let changed = ["b", "c", "d"]
+            .map(|dir| std::path::absolute(root.join(dir).join("catalog-info.yaml")).unwrap());
+        let diff = catalog
+            .update(&root, &Discovery::default(), None, &changed)
+            .unwrap();
+        assert_eq!(names(&catalog), ["svc-a", "svc-a", "svc-d"]);
+        assert_eq!(diff.added, ["component:default/svc-d"]);
+        assert_eq!(diff.removed, ["component:default/svc-b"]);
+        assert!(catalog.all_warnings().is_empty());
+        assert_eq!(catalog.entities[0].duplicates.len(), 1);
+
+        // Duplicates and relations are recomputed across unchanged files.
+        write("b/catalog-info.yaml", &entity("Group", "team-a"));
+        let changed = [root.join("b/catalog-info.yaml")];
+        catalog
+            .update(&root, &Discovery::default(), None, &changed)
+            .unwrap();
+        assert!(catalog.entities[0].duplicates.is_empty());
+        assert!(catalog.entities[0]
+            .relations
+            .iter()
+            .any(|r| r.relation_type == "ownedBy" && r.target_ref == "group:default/team-a"));
+    }
+
+    #[test]
+    fn update_keeps_unchanged_entities_and_relates_like_a_full_load() {
+        let root = TempDir::new("update-links");
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        let component = |name: &str, depends_on: &str| {
+            format!("apiVersion: backstage.io/v1alpha1\nkind: Component\nmetadata:\n  name: {name}\nspec:\n  type: service\n  lifecycle: production\n  owner: team-a\n  dependsOn: [{depends_on}]\n")
+        };
+        let group = "apiVersion: backstage.io/v1alpha1\nkind: Group\nmetadata:\n  name: team-a\nspec:\n  type: team\n  children: []\n";
+        let resource = "apiVersion: backstage.io/v1alpha1\nkind: Resource\nmetadata:\n  name: db\nspec:\n  type: database\n  owner: team-a\n";
+        write("a/catalog-info.yaml", &component("web", "db"));
+        write("b/catalog-info.yaml", &component("api", "web"));
+        write("c/catalog-info.yaml", group);
+        let discovery = Discovery::default();
+        let mut catalog = LoadedCatalog::load(&root, &discovery, None).unwrap();
+        let buffers = |catalog: &LoadedCatalog| {
+            catalog
+                .entities
+                .iter()
+                .filter(|e| !e.source_file.starts_with(root.join("c")))
+                .map(|e| e.entity.metadata.name.as_ptr())
+                .collect::<Vec<_>>()
+        };
+        let unchanged = buffers(&catalog);
+
+        // `c` gains the resource `web` depends on and a duplicate of `api`;
+        // `a` and `b` are not read again, yet their relations follow.
+        write(
+            "c/catalog-info.yaml",
+            &format!("{group}---\n{resource}---\n{}", component("api", "")),
+        );
+        let diff = catalog
+            .update(&root, &discovery, None, &[root.join("c/catalog-info.yaml")])
+            .unwrap();
+        assert_eq!(diff.added, ["resource:default/db"]);
+        assert_eq!(
+            buffers(&catalog),
+            unchanged,
+            "entities were re-parsed or copied"
+        );
+
+        let fresh = LoadedCatalog::load(&root, &discovery, None).unwrap();
+        let summary = |catalog: &LoadedCatalog| {
+            catalog
+                .entities
+                .iter()
+                .map(|e| {
+                    (
+                        e.entity.ref_key(),
+                        e.relations.clone(),
+                        e.duplicates.clone(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(summary(&catalog), summary(&fresh));
+        assert!(catalog.entities[0]
+            .relations
+            .iter()
+            .any(|r| r.relation_type == "dependsOn" && r.target_ref == "resource:default/db"));
+        assert_eq!(catalog.entities[1].duplicates.len(), 1);
+
+        // Taking it away again restores the original relations.
+        write("c/catalog-info.yaml", group);
+        catalog
+            .update(&root, &discovery, None, &[root.join("c/catalog-info.yaml")])
+            .unwrap();
+        let fresh = LoadedCatalog::load(&root, &discovery, None).unwrap();
+        assert_eq!(summary(&catalog), summary(&fresh));
+        assert_eq!(catalog.index.count("component:default/api"), 1);
+    }
+}
@@ -2,9 +2,18 @@
 //!
 //! Kept separate from `main.rs` so the parsing logic is unit-testable without a
 //! terminal. The interactive TUI is the default; `--validate` and `--json`
-//! provide non-interactive modes suitable for CI.
+//! provide non-interactive modes suitable for CI (`--validate --watch` keeps
+//! validating as files change); `new` scaffolds an entity,
+//! `fmt` normalizes catalog files, `fix` repairs common problems, `serve`
+//! exposes the catalog over a Backstage-compatible HTTP API, `query` searches it,
+//! `lsp` runs a language server for editors and `config show` prints the
+//! effective configuration.
 
+use crate::formatter::RefStyle;
+use crate::server::DEFAULT_PORT;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::str::FromStr;
 
 /// A parsed invocation of the `bsv` binary.
 #[derive(Debug, PartialEq, Eq)]
@@ -16,21 +25,118 @@ pub enum Command {
     /// Launch the interactive TUI for the given path (or the default).
     Run(Option<PathBuf>),
     /// Validate the catalog and print a report; exit non-zero on errors.
-    Validate(Option<PathBuf>),
+    /// With `watch`, validate again and print a fresh report on every change
+    /// instead of exiting.
+    Validate { path: Option<PathBuf>, watch: bool },
     /// Print the parsed catalog as JSON.
     Json(Option<PathBuf>),
+    /// Walk through the new-entity wizard on stdin/stdout.
+    New(Option<PathBuf>),
+    /// Propose fixes for common problems; apply them all or one by one.
+    Fix {
+        path: Option<PathBuf>,
+        mode: FixMode,
+    },
+    /// Rewrite catalog files into canonical form, or with `check`, only
+    /// report the files that would change.
+    Fmt {
+        path: Option<PathBuf>,
+        check: bool,
+        /// `None` when not given, leaving it to the configuration.
+        refs: Option<RefStyle>,
+    },
+    /// Serve the catalog over the Backstage catalog REST API.
+    Serve { path: Option<PathBuf>, port: u16 },
+    /// Print the entities matching a query (see [`crate::query`]).
+    Query {
+        expr: String,
+        path: Option<PathBuf>,
+        /// `None` when not given, leaving it to the configuration.
+        format: Option<OutputFormat>,
+    },
+    /// Run the language server over stdio.
+    Lsp,
+    /// Print the configuration in effect for the catalog at the path (see
+    /// [`crate::config`]).
+    ConfigShow(Option<PathBuf>),
+    /// Work on the catalog served by a Backstage catalog API instead of
+    /// local files.
+    FromApi { url: String, view: ApiView },
     /// An unrecognized option was supplied.
     Unknown(String),
 }
 
+/// What to do with a catalog fetched with `--from-api`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiView {
+    /// Browse it in the TUI.
+    Tui,
+    /// Validate it, as `--validate` does for files.
+    Validate,
+    /// Print it as JSON, as `--json` does for files.
+    Json,
+}
+
+/// How `bsv query` prints its results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Aligned columns, one entity per row.
+    #[default]
+    Table,
+    /// The same JSON as `--json`.
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            other => Err(format!(
+                "unknown output format '{other}' (expected table or json)"
+            )),
+        }
+    }
+}
+
+/// What `bsv fix` does with the fixes it finds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FixMode {
+    /// List the proposed fixes without changing any file.
+    #[default]
+    DryRun,
+    /// Apply every proposed fix (`--apply`).
+    Apply,
+    /// Ask before applying each fix (`--interactive`).
+    Interactive,
+}
+
 /// Parse process arguments (including the program name at index 0).
 pub fn parse_args(args: &[String]) -> Command {
     let mut rest = args.iter().skip(1);
     match rest.next().map(String::as_str) {
         Some("-h" | "--help") => Command::Help,
         Some("-V" | "--version") => Command::Version,
-        Some("--validate") => Command::Validate(rest.next().map(PathBuf::from)),
-        Some("--json") => Command::Json(rest.next().map(PathBuf::from)),
+        Some("--from-api") => from_api(rest.next(), ApiView::Tui),
+        Some("--validate") => parse_validate(rest),
+        Some("--json") => match rest.next().map(String::as_str) {
+            Some("--from-api") => from_api(rest.next(), ApiView::Json),
+            path => Command::Json(path.map(PathBuf::from)),
+        },
+        Some("new") => Command::New(rest.next().map(PathBuf::from)),
+        Some("fmt") => parse_fmt(rest),
+        Some("fix") => parse_fix(rest),
+        Some("serve") => parse_serve(rest),
+        Some("query") => parse_query(rest),
+        Some("lsp") => Command::Lsp,
+        Some("config") => match rest.next().map(String::as_str) {
+            Some("show") => Command::ConfigShow(rest.next().map(PathBuf::from)),
+            Some(other) => Command::Unknown(format!("config {other}")),
+            None => Command::Unknown("config (missing subcommand)".to_string()),
+        },
         // Any other leading-dash token is an unknown option, not a path.
         Some(opt) if opt.starts_with('-') => Command::Unknown(opt.to_string()),
         Some(path) => Command::Run(Some(PathBuf::from(path))),
@@ -38,6 +144,135 @@ pub fn parse_args(args: &[String]) -> Command {
     }
 }
 
+/// `--from-api URL`; the URL is required.
+fn from_api(url: Option<&String>, view: ApiView) -> Command {
+    match url {
+        Some(url) if !url.starts_with('-') => Command::FromApi {
+            url: url.clone(),
+            view,
+        },
+        _ => Command::Unknown("--from-api (missing URL)".to_string()),
+    }
+}
+
+/// Parse what follows `--validate`: `--from-api URL` or `[--watch] [PATH]`.
+fn parse_validate<'a>(mut rest: impl Iterator<Item = &'a String>) -> Command {
+    let mut path = None;
+    let mut watch = false;
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--from-api" if path.is_none() && !watch => {
+                return from_api(rest.next(), ApiView::Validate)
+            }
+            "-w" | "--watch" => watch = true,
+            opt if opt.starts_with('-') => return Command::Unknown(arg.clone()),
+            _ if path.is_none() => path = Some(PathBuf::from(arg)),
+            _ => return Command::Unknown(arg.clone()),
+        }
+    }
+    Command::Validate { path, watch }
+}
+
+/// Parse `fix [--apply | --interactive] [PATH]`.
+fn parse_fix<'a>(rest: impl Iterator<Item = &'a String>) -> Command {
+    let mut path = None;
+    let mut mode = FixMode::DryRun;
+    for arg in rest {
+        match arg.as_str() {
+            "--apply" => mode = FixMode::Apply,
+            "-i" | "--interactive" => mode = FixMode::Interactive,
+            opt if opt.starts_with('-') => return Command::Unknown(arg.clone()),
+            _ if path.is_none() => path = Some(PathBuf::from(arg)),
+            _ => return Command::Unknown(arg.clone()),
+        }
+    }
+    Command::Fix { path, mode }
+}
+
+/// Parse `serve [--port N] [PATH]`.
+fn parse_serve<'a>(mut rest: impl Iterator<Item = &'a String>) -> Command {
+    let mut path = None;
+    let mut port = DEFAULT_PORT;
+    while let Some(arg) = rest.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag, Some(value.to_string())),
+            None => (arg.as_str(), None),
+        };
+        match flag {
+            "-p" | "--port" => {
+                let value = inline_value.or_else(|| rest.next().cloned());
+                match value.as_deref().map(str::parse) {
+                    Some(Ok(p)) => port = p,
+                    _ => return Command::Unknown(format!("--port {}", value.unwrap_or_default())),
+                }
+            }
+            opt if opt.starts_with('-') => return Command::Unknown(arg.clone()),
+            _ if path.is_none() => path = Some(PathBuf::from(arg)),
+            _ => return Command::Unknown(arg.clone()),
+        }
+    }
+    Command::Serve { path, port }
+}
+
+/// Parse `query EXPR [--format table|json] [PATH]`.
+fn parse_query<'a>(mut rest: impl Iterator<Item = &'a String>) -> Command {
+    let mut expr = None;
+    let mut path = None;
+    let mut format = None;
+    while let Some(arg) = rest.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with('-') => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        match flag {
+            "-f" | "--format" => {
+                let value = inline_value.or_else(|| rest.next().cloned());
+                match value.as_deref().map(str::parse) {
+                    Some(Ok(f)) => format = Some(f),
+                    _ => {
+                        return Command::Unknown(format!("--format {}", value.unwrap_or_default()))
+                    }
+                }
+            }
+            opt if opt.starts_with('-') => return Command::Unknown(arg.clone()),
+            _ if expr.is_none() => expr = Some(arg.clone()),
+            _ if path.is_none() => path = Some(PathBuf::from(arg)),
+            _ => return Command::Unknown(arg.clone()),
+        }
+    }
+    match expr {
+        Some(expr) => Command::Query { expr, path, format },
+        None => Command::Unknown("query (missing expression)".to_string()),
+    }
+}
+
+/// Parse `fmt [--check] [--refs keep|full|minimal] [PATH]`.
+fn parse_fmt<'a>(mut rest: impl Iterator<Item = &'a String>) -> Command {
+    let mut path = None;
+    let mut check = false;
+    let mut refs = None;
+    while let Some(arg) = rest.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag, Some(value.to_string())),
+            None => (arg.as_str(), None),
+        };
+        match flag {
+            "--check" => check = true,
+            "--refs" => {
+                let value = inline_value.or_else(|| rest.next().cloned());
+                match value.as_deref().map(str::parse) {
+                    Some(Ok(style)) => refs = Some(style),
+                    _ => return Command::Unknown(format!("--refs {}", value.unwrap_or_default())),
+                }
+            }
+            opt if opt.starts_with('-') => return Command::Unknown(arg.clone()),
+            _ if path.is_none() => path = Some(PathBuf::from(arg)),
+            _ => return Command::Unknown(arg.clone()),
+        }
+    }
+    Command::Fmt { path, check, refs }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -72,10 +307,19 @@ mod tests {
 
     #[test]
     fn validate_and_json_take_optional_path() {
-        assert_eq!(parse_args(&args(&["--validate"])), Command::Validate(None));
+        assert_eq!(
+            parse_args(&args(&["--validate"])),
+            Command::Validate {
+                path: None,
+                watch: false
+            }
+        );
         assert_eq!(
             parse_args(&args(&["--validate", "dir"])),
-            Command::Validate(Some(PathBuf::from("dir")))
+            Command::Validate {
+                path: Some(PathBuf::from("dir")),
+                watch: false
+            }
         );
         assert_eq!(parse_args(&args(&["--json"])), Command::Json(None));
         assert_eq!(
@@ -84,6 +328,206 @@ mod tests {
         );
     }
 
+    #[test]
+    fn validate_watch_flag() {
+        assert_eq!(
+            parse_args(&args(&["--validate", "--watch", "dir"])),
+            Command::Validate {
+                path: Some(PathBuf::from("dir")),
+                watch: true
+            }
+        );
+        assert_eq!(
+            parse_args(&args(&["--validate", "dir", "-w"])),
+            Command::Validate {
+                path: Some(PathBuf::from("dir")),
+                watch: true
+            }
+        );
+        assert_eq!(
+            parse_args(&args(&["--validate", "--watch", "--from-api", "http://x"])),
+            Command::Unknown("--from-api".to_string())
+        );
+        assert_eq!(
+            parse_args(&args(&["--validate", "--nope"])),
+            Command::Unknown("--nope".to_string())
+        );
+    }
+
+    #[test]
+    fn new_subcommand_takes_optional_path() {
+        assert_eq!(parse_args(&args(&["new"])), Command::New(None));
+        assert_eq!(
+            parse_args(&args(&["new", "catalog"])),
+            Command::New(Some(PathBuf::from("catalog")))
+        );
+    }
+
+    #[test]
+    fn fmt_subcommand_flags() {
+        assert_eq!(
+            parse_args(&args(&["fmt"])),
+            Command::Fmt {
+                path: None,
+                check: false,
+                refs: None
+            }
+        );
+        assert_eq!(
+            parse_args(&args(&["fmt", "--check", "--refs", "full", "dir"])),
+            Command::Fmt {
+                path: Some(PathBuf::from("dir")),
+                check: true,
+                refs: Some(RefStyle::Full)
+            }
+        );
+        assert_eq!(
+            parse_args(&args(&["fmt", "dir", "--refs=minimal"])),
+            Command::Fmt {
+                path: Some(PathBuf::from("dir")),
+                check: false,
+                refs: Some(RefStyle::Minimal)
+            }
+        );
+        assert_eq!(
+            parse_args(&args(&["fmt", "--refs", "short"])),
+            Command::Unknown("--refs short".to_string())
+        );
+        assert_eq!(
+            parse_args(&args(&["fmt", "--nope"])),
+            Command::Unknown("--nope".to_string())
+        );
+    }
+
+    #[test]
+    fn fix_subcommand_modes() {
+        assert_eq!(
+            parse_args(&args(&["fix"])),
+            Command::Fix {
+                path: None,
+                mode: FixMode::DryRun
+            }
+        );
+        assert_eq!(
+            parse_args(&args(&["fix", "--apply", "dir"])),
+            Command::Fix {
+                path: Some(PathBuf::from("dir")),
+                mode: FixMode::Apply
+            }
+        );
+        assert_eq!(
+            parse_args(&args(&["fix", "-i"])),
+            Command::Fix {
+                path: None,
+                mode: FixMode::Interactive
+            }
+        );
+    }
+
+    #[test]
+    fn from_api_with_each_view() {
+        let url = "http://localhost:7007/api/catalog";
+        assert_eq!(
+            parse_args(&args(&["--from-api", url])),
+            Command::FromApi {
+                url: url.to_string(),
+                view: ApiView::Tui
+            }
+        );
+        assert_eq!(
+            parse_args(&args(&["--validate", "--from-api", url])),
+            Command::FromApi {
+                url: url.to_string(),
+                view: ApiView::Validate
+            }
+        );
+        assert_eq!(
+            parse_args(&args(&["--json", "--from-api", url])),
+            Command::FromApi {
+                url: url.to_string(),
+                view: ApiView::Json
+            }
+        );
+        assert!(matches!(
+            parse_args(&args(&["--from-api"])),
+            Command::Unknown(_)
+        ));
+    }
+
+    #[test]
+    fn serve_subcommand_port() {
+        assert_eq!(
+            parse_args(&args(&["serve"])),
+            Command::Serve {
+                path: None,
+                port: DEFAULT_PORT
+            }
+        );
+        assert_eq!(
+            parse_args(&args(&["serve", "--port", "8080", "dir"])),
+            Command::Serve {
+                path: Some(PathBuf::from("dir")),
+                port: 8080
+            }
+        );
+        assert_eq!(
+            parse_args(&args(&["serve", "--port=70000"])),
+            Command::Unknown("--port 70000".to_string())
+        );
+    }
+
+    #[test]
+    fn query_subcommand() {
+        assert_eq!(
+            parse_args(&args(&["query", "kind:api owner=team"])),
+            Command::Query {
+                expr: "kind:api owner=team".to_string(),
+                path: None,
+                format: None,
+            }
+        );
+        assert_eq!(
+            parse_args(&args(&[
+                "query",
+                "spec.type=service",
+                "--format",
+                "json",
+                "dir"
+            ])),
+            Command::Query {
+                expr: "spec.type=service".to_string(),
+                path: Some(PathBuf::from("dir")),
+                format: Some(OutputFormat::Json),
+            }
+        );
+        assert_eq!(
+            parse_args(&args(&["query", "web", "--format=yaml"])),
+            Command::Unknown("--format yaml".to_string())
+        );
+        assert!(matches!(parse_args(&args(&["query"])), Command::Unknown(_)));
+    }
+
+    #[test]
+    fn lsp_subcommand() {
+        assert_eq!(parse_args(&args(&["lsp"])), Command::Lsp);
+    }
+
+    #[test]
+    fn config_show_subcommand() {
+        assert_eq!(
+            parse_args(&args(&["config", "show"])),
+            Command::ConfigShow(None)
+        );
+        assert_eq!(
+            parse_args(&args(&["config", "show", "dir"])),
+            Command::ConfigShow(Some(PathBuf::from("dir")))
+        );
+        assert_eq!(
+            parse_args(&args(&["config", "edit"])),
+            Command::Unknown("config edit".to_string())
+        );
+    }
+
     #[test]
     fn unknown_option_is_flagged() {
         assert_eq!(
@@ -0,0 +1,393 @@
+//! Configuration files: catalog discovery, lint rules, output defaults, theme,
+//! key bindings, caching and saved searches.
+//!
+//! Configuration is read from two TOML files, both optional:
+//!
+//! - the project's `.bsv.toml`, the nearest one in the catalog directory or
+//!   any directory above it
+//! - the user's `config.toml` in `$XDG_CONFIG_HOME/bsv/` (or `~/.config/bsv/`)
+//!
+//! The user's file is applied on top of the project's: its settings win, and
+//! lists (globs, file names, saved searches) are appended.
+//!
+//! ```toml
+//! [discovery]
+//! include = ["catalog/*.yaml", "*.catalog.yaml"]
+//! exclude = ["examples", "legacy/**"]
+//! filenames = ["catalog.yaml"]
+//!
+//! [lint]
+//! broken-refs = "warn"
+//! duplicates = "off"
+//!
+//! [output]
+//! format = "json"
+//! refs = "full"
+//!
+//! [theme]
+//! selected = "magenta"
+//! border = "#5f87af"
+//!
+//! [[searches]]
+//! name = "My team's production services"
+//! query = "owner:team-a lifecycle=production spec.type=service"
+//! ```
+//!
+//! Saved searches are [`crate::query`] expressions; the TUI lists them in a
+//! picker and applies one as a persistent filter. The `[keys]` table picks a
+//! key-binding preset and rebinds actions (see [`crate::keymap`]).
+//! `bsv config show` prints the merged result with defaults filled in.
+
+use crate::cache;
+use crate::cli::OutputFormat;
+use crate::formatter::RefStyle;
+use crate::ui::theme;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// File name of the per-project configuration.
+pub const PROJECT_CONFIG: &str = ".bsv.toml";
+
+/// A named query.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+}
+
+/// Settings merged from the configuration files.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Config {
+    /// Which files are catalog files; see [`crate::parser::Discovery`].
+    #[serde(default)]
+    pub discovery: DiscoveryConfig,
+    /// Severity of each `--validate` check.
+    #[serde(default)]
+    pub lint: LintConfig,
+    /// Defaults for command-line output flags.
+    #[serde(default)]
+    pub output: OutputConfig,
+    /// Style name (e.g. `selected`, `border`) to color; see
+    /// [`crate::ui::theme`].
+    #[serde(default)]
+    pub theme: BTreeMap<String, String>,
+    /// Key bindings; the user's file overrides the project's.
+    #[serde(default)]
+    pub keys: KeysConfig,
+    /// The parsed-catalog cache; see [`crate::cache`].
+    #[serde(default)]
+    pub cache: CacheConfig,
+    /// Project searches first, then the user's.
+    #[serde(default)]
+    pub searches: Vec<SavedSearch>,
+}
+
+/// The `[discovery]` table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiscoveryConfig {
+    /// Globs for more catalog files, e.g. `catalog/*.yaml` or
+    /// `*.catalog.yaml`.
+    pub include: Vec<String>,
+    /// Globs for directories and files to skip.
+    pub exclude: Vec<String>,
+    /// File names read as catalog files besides `catalog-info.yaml`/`.yml`.
+    pub filenames: Vec<String>,
+}
+
+/// How seriously `--validate` takes a kind of problem.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Not reported.
+    Off,
+    /// Reported without failing the run.
+    Warn,
+    /// Reported, and the run fails.
+    #[default]
+    Error,
+}
+
+/// The `[lint]` table: a [`Severity`] per check, `error` when unset.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct LintConfig {
+    /// Entities that don't match the catalog JSON Schema.
+    pub schema: Option<Severity>,
+    /// References to entities that aren't in the catalog.
+    pub broken_refs: Option<Severity>,
+    /// Entities declared more than once.
+    pub duplicates: Option<Severity>,
+}
+
+/// The `[output]` table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    /// `bsv query --format` when not given.
+    pub format: Option<OutputFormat>,
+    /// `bsv fmt --refs` when not given.
+    pub refs: Option<RefStyle>,
+}
+
+/// The `[cache]` table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    /// Whether to cache; on unless set to `false`.
+    pub enabled: Option<bool>,
+    /// Where cache files go instead of the user's cache directory.
+    pub dir: Option<PathBuf>,
+}
+
+/// The `[keys]` table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeysConfig {
+    /// `default`, `vim` or `emacs`.
+    pub preset: Option<String>,
+    /// Per input mode, action (palette command name) to the keys that run it,
+    /// replacing the preset's keys for that action.
+    pub normal: BTreeMap<String, KeyList>,
+    pub search: BTreeMap<String, KeyList>,
+    pub docs: BTreeMap<String, KeyList>,
+}
+
+impl KeysConfig {
+    /// Apply `other` on top of these settings.
+    fn merge(&mut self, other: KeysConfig) {
+        if other.preset.is_some() {
+            self.preset = other.preset;
+        }
+        self.normal.extend(other.normal);
+        self.search.extend(other.search);
+        self.docs.extend(other.docs);
+    }
+}
+
+/// One key (`"q"`) or several (`["q", "Ctrl-c"]`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyList {
+    /// The key names, however many were given.
+    pub fn iter(&self) -> impl Iterator<Item = &String> {
+        match self {
+            KeyList::One(key) => std::slice::from_ref(key).iter(),
+            KeyList::Many(keys) => keys.iter(),
+        }
+    }
+}
+
+impl Config {
+    /// Read one configuration file.
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Invalid config in {}", path.display()))
+    }
+
+    /// Load the project configuration for the catalog at `root` and the
+    /// user's, with a warning for each file that could not be read.
+    pub fn load(root: &Path) -> (Self, Vec<String>) {
+        let mut config = Self::default();
+        let mut warnings = Vec::new();
+        for path in config_files(root) {
+            match Self::read(&path) {
+                Ok(file) => config.merge(file),
+                Err(e) => warnings.push(format!("{e:#}")),
+            }
+        }
+        (config, warnings)
+    }
+
+    /// Apply `other` on top of these settings.
+    fn merge(&mut self, other: Config) {
+        self.discovery.include.extend(other.discovery.include);
+        self.discovery.exclude.extend(other.discovery.exclude);
+        self.discovery.filenames.extend(other.discovery.filenames);
+        let lint = other.lint;
+        self.lint.schema = lint.schema.or(self.lint.schema);
+        self.lint.broken_refs = lint.broken_refs.or(self.lint.broken_refs);
+        self.lint.duplicates = lint.duplicates.or(self.lint.duplicates);
+        self.output.format = other.output.format.or(self.output.format);
+        self.output.refs = other.output.refs.or(self.output.refs);
+        self.theme.extend(other.theme);
+        self.keys.merge(other.keys);
+        self.cache.enabled = other.cache.enabled.or(self.cache.enabled);
+        self.cache.dir = other.cache.dir.or(self.cache.dir.take());
+        self.searches.extend(other.searches);
+    }
+
+    /// These settings with every default spelled out, as `bsv config show`
+    /// prints them.
+    pub fn effective(mut self) -> Self {
+        self.lint.schema.get_or_insert_default();
+        self.lint.broken_refs.get_or_insert_default();
+        self.lint.duplicates.get_or_insert_default();
+        self.output.format.get_or_insert_default();
+        self.output.refs.get_or_insert_default();
+        for (name, color) in theme::DEFAULT_COLORS {
+            self.theme
+                .entry(name.to_string())
+                .or_insert_with(|| color.to_string().to_lowercase());
+        }
+        self.keys
+            .preset
+            .get_or_insert_with(|| "default".to_string());
+        self.cache.enabled.get_or_insert(true);
+        if self.cache.dir.is_none() {
+            self.cache.dir = cache::default_cache_dir();
+        }
+        self
+    }
+
+    /// The settings as TOML.
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string(self).context("Failed to write the configuration as TOML")
+    }
+}
+
+/// The configuration files that apply to the catalog at `root`, in the order
+/// they are applied: the project's, then the user's.
+pub fn config_files(root: &Path) -> Vec<PathBuf> {
+    [project_config_path(root), user_config_path()]
+        .into_iter()
+        .flatten()
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// The nearest project configuration file for the catalog at `root` (a
+/// directory, or a catalog file whose directory is used), looking in that
+/// directory and then each one above it.
+pub fn project_config_path(root: &Path) -> Option<PathBuf> {
+    let dir = if root.is_file() {
+        root.parent().unwrap_or(Path::new("."))
+    } else {
+        root
+    };
+    // Relative paths such as `.` have no parents to walk up to.
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    dir.ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG))
+        .find(|path| path.is_file())
+}
+
+/// The user's configuration file, if a config or home directory is known.
+pub fn user_config_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))?;
+    Some(base.join("bsv").join("config.toml"))
+}
+
+/// Append a saved search to the configuration file at `path`, creating it
+/// (and its directory) if needed. Existing content is left untouched.
+pub fn append_search(path: &Path, search: &SavedSearch) -> Result<()> {
+    #[derive(Serialize)]
+    struct Entry<'a> {
+        searches: [&'a SavedSearch; 1],
+    }
+    let entry = toml::to_string(&Entry { searches: [search] })?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    let existing = fs::read_to_string(path).unwrap_or_default();
+    let separator = match existing.as_str() {
+        "" => "",
+        s if s.ends_with("\n\n") => "",
+        s if s.ends_with('\n') => "\n",
+        _ => "\n\n",
+    };
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    write!(file, "{separator}{entry}")
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn appended_searches_read_back_after_existing_content() {
+        let dir = TempDir::new("config");
+        let path = dir.join(PROJECT_CONFIG);
+        fs::write(
+            &path,
+            "# team searches\n[[searches]]\nname = \"APIs\"\nquery = \"kind:api\"\n",
+        )
+        .unwrap();
+
+        let mine = SavedSearch {
+            name: "My team's \"prod\" services".to_string(),
+            query: "owner:team-a lifecycle=production".to_string(),
+        };
+        append_search(&path, &mine).unwrap();
+
+        let config = Config::read(&path).unwrap();
+        assert_eq!(config.searches.len(), 2);
+        assert_eq!(config.searches[0].name, "APIs");
+        assert_eq!(config.searches[1], mine);
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .starts_with("# team searches\n"));
+
+        fs::write(&path, "searches = 3").unwrap();
+        assert!(Config::read(&path).is_err());
+    }
+
+    #[test]
+    fn project_config_is_found_above_the_root_and_merged_under_the_user_s() {
+        let dir = TempDir::new("config-walk");
+        let root = dir.join("services").join("payments");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            dir.join(PROJECT_CONFIG),
+            "[discovery]\nexclude = [\"examples\"]\n[lint]\nschema = \"warn\"\nduplicates = \"off\"\n[output]\nformat = \"json\"\n",
+        )
+        .unwrap();
+        assert_eq!(project_config_path(&root), Some(dir.join(PROJECT_CONFIG)));
+
+        let mut config = Config::read(&dir.join(PROJECT_CONFIG)).unwrap();
+        config.merge(
+            toml::from_str(
+                "[discovery]\nexclude = [\"legacy\"]\n[lint]\nschema = \"error\"\n[output]\nrefs = \"full\"\n",
+            )
+            .unwrap(),
+        );
+        assert_eq!(config.discovery.exclude, ["examples", "legacy"]);
+        assert_eq!(config.lint.schema, Some(Severity::Error));
+        assert_eq!(config.lint.duplicates, Some(Severity::Off));
+        assert_eq!(config.output.format, Some(OutputFormat::Json));
+        assert_eq!(config.output.refs, Some(RefStyle::Full));
+
+        // The effective configuration spells out every default and reads back.
+        let effective = config.effective();
+        assert_eq!(effective.lint.broken_refs, Some(Severity::Error));
+        assert_eq!(effective.keys.preset.as_deref(), Some("default"));
+        assert_eq!(effective.theme["dimmed"], "darkgray");
+        assert!(theme::Theme::from_config(&effective.theme).1.is_empty());
+        let shown: Config = toml::from_str(&effective.to_toml().unwrap()).unwrap();
+        assert_eq!(shown, effective);
+
+        assert!(toml::from_str::<Config>("[lint]\nschema = \"loud\"\n").is_err());
+    }
+}
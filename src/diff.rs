@@ -0,0 +1,242 @@
+//! What changed between two loads of a catalog.
+//!
+//! [`CatalogDiff::between`] compares entities by their canonical ref: refs
+//! only in the new load were added, refs only in the old one were removed, and
+//! refs in both whose declarations differ were modified. Validation errors are
+//! compared too, so a reload can say how many errors it introduced and fixed.
+//! [`CatalogDiff::replacing`] works the same out from just the entities of the
+//! files a reload read again.
+
+use crate::entity::{Entity, EntityIndex, EntityWithSource};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// How a reload changed an entity that is still in the catalog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Added,
+    Modified,
+}
+
+/// The differences between two loads of a catalog.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CatalogDiff {
+    /// Refs only in the new catalog, sorted.
+    pub added: Vec<String>,
+    /// Refs only in the old catalog, sorted.
+    pub removed: Vec<String>,
+    /// Refs in both whose declarations differ, sorted.
+    pub modified: Vec<String>,
+    /// Validation errors in the new catalog that the old one did not have.
+    pub errors_introduced: usize,
+    /// Validation errors in the old catalog that are gone from the new one.
+    pub errors_fixed: usize,
+}
+
+impl CatalogDiff {
+    /// Compare the entities of an `old` and a `new` load.
+    ///
+    /// A ref declared more than once counts as modified when any of its
+    /// declarations changed. Where entities sit in their files is ignored, so
+    /// moving one is not a change.
+    pub fn between(old: &[EntityWithSource], new: &[EntityWithSource]) -> Self {
+        let old_declarations = declarations(old);
+        let new_declarations = declarations(new);
+        let mut diff = Self::default();
+        for (key, declared) in &new_declarations {
+            match old_declarations.get(key) {
+                None => diff.added.push(key.clone()),
+                Some(before) if before != declared => diff.modified.push(key.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = old_declarations
+            .keys()
+            .filter(|key| !new_declarations.contains_key(*key))
+            .cloned()
+            .collect();
+
+        diff.count_errors(old, new);
+        diff
+    }
+
+    /// Compare a catalog before and after the entities in `removed` were
+    /// replaced by those in `added`, where `index` is the catalog's index
+    /// afterwards. Only the refs of those entities are looked at.
+    pub fn replacing(
+        removed: &[EntityWithSource],
+        added: &[&EntityWithSource],
+        index: &EntityIndex,
+    ) -> Self {
+        let old_declarations = declarations(removed);
+        let new_declarations = declarations(added.iter().copied());
+        let keys: BTreeSet<&String> = old_declarations
+            .keys()
+            .chain(new_declarations.keys())
+            .collect();
+        let mut diff = Self::default();
+        for key in keys {
+            let before = old_declarations.get(key);
+            let after = new_declarations.get(key);
+            let now = index.count(key);
+            // Declarations outside the replaced entities are there both times.
+            let kept = now.saturating_sub(after.map_or(0, Vec::len));
+            let was = kept + before.map_or(0, Vec::len);
+            if was == 0 && now > 0 {
+                diff.added.push(key.clone());
+            } else if now == 0 && was > 0 {
+                diff.removed.push(key.clone());
+            } else if before != after {
+                diff.modified.push(key.clone());
+            }
+        }
+        diff.count_errors(removed, added.iter().copied());
+        diff
+    }
+
+    fn count_errors<'a>(
+        &mut self,
+        old: impl IntoIterator<Item = &'a EntityWithSource>,
+        new: impl IntoIterator<Item = &'a EntityWithSource>,
+    ) {
+        let old_errors = errors(old);
+        let new_errors = errors(new);
+        self.errors_introduced = surplus(&new_errors, &old_errors);
+        self.errors_fixed = surplus(&old_errors, &new_errors);
+    }
+
+    /// Whether nothing changed.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The added and modified refs, with how each changed.
+    pub fn changes(&self) -> impl Iterator<Item = (&str, Change)> {
+        let added = self.added.iter().map(|r| (r.as_str(), Change::Added));
+        let modified = self.modified.iter().map(|r| (r.as_str(), Change::Modified));
+        added.chain(modified)
+    }
+
+    /// One line such as `2 added, 1 modified; 1 new error, 3 fixed`.
+    pub fn summary(&self) -> String {
+        let counts = [
+            (self.added.len(), "added"),
+            (self.modified.len(), "modified"),
+            (self.removed.len(), "removed"),
+        ];
+        let mut parts: Vec<String> = counts
+            .iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, what)| format!("{count} {what}"))
+            .collect();
+        if parts.is_empty() {
+            parts.push("no entities changed".to_string());
+        }
+        let mut summary = parts.join(", ");
+        if self.errors_introduced > 0 || self.errors_fixed > 0 {
+            let noun = if self.errors_introduced == 1 {
+                "error"
+            } else {
+                "errors"
+            };
+            summary.push_str(&format!(
+                "; {} new {noun}, {} fixed",
+                self.errors_introduced, self.errors_fixed
+            ));
+        }
+        summary
+    }
+}
+
+/// Each ref's declarations (several when it is duplicated), in catalog order.
+fn declarations<'a>(
+    entities: impl IntoIterator<Item = &'a EntityWithSource>,
+) -> BTreeMap<String, Vec<&'a Entity>> {
+    let mut declarations: BTreeMap<String, Vec<&Entity>> = BTreeMap::new();
+    for ews in entities {
+        declarations
+            .entry(ews.entity.ref_key())
+            .or_default()
+            .push(&ews.entity);
+    }
+    declarations
+}
+
+/// Each validation error as `(entity ref, path, message)`, with how many
+/// times it occurs.
+fn errors<'a>(
+    entities: impl IntoIterator<Item = &'a EntityWithSource>,
+) -> HashMap<(String, &'a str, &'a str), usize> {
+    let mut errors = HashMap::new();
+    for ews in entities {
+        for error in &ews.validation_errors {
+            let key = (
+                ews.entity.ref_key(),
+                error.path.as_str(),
+                error.message.as_str(),
+            );
+            *errors.entry(key).or_insert(0) += 1;
+        }
+    }
+    errors
+}
+
+/// How many of the errors in `a` are not in `b`, counting repeats.
+fn surplus(
+    a: &HashMap<(String, &str, &str), usize>,
+    b: &HashMap<(String, &str, &str), usize>,
+) -> usize {
+    a.iter()
+        .map(|(key, count)| count.saturating_sub(b.get(key).copied().unwrap_or(0)))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_documents;
+    use std::path::Path;
+
+    fn load(yaml: &str) -> Vec<EntityWithSource> {
+        parse_documents(yaml, Path::new("catalog-info.yaml")).0
+    }
+
+    const GROUP: &str =
+        "apiVersion: backstage.io/v1alpha1\nkind: Group\nmetadata:\n  name: team-a\nspec:\n  type: team\n  children: []\n";
+
+    #[test]
+    fn added_removed_and_modified_entities_and_errors() {
+        let old = load(&format!(
+            "{GROUP}---\napiVersion: backstage.io/v1alpha1\nkind: Component\nmetadata:\n  name: svc\nspec:\n  owner: team-a\n---\napiVersion: backstage.io/v1alpha1\nkind: System\nmetadata:\n  name: gone\nspec:\n  owner: team-a\n"
+        ));
+        // `svc` gains its type and lifecycle (fixing errors); `api` is new and
+        // lacks its spec fields.
+        let new = load(&format!(
+            "\n\n{GROUP}---\napiVersion: backstage.io/v1alpha1\nkind: Component\nmetadata:\n  name: svc\nspec:\n  type: service\n  lifecycle: production\n  owner: team-a\n---\napiVersion: backstage.io/v1alpha1\nkind: API\nmetadata:\n  name: api\nspec:\n  owner: team-a\n"
+        ));
+        let diff = CatalogDiff::between(&old, &new);
+        assert_eq!(diff.added, ["api:default/api"]);
+        assert_eq!(diff.removed, ["system:default/gone"]);
+        assert_eq!(
+            diff.modified,
+            ["component:default/svc"],
+            "the moved group is unchanged"
+        );
+        assert!(diff.errors_introduced > 0);
+        assert!(diff.errors_fixed > 0);
+        let changes: Vec<_> = diff.changes().collect();
+        assert_eq!(
+            changes,
+            [
+                ("api:default/api", Change::Added),
+                ("component:default/svc", Change::Modified)
+            ]
+        );
+        assert!(diff
+            .summary()
+            .starts_with("1 added, 1 modified, 1 removed; "));
+
+        let same = CatalogDiff::between(&new, &new);
+        assert!(same.is_empty());
+        assert_eq!(same.summary(), "no entities changed");
+    }
+}
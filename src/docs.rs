@@ -97,6 +97,15 @@ impl DocsBrowser {
         }
     }
 
+    /// Jump to the bottom of the open file, or to the last file in the list.
+    pub fn last(&mut self, visible_height: usize) {
+        if let Some(content) = &self.viewing_content {
+            self.scroll_offset = content.lines.len().saturating_sub(visible_height);
+        } else {
+            self.selected_index = self.files.len().saturating_sub(1);
+        }
+    }
+
     pub fn open_selected(&mut self) {
         if self.viewing_content.is_some() {
             return;
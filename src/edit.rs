@@ -0,0 +1,372 @@
+//! In-TUI editing of common entity fields.
+//!
+//! [`EditField`] lists the fields that can be edited from the details panel and
+//! knows where each lives in the YAML (`section.key`), how to show its current
+//! value as a single line of text, how to turn edited text back into a
+//! [`FieldValue`], and which completions to offer. [`EditForm`] holds the
+//! form's interaction state; writing the change back is done by
+//! [`App::apply_edit`](crate::app::App::apply_edit) via [`crate::yaml_edit`].
+//!
+//! Text forms:
+//!
+//! - scalars (owner, lifecycle, system, description) are edited as-is; an empty
+//!   value removes the key
+//! - tags are comma-separated: `java, backend`
+//! - links are `;`-separated `title | url` pairs (the title is optional)
+
+use crate::entity::{Entity, EntityIndex, Link};
+use crate::yaml_edit::FieldValue;
+
+/// Lifecycle values offered for completion (Backstage's well-known stages).
+pub const LIFECYCLES: &[&str] = &["experimental", "production", "deprecated"];
+
+/// A field that can be edited from the details panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditField {
+    Owner,
+    Lifecycle,
+    System,
+    Tags,
+    Description,
+    Links,
+}
+
+impl EditField {
+    /// Every editable field, in form order.
+    pub const ALL: [EditField; 6] = [
+        EditField::Owner,
+        EditField::Lifecycle,
+        EditField::System,
+        EditField::Tags,
+        EditField::Description,
+        EditField::Links,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            EditField::Owner => "Owner",
+            EditField::Lifecycle => "Lifecycle",
+            EditField::System => "System",
+            EditField::Tags => "Tags",
+            EditField::Description => "Description",
+            EditField::Links => "Links",
+        }
+    }
+
+    /// Where the field lives in the entity document: `(section, key)`.
+    pub fn location(self) -> (&'static str, &'static str) {
+        match self {
+            EditField::Owner => ("spec", "owner"),
+            EditField::Lifecycle => ("spec", "lifecycle"),
+            EditField::System => ("spec", "system"),
+            EditField::Tags => ("metadata", "tags"),
+            EditField::Description => ("metadata", "description"),
+            EditField::Links => ("metadata", "links"),
+        }
+    }
+
+    /// The field's current value in its editable text form.
+    pub fn current_text(self, entity: &Entity) -> String {
+        match self {
+            EditField::Owner => entity.owner().unwrap_or_default(),
+            EditField::Lifecycle => entity.lifecycle().unwrap_or_default(),
+            EditField::System => entity.system().unwrap_or_default(),
+            EditField::Tags => entity.metadata.tags.join(", "),
+            EditField::Description => entity.metadata.description.clone().unwrap_or_default(),
+            EditField::Links => entity
+                .metadata
+                .links
+                .iter()
+                .map(|l| {
+                    let url = l.url.as_deref().unwrap_or("");
+                    match &l.title {
+                        Some(title) => format!("{title} | {url}"),
+                        None => url.to_string(),
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("; "),
+        }
+    }
+
+    /// Parse edited text into the value to write. Links keep the `icon` of any
+    /// existing link with the same URL, since the text form has no slot for it.
+    pub fn parse_text(self, text: &str, entity: &Entity) -> FieldValue {
+        match self {
+            EditField::Tags => FieldValue::List(
+                text.split(',')
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                    .map(String::from)
+                    .collect(),
+            ),
+            EditField::Links => FieldValue::Mappings(
+                text.split(';')
+                    .filter_map(|entry| parse_link(entry, &entity.metadata.links))
+                    .collect(),
+            ),
+            _ => FieldValue::Scalar(text.trim().to_string()),
+        }
+    }
+
+    /// The kind a reference in this field defaults to, for reference fields.
+    fn default_kind(self) -> Option<&'static str> {
+        match self {
+            EditField::Owner => Some("group"),
+            EditField::System => Some("system"),
+            _ => None,
+        }
+    }
+
+    /// Completion candidates for `input`: catalog refs for reference fields
+    /// (in their shortest resolvable form), well-known values for lifecycle.
+    pub fn completions(self, index: &EntityIndex, input: &str) -> Vec<String> {
+        let needle = input.trim().to_lowercase();
+        let candidates: Vec<String> = match self {
+            EditField::Owner => ["group", "user"]
+                .iter()
+                .flat_map(|kind| index.refs_of_kind(kind))
+                .map(|r| r.shorthand("group"))
+                .collect(),
+            EditField::System => index
+                .refs_of_kind("system")
+                .into_iter()
+                .map(|r| r.shorthand("system"))
+                .collect(),
+            EditField::Lifecycle => LIFECYCLES.iter().map(|s| s.to_string()).collect(),
+            _ => Vec::new(),
+        };
+        candidates
+            .into_iter()
+            .filter(|c| c.to_lowercase().contains(&needle))
+            .collect()
+    }
+
+    /// Whether the field holds an entity reference (used to colour the form).
+    pub fn is_reference(self) -> bool {
+        self.default_kind().is_some()
+    }
+}
+
+/// Parse one `title | url` link entry, carrying over an existing link's icon.
+fn parse_link(entry: &str, existing: &[Link]) -> Option<Vec<(String, String)>> {
+    let (title, url) = match entry.rsplit_once('|') {
+        Some((title, url)) => (title.trim(), url.trim()),
+        None => ("", entry.trim()),
+    };
+    if url.is_empty() {
+        return None;
+    }
+    let mut map = vec![("url".to_string(), url.to_string())];
+    if !title.is_empty() {
+        map.push(("title".to_string(), title.to_string()));
+    }
+    if let Some(icon) = existing
+        .iter()
+        .find(|l| l.url.as_deref() == Some(url))
+        .and_then(|l| l.icon.clone())
+    {
+        map.push(("icon".to_string(), icon));
+    }
+    Some(map)
+}
+
+/// Interaction state of the field editor shown in the details panel.
+#[derive(Debug, Default)]
+pub struct EditForm {
+    /// Index into [`EditField::ALL`] of the highlighted field.
+    pub selected: usize,
+    /// Text being edited, while a field is open for editing.
+    pub input: Option<String>,
+    /// What the user typed before cycling completions; candidates are
+    /// filtered by this so Tab can step through them.
+    pub typed: String,
+    /// Index of the completion last inserted with Tab, if cycling.
+    pub completion: Option<usize>,
+    /// Result of the last save (or the reason it failed).
+    pub message: Option<String>,
+}
+
+impl EditForm {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn field(&self) -> EditField {
+        EditField::ALL[self.selected.min(EditField::ALL.len() - 1)]
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.input.is_some()
+    }
+
+    pub fn move_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn move_down(&mut self) {
+        if self.selected + 1 < EditField::ALL.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Open the highlighted field for editing, starting from `current`.
+    pub fn begin(&mut self, current: String) {
+        self.typed = current.clone();
+        self.input = Some(current);
+        self.completion = None;
+        self.message = None;
+    }
+
+    /// Stop editing the field without saving.
+    pub fn cancel(&mut self) {
+        self.input = None;
+        self.completion = None;
+    }
+
+    pub fn input_char(&mut self, c: char) {
+        if let Some(input) = &mut self.input {
+            input.push(c);
+            self.typed = input.clone();
+            self.completion = None;
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if let Some(input) = &mut self.input {
+            input.pop();
+            self.typed = input.clone();
+            self.completion = None;
+        }
+    }
+
+    /// Candidates for the field being edited, filtered by what was typed.
+    pub fn candidates(&self, index: &EntityIndex) -> Vec<String> {
+        if self.is_editing() {
+            self.field().completions(index, &self.typed)
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Insert the next completion candidate, cycling through them (Tab).
+    pub fn complete(&mut self, index: &EntityIndex) {
+        let candidates = self.candidates(index);
+        if candidates.is_empty() {
+            return;
+        }
+        let next = self.completion.map_or(0, |i| (i + 1) % candidates.len());
+        self.input = Some(candidates[next].clone());
+        self.completion = Some(next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{EntityKind, EntityWithSource, Metadata};
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    fn entity(kind: EntityKind, name: &str) -> Entity {
+        Entity {
+            api_version: "backstage.io/v1alpha1".to_string(),
+            kind,
+            metadata: Metadata {
+                name: name.to_string(),
+                title: None,
+                namespace: None,
+                description: None,
+                labels: HashMap::new(),
+                annotations: HashMap::new(),
+                tags: Vec::new(),
+                links: Vec::new(),
+            },
+            spec: serde_yaml::Value::Null,
+        }
+    }
+
+    fn index() -> EntityIndex {
+        let entities: Vec<EntityWithSource> = [
+            (EntityKind::Group, "team-a"),
+            (EntityKind::Group, "team-b"),
+            (EntityKind::User, "alice"),
+            (EntityKind::System, "billing"),
+        ]
+        .into_iter()
+        .map(|(k, n)| EntityWithSource::new(entity(k, n), PathBuf::from("c.yaml")))
+        .collect();
+        EntityIndex::build(&entities)
+    }
+
+    #[test]
+    fn owner_completions_use_shortest_refs() {
+        let idx = index();
+        assert_eq!(
+            EditField::Owner.completions(&idx, ""),
+            vec!["team-a", "team-b", "user:alice"]
+        );
+        assert_eq!(
+            EditField::Owner.completions(&idx, "ALI"),
+            vec!["user:alice"]
+        );
+        assert_eq!(EditField::System.completions(&idx, "b"), vec!["billing"]);
+        assert_eq!(
+            EditField::Lifecycle.completions(&idx, "prod"),
+            vec!["production"]
+        );
+        assert!(EditField::Tags.completions(&idx, "").is_empty());
+    }
+
+    #[test]
+    fn tab_cycles_through_candidates_for_typed_text() {
+        let idx = index();
+        let mut form = EditForm::new();
+        form.begin(String::new());
+        for c in "team".chars() {
+            form.input_char(c);
+        }
+        form.complete(&idx);
+        assert_eq!(form.input.as_deref(), Some("team-a"));
+        form.complete(&idx);
+        assert_eq!(form.input.as_deref(), Some("team-b"));
+        form.complete(&idx);
+        assert_eq!(form.input.as_deref(), Some("team-a"), "wraps around");
+
+        // Typing again restarts filtering from the new text.
+        form.backspace();
+        assert_eq!(form.typed, "team-");
+        assert_eq!(form.completion, None);
+    }
+
+    #[test]
+    fn tags_and_links_round_trip_through_text() {
+        let mut e = entity(EntityKind::Component, "svc");
+        e.metadata.tags = vec!["java".into(), "backend".into()];
+        e.metadata.links = vec![Link {
+            url: Some("https://x/runbook".into()),
+            title: Some("Runbook".into()),
+            icon: Some("docs".into()),
+        }];
+
+        assert_eq!(EditField::Tags.current_text(&e), "java, backend");
+        assert_eq!(
+            EditField::Tags.parse_text(" java, ,rust ", &e),
+            FieldValue::List(vec!["java".into(), "rust".into()])
+        );
+
+        let text = EditField::Links.current_text(&e);
+        assert_eq!(text, "Runbook | https://x/runbook");
+        assert_eq!(
+            EditField::Links.parse_text(&format!("{text}; https://y"), &e),
+            FieldValue::Mappings(vec![
+                vec![
+                    ("url".into(), "https://x/runbook".into()),
+                    ("title".into(), "Runbook".into()),
+                    ("icon".into(), "docs".into()),
+                ],
+                vec![("url".into(), "https://y".into())],
+            ])
+        );
+    }
+}
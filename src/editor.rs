@@ -0,0 +1,103 @@
+//! Launching the user's editor on an entity's source file.
+//!
+//! The editor comes from `$VISUAL`, then `$EDITOR`, falling back to a platform
+//! default. Most terminal editors accept `+LINE FILE`; GUI editors that don't
+//! get their own spelling of "open at line" (see [`editor_command`]).
+
+use std::env;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+/// Editor used when neither `$VISUAL` nor `$EDITOR` is set.
+#[cfg(windows)]
+const DEFAULT_EDITOR: &str = "notepad";
+#[cfg(not(windows))]
+const DEFAULT_EDITOR: &str = "vi";
+
+/// The editor command line to use: `$VISUAL`, then `$EDITOR`, then a default.
+pub fn resolve_editor() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string())
+}
+
+/// Build the command that opens `path` at `line` with `editor`.
+///
+/// `editor` may include arguments (e.g. `code --wait`); it is split on
+/// whitespace. The line-positioning syntax is chosen from the program name.
+pub fn editor_command(editor: &str, path: &Path, line: usize) -> Command {
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or(DEFAULT_EDITOR);
+    let mut cmd = Command::new(program);
+    cmd.args(parts);
+
+    let name = Path::new(program)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(program);
+    let file = path.display().to_string();
+    match name {
+        // VS Code family: `--goto file:line`.
+        "code" | "code-insiders" | "codium" | "cursor" => {
+            cmd.arg("--goto").arg(format!("{file}:{line}"));
+        }
+        // Editors that take `file:line` directly.
+        "subl" | "zed" | "hx" | "helix" => {
+            cmd.arg(format!("{file}:{line}"));
+        }
+        // No line support.
+        "notepad" => {
+            cmd.arg(file);
+        }
+        // vi, vim, nvim, nano, emacs, micro, kak, … all understand `+LINE`.
+        _ => {
+            cmd.arg(format!("+{line}")).arg(file);
+        }
+    }
+    cmd
+}
+
+/// Open `path` at `line` in the user's editor and wait for it to exit.
+pub fn open_in_editor(path: &Path, line: usize) -> io::Result<()> {
+    let editor = resolve_editor();
+    let status = editor_command(&editor, path, line).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("{editor} exited with {status}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(cmd: &Command) -> Vec<String> {
+        cmd.get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn terminal_editors_use_plus_line() {
+        let cmd = editor_command("nvim", Path::new("c.yaml"), 12);
+        assert_eq!(cmd.get_program(), "nvim");
+        assert_eq!(args(&cmd), vec!["+12", "c.yaml"]);
+    }
+
+    #[test]
+    fn editor_arguments_are_preserved() {
+        let cmd = editor_command("/usr/bin/code --wait", Path::new("c.yaml"), 3);
+        assert_eq!(cmd.get_program(), "/usr/bin/code");
+        assert_eq!(args(&cmd), vec!["--wait", "--goto", "c.yaml:3"]);
+    }
+
+    #[test]
+    fn file_colon_line_editors() {
+        let cmd = editor_command("hx", Path::new("c.yaml"), 7);
+        assert_eq!(args(&cmd), vec!["c.yaml:7"]);
+    }
+}
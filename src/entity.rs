@@ -70,12 +70,14 @@
 //! - [`EntityWithSource`] - Entity wrapper tracking source file and validation errors
 //! - [`ValidationError`] - Structured validation error from JSON Schema
 
+use crate::relations::Relation;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::rc::Rc;
 
 /// Validation error from JSON Schema validation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationError {
     pub path: String,
     pub message: String,
@@ -126,6 +128,23 @@ impl EntityRef {
         format!("{}:{}/{}", self.kind, self.namespace, self.name)
     }
 
+    /// The shortest reference string that resolves back to this entity from a
+    /// field whose default kind is `default_kind` (e.g. `team-a` for an owner
+    /// group, `user:alice` for an owner user, `payments/api` outside `default`).
+    pub fn shorthand(&self, default_kind: &str) -> String {
+        let mut out = String::new();
+        if !self.kind.eq_ignore_ascii_case(default_kind) {
+            out.push_str(&self.kind);
+            out.push(':');
+        }
+        if self.namespace != "default" {
+            out.push_str(&self.namespace);
+            out.push('/');
+        }
+        out.push_str(&self.name);
+        out
+    }
+
     /// Check if the kind is a known Backstage kind
     pub fn is_known_kind(&self) -> bool {
         matches!(
@@ -144,7 +163,7 @@ impl std::fmt::Display for EntityRef {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EntityKind {
     Component,
-    #[serde(alias = "API")]
+    #[serde(rename = "API", alias = "Api")]
     Api,
     Resource,
     System,
@@ -172,7 +191,7 @@ impl std::fmt::Display for EntityKind {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Metadata {
     pub name: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -191,7 +210,7 @@ pub struct Metadata {
     pub links: Vec<Link>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Link {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
@@ -201,7 +220,7 @@ pub struct Link {
     pub icon: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Entity {
     pub api_version: String,
@@ -215,7 +234,15 @@ pub struct Entity {
 pub struct EntityWithSource {
     pub entity: Entity,
     pub source_file: PathBuf,
+    /// 1-based line in `source_file` where this entity's YAML document starts.
+    pub line: usize,
     pub validation_errors: Vec<ValidationError>,
+    /// Other `(file, line)` locations declaring the same `kind:namespace/name`,
+    /// filled in by [`mark_duplicates`].
+    pub duplicates: Vec<(PathBuf, usize)>,
+    /// Backstage-style relations computed from the whole catalog, filled in by
+    /// [`attach_relations`](crate::relations::attach_relations).
+    pub relations: Vec<Relation>,
 }
 
 impl EntityWithSource {
@@ -223,7 +250,10 @@ impl EntityWithSource {
         Self {
             entity,
             source_file,
+            line: 1,
             validation_errors: Vec::new(),
+            duplicates: Vec::new(),
+            relations: Vec::new(),
         }
     }
 
@@ -231,6 +261,44 @@ impl EntityWithSource {
         self.validation_errors = errors;
         self
     }
+
+    pub fn with_line(mut self, line: usize) -> Self {
+        self.line = line;
+        self
+    }
+}
+
+/// Indices of entities that share a `kind:namespace/name`, one group per
+/// duplicated ref, in catalog order.
+pub fn duplicate_groups(entities: &[EntityWithSource]) -> Vec<Vec<usize>> {
+    let mut by_ref: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut order = Vec::new();
+    for (i, ews) in entities.iter().enumerate() {
+        let key = ews.entity.ref_key();
+        let group = by_ref.entry(key.clone()).or_default();
+        if group.is_empty() {
+            order.push(key);
+        }
+        group.push(i);
+    }
+    order
+        .into_iter()
+        .filter_map(|key| by_ref.remove(&key))
+        .filter(|group| group.len() > 1)
+        .collect()
+}
+
+/// Record on each duplicated entity where its other declarations live.
+pub fn mark_duplicates(entities: &mut [EntityWithSource]) {
+    for group in duplicate_groups(entities) {
+        for &i in &group {
+            entities[i].duplicates = group
+                .iter()
+                .filter(|&&j| j != i)
+                .map(|&j| (entities[j].source_file.clone(), entities[j].line))
+                .collect();
+        }
+    }
 }
 
 impl Entity {
@@ -275,6 +343,16 @@ impl Entity {
             .unwrap_or_default()
     }
 
+    /// Groups this entity (typically a User) belongs to (`spec.memberOf`).
+    pub fn member_of(&self) -> Vec<String> {
+        self.spec_string_array("memberOf")
+    }
+
+    /// Users a Group lists directly as members (`spec.members`).
+    pub fn members(&self) -> Vec<String> {
+        self.spec_string_array("members")
+    }
+
     pub fn owner(&self) -> Option<String> {
         self.get_spec_string("owner")
     }
@@ -325,25 +403,11 @@ impl Entity {
                 });
             }
         };
-        push_single("owner", "group", self.owner());
-        push_single("system", "system", self.system());
-        push_single("domain", "domain", self.domain());
-        push_single("parent", "group", self.parent());
-        push_single(
-            "subcomponentOf",
-            "component",
-            self.get_spec_string("subcomponentOf"),
-        );
+        for &(field, kind) in SINGLE_REFERENCE_FIELDS {
+            push_single(field, kind, self.get_spec_string(field));
+        }
 
-        // Array reference fields: (spec key, default kind, fallback kinds).
-        const ARRAYS: &[(&str, &str, &[&str])] = &[
-            ("dependsOn", "component", &["resource"]),
-            ("providesApis", "api", &[]),
-            ("consumesApis", "api", &[]),
-            ("memberOf", "group", &[]),
-            ("children", "group", &[]),
-        ];
-        for &(field, kind, fallbacks) in ARRAYS {
+        for &(field, kind, fallbacks) in ARRAY_REFERENCE_FIELDS {
             for reference in self.spec_string_array(field) {
                 refs.push(OutgoingRef {
                     field,
@@ -358,6 +422,25 @@ impl Entity {
     }
 }
 
+/// Spec fields holding a single entity reference: (spec key, default kind).
+pub const SINGLE_REFERENCE_FIELDS: &[(&str, &str)] = &[
+    ("owner", "group"),
+    ("system", "system"),
+    ("domain", "domain"),
+    ("parent", "group"),
+    ("subcomponentOf", "component"),
+];
+
+/// Spec fields holding a list of entity references: (spec key, default kind,
+/// fallback kinds).
+pub const ARRAY_REFERENCE_FIELDS: &[(&str, &str, &[&str])] = &[
+    ("dependsOn", "component", &["resource"]),
+    ("providesApis", "api", &[]),
+    ("consumesApis", "api", &[]),
+    ("memberOf", "group", &[]),
+    ("children", "group", &[]),
+];
+
 /// A reference an entity declares in its spec, with the kinds it may resolve to.
 ///
 /// `fallback_kinds` are tried only when the reference is unqualified (no
@@ -371,28 +454,119 @@ pub struct OutgoingRef {
     pub reference: String,
 }
 
+/// A catalog's entities in one shared allocation. Views that show entities
+/// (the tree) refer into it through [`SharedEntity`] handles rather than
+/// holding copies. Once those handles are dropped, the catalog can update the
+/// store in place (see [`crate::catalog::LoadedCatalog::update`]).
+pub type EntityStore = Rc<Vec<EntityWithSource>>;
+
+/// One entity of an [`EntityStore`], by position; cloning it only bumps the
+/// store's reference count. Dereferences to the entity.
+#[derive(Clone)]
+pub struct SharedEntity {
+    store: EntityStore,
+    index: usize,
+}
+
+impl SharedEntity {
+    /// The entity at `index` in `store`.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    pub fn new(store: &EntityStore, index: usize) -> Self {
+        assert!(index < store.len(), "entity {index} is not in the store");
+        Self {
+            store: Rc::clone(store),
+            index,
+        }
+    }
+
+    /// The entity's position in its store.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl std::ops::Deref for SharedEntity {
+    type Target = EntityWithSource;
+
+    fn deref(&self) -> &EntityWithSource {
+        &self.store[self.index]
+    }
+}
+
+impl std::fmt::Debug for SharedEntity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
 /// Index of all loaded entities for reference validation
 #[derive(Debug, Clone, Default)]
 pub struct EntityIndex {
-    keys: HashSet<String>,
+    /// How many times each canonical ref is declared.
+    keys: HashMap<String, usize>,
 }
 
 impl EntityIndex {
     /// Build an index from a list of entities for O(1) reference validation.
     pub fn build(entities: &[EntityWithSource]) -> Self {
-        let keys = entities.iter().map(|e| e.entity.ref_key()).collect();
-        Self { keys }
+        let mut index = Self::default();
+        for ews in entities {
+            index.insert(ews.entity.ref_key());
+        }
+        index
     }
 
     /// Check if the given entity reference exists in the index.
     pub fn contains(&self, entity_ref: &EntityRef) -> bool {
-        self.keys.contains(&entity_ref.canonical())
+        self.contains_key(&entity_ref.canonical())
+    }
+
+    /// Whether an entity with the canonical ref `key` is indexed.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.keys.contains_key(key)
+    }
+
+    /// How many entities declare the canonical ref `key`.
+    pub fn count(&self, key: &str) -> usize {
+        self.keys.get(key).copied().unwrap_or(0)
+    }
+
+    /// Record one more declaration of the canonical ref `key`.
+    pub fn insert(&mut self, key: String) {
+        *self.keys.entry(key).or_insert(0) += 1;
+    }
+
+    /// Forget one declaration of the canonical ref `key`.
+    pub fn remove(&mut self, key: &str) {
+        if let Some(count) = self.keys.get_mut(key) {
+            *count -= 1;
+            if *count == 0 {
+                self.keys.remove(key);
+            }
+        }
+    }
+
+    /// All indexed entities of the given (lowercase) kind, sorted by canonical ref.
+    pub fn refs_of_kind(&self, kind: &str) -> Vec<EntityRef> {
+        let prefix = format!("{kind}:");
+        let mut refs: Vec<EntityRef> = self
+            .keys
+            .keys()
+            .filter(|k| k.starts_with(&prefix))
+            .map(|k| EntityRef::parse(k, kind))
+            .collect();
+        refs.sort_by_key(EntityRef::canonical);
+        refs
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashSet;
 
     #[test]
     fn test_entity_ref_parsing_variations() {
@@ -584,6 +758,16 @@ mod tests {
         ))); // Wrong namespace
     }
 
+    #[test]
+    fn test_entity_ref_shorthand() {
+        let group = EntityRef::parse("group:default/team-a", "group");
+        assert_eq!(group.shorthand("group"), "team-a");
+        let user = EntityRef::parse("user:default/alice", "user");
+        assert_eq!(user.shorthand("group"), "user:alice");
+        let api = EntityRef::parse("api:payments/charge", "api");
+        assert_eq!(api.shorthand("api"), "payments/charge");
+    }
+
     #[test]
     fn test_entity_display_name() {
         // Test with title
@@ -889,6 +1073,40 @@ mod tests {
         assert!(by_field("memberOf").is_empty());
     }
 
+    #[test]
+    fn test_mark_duplicates() {
+        let yaml = "\
+apiVersion: backstage.io/v1alpha1
+kind: Component
+metadata:
+  name: svc
+---
+apiVersion: backstage.io/v1alpha1
+kind: API
+metadata:
+  name: svc
+---
+apiVersion: backstage.io/v1alpha1
+kind: Component
+metadata:
+  name: svc
+  namespace: default
+";
+        let mut entities: Vec<EntityWithSource> = serde_yaml::Deserializer::from_str(yaml)
+            .enumerate()
+            .map(|(i, doc)| {
+                EntityWithSource::new(Entity::deserialize(doc).unwrap(), PathBuf::from("a.yaml"))
+                    .with_line(i * 5 + 1)
+            })
+            .collect();
+
+        assert_eq!(duplicate_groups(&entities), vec![vec![0, 2]]);
+        mark_duplicates(&mut entities);
+        assert_eq!(entities[0].duplicates, vec![(PathBuf::from("a.yaml"), 11)]);
+        assert_eq!(entities[2].duplicates, vec![(PathBuf::from("a.yaml"), 1)]);
+        assert!(entities[1].duplicates.is_empty());
+    }
+
     #[test]
     fn test_validation_error() {
         let error = ValidationError {
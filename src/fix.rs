@@ -0,0 +1,475 @@
+//! Automatic fixes for common catalog problems (`bsv fix`).
+//!
+//! [`propose_fixes`] looks for problems with an unambiguous repair:
+//!
+//! - a reference that doesn't resolve but is a near-miss (small edit
+//!   distance) of an existing entity of an acceptable kind
+//! - an unqualified reference that only resolves under one of its field's
+//!   fallback kinds (e.g. a Resource in `dependsOn`), which gets an explicit
+//!   `kind:` prefix
+//! - a required `spec` field that is missing, inserted with a placeholder
+//! - an `apiVersion` the schema doesn't accept for the kind
+//!
+//! Each [`Fix`] is applied to the source text through [`crate::yaml_edit`],
+//! so the rest of the file (comments, order, other documents) is untouched.
+
+use crate::entity::{EntityIndex, EntityRef, EntityWithSource, SINGLE_REFERENCE_FIELDS};
+use crate::parser::document_start_lines;
+use crate::validator::{allowed_api_versions, required_spec_fields};
+use crate::yaml_edit::{set_field, set_top_level, FieldValue};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+/// Placeholder written into missing required scalar fields.
+pub const PLACEHOLDER: &str = "TODO";
+
+/// Why a reference is being rewritten.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefFixReason {
+    /// Resolves only under another kind; add the explicit `kind:` prefix.
+    KindPrefix,
+    /// Broken; the closest existing entity by edit distance.
+    ClosestMatch,
+}
+
+/// The change a [`Fix`] makes to its entity's document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FixEdit {
+    /// Replace one reference in a spec reference field.
+    Reference {
+        field: &'static str,
+        from: String,
+        to: String,
+        reason: RefFixReason,
+    },
+    /// Insert a missing required spec field.
+    SpecField { key: String, value: FieldValue },
+    /// Replace `apiVersion`.
+    ApiVersion { from: String, to: String },
+}
+
+/// A proposed fix to one entity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    /// Canonical ref of the entity being fixed.
+    pub entity: String,
+    pub source_file: PathBuf,
+    /// 1-based line where the entity's document starts.
+    pub line: usize,
+    pub edit: FixEdit,
+}
+
+impl fmt::Display for Fix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.edit {
+            FixEdit::Reference {
+                field,
+                from,
+                to,
+                reason: RefFixReason::KindPrefix,
+            } => write!(f, "{field}: {from} → {to} (add kind prefix)"),
+            FixEdit::Reference {
+                field, from, to, ..
+            } => write!(f, "{field}: {from} → {to} (closest match)"),
+            FixEdit::SpecField { key, value } => {
+                let shown = match value {
+                    FieldValue::Raw(raw) => raw.clone(),
+                    _ => PLACEHOLDER.to_string(),
+                };
+                write!(f, "spec.{key}: missing → {shown} (required placeholder)")
+            }
+            FixEdit::ApiVersion { from, to } => write!(f, "apiVersion: {from} → {to}"),
+        }
+    }
+}
+
+/// Levenshtein distance between two strings, by characters.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = prev[j] + usize::from(ca != *cb);
+            current.push(substitute.min(prev[j + 1] + 1).min(current[j] + 1));
+        }
+        prev = current;
+    }
+    prev[b.len()]
+}
+
+/// Write `target` the way `original` was written: unqualified references
+/// stay as short as possible, qualified ones keep their kind (and namespace).
+fn render_like(original: &EntityRef, target: &EntityRef, default_kind: &str) -> String {
+    if original.kind_inferred {
+        return target.shorthand(default_kind);
+    }
+    if original.namespace_inferred && target.namespace == "default" {
+        format!("{}:{}", target.kind, target.name)
+    } else {
+        target.canonical()
+    }
+}
+
+/// Existing entities a broken `reference` may have meant, nearest first by
+/// edit distance (then by ref): names within typo distance among the kinds
+/// the field accepts (`default_kind` and `fallbacks`, plus the kind written
+/// in `reference`, if any). An exact name under another accepted kind ranks
+/// at distance 0.
+fn candidates(
+    index: &EntityIndex,
+    parsed: &EntityRef,
+    default_kind: &str,
+    fallbacks: &[&str],
+) -> Vec<(usize, EntityRef)> {
+    let mut kinds: Vec<&str> = std::iter::once(default_kind)
+        .chain(fallbacks.iter().copied())
+        .collect();
+    if !parsed.kind_inferred && !kinds.contains(&parsed.kind.as_str()) {
+        kinds.push(&parsed.kind);
+    }
+    let name = parsed.name.to_lowercase();
+    let max_distance = (name.chars().count() / 3).clamp(1, 3);
+    let mut ranked: Vec<(usize, EntityRef)> = kinds
+        .iter()
+        .flat_map(|kind| index.refs_of_kind(kind))
+        .filter(|candidate| candidate.namespace == parsed.namespace)
+        .map(|candidate| {
+            (
+                edit_distance(&name, &candidate.name.to_lowercase()),
+                candidate,
+            )
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    ranked.sort_by(|(da, a), (db, b)| da.cmp(db).then_with(|| a.canonical().cmp(&b.canonical())));
+    ranked
+}
+
+/// A reference that would resolve in place of the broken `reference`: the
+/// same name under one of the field's fallback kinds (for unqualified
+/// references), otherwise the closest name among the acceptable kinds, if one
+/// is close enough to be a plausible typo.
+pub fn suggest_reference(
+    index: &EntityIndex,
+    reference: &str,
+    default_kind: &str,
+    fallbacks: &[&str],
+) -> Option<(String, RefFixReason)> {
+    let parsed = EntityRef::parse(reference, default_kind);
+    let (distance, target) = candidates(index, &parsed, default_kind, fallbacks)
+        .into_iter()
+        .next()?;
+    let reason = if parsed.kind_inferred && distance == 0 && target.kind != parsed.kind {
+        RefFixReason::KindPrefix
+    } else {
+        RefFixReason::ClosestMatch
+    };
+    Some((render_like(&parsed, &target, default_kind), reason))
+}
+
+/// Up to `limit` existing entity refs a broken `reference` may have meant,
+/// nearest first, among the kinds the field accepts (see [`candidates`]).
+/// Each is written the way `reference` was (see [`render_like`]).
+pub fn did_you_mean(
+    index: &EntityIndex,
+    reference: &str,
+    default_kind: &str,
+    fallbacks: &[&str],
+    limit: usize,
+) -> Vec<String> {
+    let parsed = EntityRef::parse(reference, default_kind);
+    let mut seen = Vec::new();
+    for (_, target) in candidates(index, &parsed, default_kind, fallbacks) {
+        if seen.len() == limit {
+            break;
+        }
+        let shown = render_like(&parsed, &target, default_kind);
+        if !seen.contains(&shown) {
+            seen.push(shown);
+        }
+    }
+    seen
+}
+
+/// Propose fixes for every entity, in catalog order.
+pub fn propose_fixes(entities: &[EntityWithSource]) -> Vec<Fix> {
+    let index = EntityIndex::build(entities);
+    let mut fixes = Vec::new();
+
+    for ews in entities {
+        let entity = &ews.entity;
+        let mut push = |edit| {
+            fixes.push(Fix {
+                entity: entity.ref_key(),
+                source_file: ews.source_file.clone(),
+                line: ews.line,
+                edit,
+            })
+        };
+
+        let allowed = allowed_api_versions(&entity.kind);
+        if !allowed.is_empty() && !allowed.contains(&entity.api_version) {
+            push(FixEdit::ApiVersion {
+                from: entity.api_version.clone(),
+                to: allowed[0].clone(),
+            });
+        }
+
+        for field in required_spec_fields(&entity.kind) {
+            let present = entity.spec.get(&field.name).is_some_and(|v| !v.is_null());
+            if !present {
+                let value = if field.is_array {
+                    FieldValue::Raw("[]".to_string())
+                } else {
+                    FieldValue::Scalar(PLACEHOLDER.to_string())
+                };
+                push(FixEdit::SpecField {
+                    key: field.name,
+                    value,
+                });
+            }
+        }
+
+        for r in entity.outgoing_references() {
+            let parsed = EntityRef::parse(&r.reference, r.default_kind);
+            if index.contains(&parsed) {
+                continue;
+            }
+            if let Some((to, reason)) =
+                suggest_reference(&index, &r.reference, r.default_kind, r.fallback_kinds)
+            {
+                push(FixEdit::Reference {
+                    field: r.field,
+                    from: r.reference,
+                    to,
+                    reason,
+                });
+            }
+        }
+    }
+    fixes
+}
+
+/// Apply one fix to a file's content.
+pub fn apply_to_content(content: &str, fix: &Fix) -> Result<String> {
+    match &fix.edit {
+        FixEdit::ApiVersion { to, .. } => set_top_level(content, fix.line, "apiVersion", to),
+        FixEdit::SpecField { key, value } => set_field(content, fix.line, "spec", key, value),
+        FixEdit::Reference {
+            field, from, to, ..
+        } => {
+            if SINGLE_REFERENCE_FIELDS.iter().any(|(f, _)| f == field) {
+                return set_field(
+                    content,
+                    fix.line,
+                    "spec",
+                    field,
+                    &FieldValue::Scalar(to.clone()),
+                );
+            }
+            // List fields are rewritten whole, from the document's current
+            // contents, so several fixes to one list compose.
+            let items: Vec<String> = document_value(content, fix.line)?
+                .get("spec")
+                .and_then(|spec| spec.get(*field))
+                .and_then(|v| v.as_sequence())
+                .context("reference list not found")?
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(|item| {
+                    if item == from {
+                        to.clone()
+                    } else {
+                        item.to_string()
+                    }
+                })
+                .collect();
+            set_field(content, fix.line, "spec", field, &FieldValue::List(items))
+        }
+    }
+}
+
+/// The parsed YAML document that starts at 1-based `line`.
+fn document_value(content: &str, line: usize) -> Result<serde_yaml::Value> {
+    let position = document_start_lines(content)
+        .iter()
+        .position(|&start| start == line)
+        .context("document not found")?;
+    let document = serde_yaml::Deserializer::from_str(content)
+        .nth(position)
+        .context("document not found")?;
+    Ok(serde_yaml::Value::deserialize(document)?)
+}
+
+/// Apply fixes, writing each affected file once. Documents are edited from
+/// the bottom of the file up so earlier documents' start lines stay valid.
+/// Returns the number of fixes applied.
+pub fn apply_fixes(fixes: &[&Fix]) -> Result<usize> {
+    let mut by_file: BTreeMap<&PathBuf, Vec<&Fix>> = BTreeMap::new();
+    for fix in fixes {
+        by_file.entry(&fix.source_file).or_default().push(fix);
+    }
+
+    let mut applied = 0;
+    for (path, mut fixes) in by_file {
+        fixes.sort_by_key(|fix| std::cmp::Reverse(fix.line));
+        let mut content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read file: {}", path.display()))?;
+        for fix in fixes {
+            content = apply_to_content(&content, fix)
+                .with_context(|| format!("{}: {fix}", fix.entity))?;
+            applied += 1;
+        }
+        fs::write(path, content)
+            .with_context(|| format!("Failed to write file: {}", path.display()))?;
+    }
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::Entity;
+    use crate::test_support::TempDir;
+    use crate::validator::validate_entity;
+    use std::path::Path;
+
+    const CATALOG: &str = "\
+apiVersion: backstage.io/v1alpha1
+kind: Group
+metadata:
+  name: platform-team
+spec:
+  type: team
+  children: []
+---
+apiVersion: backstage.io/v1alpha1
+kind: User
+metadata:
+  name: alice
+spec:
+  memberOf: []
+---
+apiVersion: backstage.io/v1alpha1
+kind: Resource
+metadata:
+  name: orders-db
+spec:
+  type: database
+  owner: platform-team
+---
+apiVersion: backstage/v1
+kind: Component
+metadata:
+  name: orders
+spec:
+  type: service
+  lifecycle: production
+  owner: platform-tem # typo
+  dependsOn:
+    - orders-db
+    - component:billing
+---
+apiVersion: backstage.io/v1alpha1
+kind: Component
+metadata:
+  name: payments
+spec:
+  owner: alice
+";
+
+    fn parse(content: &str, path: &Path) -> Vec<EntityWithSource> {
+        let starts = document_start_lines(content);
+        serde_yaml::Deserializer::from_str(content)
+            .zip(starts)
+            .map(|(doc, line)| {
+                let entity = Entity::deserialize(doc).unwrap();
+                let errors = validate_entity(&entity);
+                EntityWithSource::new(entity, path.to_path_buf())
+                    .with_line(line)
+                    .with_validation_errors(errors)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn edit_distance_counts_single_character_edits() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("team-a", "team-a"), 0);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn suggestions_prefer_kind_prefix_then_closest_name() {
+        let entities = parse(CATALOG, Path::new("c.yaml"));
+        let index = EntityIndex::build(&entities);
+        assert_eq!(
+            suggest_reference(&index, "orders-db", "component", &["resource"]),
+            Some(("resource:orders-db".to_string(), RefFixReason::KindPrefix))
+        );
+        // A User isn't an acceptable owner, so it isn't suggested.
+        assert_eq!(suggest_reference(&index, "alice", "group", &[]), None);
+        assert_eq!(
+            suggest_reference(&index, "platform-tem", "group", &[]),
+            Some(("platform-team".to_string(), RefFixReason::ClosestMatch))
+        );
+        assert_eq!(
+            suggest_reference(&index, "group:default/platfrm-team", "group", &[]),
+            Some((
+                "group:default/platform-team".to_string(),
+                RefFixReason::ClosestMatch
+            ))
+        );
+        assert_eq!(suggest_reference(&index, "billing", "group", &[]), None);
+    }
+
+    #[test]
+    fn proposes_each_kind_of_fix() {
+        let entities = parse(CATALOG, Path::new("c.yaml"));
+        let shown: Vec<String> = propose_fixes(&entities)
+            .iter()
+            .map(|fix| format!("{} {fix}", fix.entity))
+            .collect();
+        assert_eq!(
+            shown,
+            vec![
+                "component:default/orders apiVersion: backstage/v1 → backstage.io/v1alpha1",
+                "component:default/orders owner: platform-tem → platform-team (closest match)",
+                "component:default/orders dependsOn: orders-db → resource:orders-db (add kind prefix)",
+                "component:default/payments spec.type: missing → TODO (required placeholder)",
+                "component:default/payments spec.lifecycle: missing → TODO (required placeholder)",
+            ]
+        );
+    }
+
+    #[test]
+    fn applying_fixes_rewrites_only_the_affected_lines() {
+        let dir = TempDir::new("fix-test");
+        let path = dir.join("catalog-info.yaml");
+        fs::write(&path, CATALOG).unwrap();
+
+        let entities = parse(CATALOG, &path);
+        let fixes = propose_fixes(&entities);
+        assert_eq!(
+            apply_fixes(&fixes.iter().collect::<Vec<_>>()).unwrap(),
+            fixes.len()
+        );
+
+        let fixed = fs::read_to_string(&path).unwrap();
+        assert!(fixed.contains(
+            "apiVersion: backstage.io/v1alpha1\nkind: Component\nmetadata:\n  name: orders\n"
+        ));
+        assert!(fixed.contains("  owner: platform-team # typo\n"));
+        assert!(fixed.contains("    - resource:orders-db\n    - component:billing\n"));
+        assert!(fixed.contains("  owner: alice\n  type: TODO\n  lifecycle: TODO\n"));
+
+        // Nothing left to fix except the references with no plausible target.
+        let again = propose_fixes(&parse(&fixed, &path));
+        assert!(again.is_empty(), "{again:?}");
+    }
+}
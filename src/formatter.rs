@@ -0,0 +1,594 @@
+//! Canonical layout for catalog files (`bsv fmt`).
+//!
+//! [`format_content`] rewrites a catalog file into one consistent shape while
+//! keeping its comments:
+//!
+//! - top-level keys in the order `apiVersion`, `kind`, `metadata`, `spec`
+//!   (anything else follows, in its original order)
+//! - `metadata.tags` sorted
+//! - spec entity references rewritten fully-qualified (`group:default/team-a`)
+//!   or minimal (`team-a`) when a [`RefStyle`] other than `Keep` is chosen
+//! - documents separated by a bare `---`, with no empty documents, `...` end
+//!   markers, or stray blank lines around them
+//!
+//! Like [`crate::yaml_edit`], the formatter works on lines rather than
+//! re-serializing, so comments travel with the keys and list items they sit
+//! above. Documents it can't restructure safely (e.g. a flow-style mapping at
+//! the top level) are left as they are.
+
+use crate::entity::{EntityIndex, EntityRef, ARRAY_REFERENCE_FIELDS, SINGLE_REFERENCE_FIELDS};
+use crate::yaml_edit::{
+    block_end, block_end_for_key, child_indent, ensure_parses, find_key, indent_of, inline_value,
+    is_blank_or_comment, is_marker, scalar, strip_comment,
+};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// How `bsv fmt` writes entity references in spec fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RefStyle {
+    /// Leave references as written.
+    #[default]
+    Keep,
+    /// `kind:namespace/name`, always.
+    Full,
+    /// The shortest form that still resolves to the same entity.
+    Minimal,
+}
+
+impl FromStr for RefStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keep" => Ok(RefStyle::Keep),
+            "full" => Ok(RefStyle::Full),
+            "minimal" => Ok(RefStyle::Minimal),
+            other => Err(format!(
+                "unknown reference style '{other}' (expected keep, full or minimal)"
+            )),
+        }
+    }
+}
+
+/// Options for [`format_content`].
+#[derive(Debug, Clone, Default)]
+pub struct FormatOptions {
+    pub refs: RefStyle,
+}
+
+/// Top-level keys in canonical order; any other key sorts after these.
+const KEY_ORDER: [&str; 4] = ["apiVersion", "kind", "metadata", "spec"];
+
+/// Format a catalog file's content. `index` resolves unqualified references
+/// (e.g. a `dependsOn` entry that names a Resource) when restyling them.
+///
+/// Returns an error, without formatting, if the content is not valid YAML.
+pub fn format_content(
+    content: &str,
+    index: &EntityIndex,
+    options: &FormatOptions,
+) -> Result<String> {
+    ensure_parses(content)?;
+    let (header, documents) = split_documents(content)?;
+
+    let documents: Vec<Vec<String>> = documents
+        .into_iter()
+        .map(|doc| format_document(doc, index, options))
+        .collect();
+
+    let mut out: Vec<String> = Vec::new();
+    if !header.is_empty() {
+        out.extend(header);
+        out.push("---".to_string());
+    }
+    for (i, doc) in documents.into_iter().enumerate() {
+        if i > 0 {
+            out.push("---".to_string());
+        }
+        out.extend(doc);
+    }
+
+    let mut formatted = out.join("\n");
+    formatted.push('\n');
+    ensure_parses(&formatted)?;
+    Ok(formatted)
+}
+
+/// Split a file into a comment-only header (kept above the first `---`) and
+/// its documents, dropping blank documents and trimming blank lines at the
+/// edges of each.
+fn split_documents(content: &str) -> Result<(Vec<String>, Vec<Vec<String>>)> {
+    let mut chunks: Vec<Vec<String>> = vec![Vec::new()];
+    let mut first_marker_seen = false;
+    let mut header_candidate = None;
+
+    for line in content.lines() {
+        if is_marker(line) {
+            if !first_marker_seen {
+                first_marker_seen = true;
+                if chunks[0].iter().all(|l| is_blank_or_comment(l)) {
+                    header_candidate = Some(0);
+                }
+            }
+            chunks.push(Vec::new());
+            // `--- # comment` keeps its comment; other content after the
+            // marker (tags, inline values) is not something we rewrite.
+            let tail = line.trim_end().strip_prefix("---").unwrap_or("").trim();
+            if tail.starts_with('#') {
+                chunks.last_mut().unwrap().push(tail.to_string());
+            } else if !tail.is_empty() {
+                bail!("content on a document marker line is not supported: `{line}`");
+            }
+        } else {
+            chunks.last_mut().unwrap().push(line.to_string());
+        }
+    }
+
+    let header = match header_candidate {
+        Some(0) => trim_blank_edges(chunks.remove(0)),
+        _ => Vec::new(),
+    };
+    let documents = chunks
+        .into_iter()
+        .map(trim_blank_edges)
+        .filter(|doc| !doc.is_empty())
+        .collect();
+    Ok((header, documents))
+}
+
+fn trim_blank_edges(mut lines: Vec<String>) -> Vec<String> {
+    while lines.last().is_some_and(|l| l.trim().is_empty()) {
+        lines.pop();
+    }
+    let leading = lines.iter().take_while(|l| l.trim().is_empty()).count();
+    lines.drain(..leading);
+    lines
+}
+
+fn format_document(doc: Vec<String>, index: &EntityIndex, options: &FormatOptions) -> Vec<String> {
+    let Some(mut lines) = reorder_top_level(&doc) else {
+        return doc;
+    };
+    if let Some((start, end, indent)) = section(&lines, "metadata") {
+        if let Some(key_idx) = find_key(&lines, start, end, indent, "tags") {
+            sort_sequence(&mut lines, key_idx, end, indent);
+        }
+    }
+    if options.refs != RefStyle::Keep {
+        restyle_references(&mut lines, index, options.refs);
+    }
+    lines
+}
+
+/// A top-level key with the lines that belong to it: comments directly above
+/// it, the key line, and its (indented) value.
+struct Block {
+    key: String,
+    lines: Vec<String>,
+}
+
+/// The document with its top-level keys in canonical order, or `None` if
+/// its top level isn't a plain block mapping.
+fn reorder_top_level(doc: &[String]) -> Option<Vec<String>> {
+    let mut preamble: Vec<String> = Vec::new();
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut pending: Vec<String> = Vec::new();
+
+    for line in doc {
+        if is_blank_or_comment(line) {
+            pending.push(line.clone());
+        } else if indent_of(line) == 0 {
+            let key = top_level_key(line)?;
+            // Indented comments right after a block still belong to it.
+            let keep = pending
+                .iter()
+                .rposition(|l| !l.trim().is_empty() && indent_of(l) > 0)
+                .map_or(0, |i| i + 1);
+            match blocks.last_mut() {
+                Some(last) => last.lines.extend(pending.drain(..keep)),
+                None => preamble.extend(pending.drain(..keep)),
+            }
+            let mut lines: Vec<String> = std::mem::take(&mut pending);
+            lines.push(line.clone());
+            blocks.push(Block { key, lines });
+        } else {
+            let last = blocks.last_mut()?;
+            last.lines.append(&mut pending);
+            last.lines.push(line.clone());
+        }
+    }
+
+    let rank = |key: &str| {
+        KEY_ORDER
+            .iter()
+            .position(|k| *k == key)
+            .unwrap_or(KEY_ORDER.len())
+    };
+    blocks.sort_by_key(|b| rank(&b.key));
+
+    let mut out = preamble;
+    for block in blocks {
+        out.extend(block.lines);
+    }
+    out.extend(pending);
+    Some(trim_blank_edges(out))
+}
+
+/// The key of a top-level `key:` / `key: value` line.
+fn top_level_key(line: &str) -> Option<String> {
+    if line.starts_with(['-', '?', '{', '[', '&', '*', '!', '|', '>']) {
+        return None;
+    }
+    let (key, rest) = line.split_once(':')?;
+    if !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+    Some(key.trim().trim_matches(['"', '\'']).to_string())
+}
+
+/// `(first child line, end, child indent)` of the top-level block `name`.
+fn section(lines: &[String], name: &str) -> Option<(usize, usize, usize)> {
+    let idx = find_key(lines, 0, lines.len(), 0, name)?;
+    if !inline_value(&lines[idx], name).is_empty() {
+        return None;
+    }
+    let end = block_end(lines, idx, lines.len(), 0);
+    let indent = child_indent(lines, idx + 1, end)?;
+    Some((idx + 1, end, indent))
+}
+
+/// The plain string a scalar YAML value denotes (`"a"`, `'a'`, `a` → `a`).
+fn scalar_text(value: &str) -> Option<String> {
+    match serde_yaml::from_str::<serde_yaml::Value>(value).ok()? {
+        serde_yaml::Value::String(s) => Some(s),
+        serde_yaml::Value::Number(n) => Some(n.to_string()),
+        serde_yaml::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// The value of a `- item` line, without any trailing comment.
+fn item_value(line: &str) -> &str {
+    let item = line.trim_start().strip_prefix('-').unwrap_or("");
+    strip_comment(item).trim()
+}
+
+/// Sort the sequence held by the key at `key_idx` (block or single-line flow
+/// style). Each block item keeps the comment lines directly above it.
+fn sort_sequence(lines: &mut Vec<String>, key_idx: usize, end: usize, indent: usize) {
+    let key = lines[key_idx]
+        .trim_start()
+        .split(':')
+        .next()
+        .unwrap_or("")
+        .to_string();
+    let inline = inline_value(&lines[key_idx], &key).to_string();
+    if !inline.is_empty() {
+        let Ok(mut items) = serde_yaml::from_str::<Vec<String>>(&inline) else {
+            return;
+        };
+        if inline.starts_with('[') && !items.is_empty() {
+            items.sort_by_key(|t| t.to_lowercase());
+            let rendered: Vec<String> = items.iter().map(|t| scalar(t)).collect();
+            let comment = lines[key_idx][indent..]
+                .split_once(':')
+                .map(|(_, v)| v[strip_comment(v).len()..].trim().to_string())
+                .filter(|c| !c.is_empty())
+                .map(|c| format!(" {c}"))
+                .unwrap_or_default();
+            lines[key_idx] = format!(
+                "{}{key}: [{}]{comment}",
+                " ".repeat(indent),
+                rendered.join(", ")
+            );
+        }
+        return;
+    }
+
+    let value_end = block_end_for_key(lines, key_idx, end, indent);
+    let mut items: Vec<(String, Vec<String>)> = Vec::new();
+    let mut pending: Vec<String> = Vec::new();
+    let mut item_indent = None;
+    for line in &lines[key_idx + 1..value_end] {
+        if is_blank_or_comment(line) {
+            pending.push(line.clone());
+            continue;
+        }
+        let is_item =
+            line.trim_start().starts_with('-') && item_indent.is_none_or(|i| indent_of(line) == i);
+        if is_item {
+            item_indent = Some(indent_of(line));
+            let Some(text) = scalar_text(item_value(line)) else {
+                return; // nested structures: leave the list alone
+            };
+            let mut group = std::mem::take(&mut pending);
+            group.push(line.clone());
+            items.push((text, group));
+        } else {
+            let Some((_, group)) = items.last_mut() else {
+                return;
+            };
+            group.append(&mut pending);
+            group.push(line.clone());
+        }
+    }
+    items.sort_by_key(|(text, _)| text.to_lowercase());
+    let sorted: Vec<String> = items
+        .into_iter()
+        .flat_map(|(_, group)| group)
+        .chain(pending)
+        .collect();
+    lines.splice(key_idx + 1..value_end, sorted);
+}
+
+/// Rewrite a reference per `style`, resolving unqualified references against
+/// the fallback kinds when the default kind doesn't match anything.
+fn restyle(
+    reference: &str,
+    default_kind: &str,
+    fallbacks: &[&str],
+    index: &EntityIndex,
+    style: RefStyle,
+) -> String {
+    let parsed = EntityRef::parse(reference, default_kind);
+    let resolved = if parsed.kind_inferred && !index.contains(&parsed) {
+        fallbacks
+            .iter()
+            .map(|kind| EntityRef::parse(reference, kind))
+            .find(|r| index.contains(r))
+            .unwrap_or(parsed)
+    } else {
+        parsed
+    };
+    match style {
+        RefStyle::Keep => reference.to_string(),
+        RefStyle::Full => resolved.canonical(),
+        RefStyle::Minimal => resolved.shorthand(default_kind),
+    }
+}
+
+/// Replace the value part of `line` (after `prefix`), keeping any comment.
+fn replace_value(line: &str, prefix_len: usize, value: &str) -> String {
+    let rest = &line[prefix_len..];
+    let comment = &rest[strip_comment(rest).len()..];
+    let comment = if comment.trim().is_empty() {
+        String::new()
+    } else {
+        format!(" {}", comment.trim())
+    };
+    format!("{}{}{comment}", &line[..prefix_len], value)
+}
+
+fn restyle_references(lines: &mut [String], index: &EntityIndex, style: RefStyle) {
+    let Some((start, end, indent)) = section(lines, "spec") else {
+        return;
+    };
+
+    for &(field, kind) in SINGLE_REFERENCE_FIELDS {
+        let Some(idx) = find_key(lines, start, end, indent, field) else {
+            continue;
+        };
+        let Some(current) = scalar_text(inline_value(&lines[idx], field)) else {
+            continue;
+        };
+        let restyled = restyle(&current, kind, &[], index, style);
+        if restyled != current {
+            let prefix = lines[idx].find(':').map_or(0, |i| i + 1);
+            lines[idx] = replace_value(&lines[idx], prefix, &format!(" {}", scalar(&restyled)));
+        }
+    }
+
+    for &(field, kind, fallbacks) in ARRAY_REFERENCE_FIELDS {
+        let Some(idx) = find_key(lines, start, end, indent, field) else {
+            continue;
+        };
+        let inline = inline_value(&lines[idx], field).to_string();
+        if !inline.is_empty() {
+            let Ok(items) = serde_yaml::from_str::<Vec<String>>(&inline) else {
+                continue;
+            };
+            if items.is_empty() {
+                continue;
+            }
+            let rendered: Vec<String> = items
+                .iter()
+                .map(|r| scalar(&restyle(r, kind, fallbacks, index, style)))
+                .collect();
+            let prefix = lines[idx].find(':').map_or(0, |i| i + 1);
+            lines[idx] = replace_value(&lines[idx], prefix, &format!(" [{}]", rendered.join(", ")));
+            continue;
+        }
+        let value_end = block_end_for_key(lines, idx, end, indent);
+        for line in &mut lines[idx + 1..value_end] {
+            if is_blank_or_comment(line) || !line.trim_start().starts_with('-') {
+                continue;
+            }
+            let Some(current) = scalar_text(item_value(line)) else {
+                continue;
+            };
+            let restyled = restyle(&current, kind, fallbacks, index, style);
+            if restyled != current {
+                let prefix = line.find('-').map_or(0, |i| i + 1);
+                *line = replace_value(line, prefix, &format!(" {}", scalar(&restyled)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{Entity, EntityWithSource};
+    use serde::Deserialize;
+    use std::path::PathBuf;
+
+    fn fmt(content: &str) -> String {
+        format_content(content, &EntityIndex::default(), &FormatOptions::default()).unwrap()
+    }
+
+    fn index(yaml: &str) -> EntityIndex {
+        let entities: Vec<EntityWithSource> = serde_yaml::Deserializer::from_str(yaml)
+            .map(|doc| {
+                EntityWithSource::new(Entity::deserialize(doc).unwrap(), PathBuf::from("c.yaml"))
+            })
+            .collect();
+        EntityIndex::build(&entities)
+    }
+
+    #[test]
+    fn reorders_top_level_keys_keeping_comments() {
+        let input = "\
+# the payments service
+kind: Component
+spec:
+  # who to page
+  owner: team-a
+  type: service
+
+metadata:
+  name: payments
+apiVersion: backstage.io/v1alpha1
+";
+        assert_eq!(
+            fmt(input),
+            "\
+apiVersion: backstage.io/v1alpha1
+# the payments service
+kind: Component
+
+metadata:
+  name: payments
+spec:
+  # who to page
+  owner: team-a
+  type: service
+"
+        );
+    }
+
+    #[test]
+    fn sorts_tags_in_block_and_flow_style() {
+        let input = "\
+apiVersion: backstage.io/v1alpha1
+kind: Component
+metadata:
+  name: a
+  tags:
+    - rust
+    # legacy
+    - Java
+    - backend
+spec: {}
+---
+apiVersion: backstage.io/v1alpha1
+kind: Component
+metadata:
+  name: b
+  tags: [web, api] # inline
+";
+        let out = fmt(input);
+        assert!(
+            out.contains("  tags:\n    - backend\n    # legacy\n    - Java\n    - rust\nspec: {}")
+        );
+        assert!(out.contains("  tags: [api, web] # inline\n"));
+    }
+
+    #[test]
+    fn normalizes_document_separators() {
+        let input = "\
+# Team catalog
+---
+apiVersion: v1
+kind: Group
+
+
+---
+
+---
+apiVersion: v1
+kind: User
+...
+";
+        assert_eq!(
+            fmt(input),
+            "# Team catalog\n---\napiVersion: v1\nkind: Group\n---\napiVersion: v1\nkind: User\n"
+        );
+        // No header: no leading marker.
+        assert_eq!(fmt("---\nkind: A\n"), "kind: A\n");
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        let input = std::fs::read_to_string("testdata/large-catalog.yaml").unwrap();
+        let once = fmt(&input);
+        assert_eq!(fmt(&once), once);
+    }
+
+    #[test]
+    fn restyles_references() {
+        let catalog = "\
+apiVersion: backstage.io/v1alpha1
+kind: Group
+metadata: {name: team-a}
+---
+apiVersion: backstage.io/v1alpha1
+kind: Resource
+metadata: {name: db}
+";
+        let idx = index(catalog);
+        let input = "\
+kind: Component
+metadata:
+  name: svc
+spec:
+  owner: team-a # primary
+  system: payments/billing
+  dependsOn:
+    - db
+    - component:default/cache
+";
+        let full = format_content(
+            input,
+            &idx,
+            &FormatOptions {
+                refs: RefStyle::Full,
+            },
+        )
+        .unwrap();
+        assert!(full.contains("  owner: group:default/team-a # primary\n"));
+        assert!(full.contains("  system: system:payments/billing\n"));
+        assert!(full.contains("    - resource:default/db\n    - component:default/cache\n"));
+
+        let minimal = format_content(
+            &full,
+            &idx,
+            &FormatOptions {
+                refs: RefStyle::Minimal,
+            },
+        )
+        .unwrap();
+        assert!(minimal.contains("  owner: team-a # primary\n"));
+        assert!(minimal.contains("  system: payments/billing\n"));
+        assert!(minimal.contains("    - resource:db\n    - cache\n"));
+    }
+
+    #[test]
+    fn unparsable_content_is_an_error() {
+        let result = format_content(
+            "kind: [unclosed\n",
+            &EntityIndex::default(),
+            &FormatOptions::default(),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parses_ref_style() {
+        assert_eq!("full".parse(), Ok(RefStyle::Full));
+        assert_eq!("minimal".parse(), Ok(RefStyle::Minimal));
+        assert!("short".parse::<RefStyle>().is_err());
+    }
+}
@@ -106,8 +106,7 @@
 //! - [`RelationType`] - Type of relationship (Owner, System, DependsOn, etc.)
 //! - [`EntityNode`] - Node in the graph representing an entity reference
 
-use crate::entity::{EntityRef, EntityWithSource};
-use std::collections::HashMap;
+use crate::entity::{EntityIndex, EntityRef, EntityWithSource};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RelationType {
@@ -191,11 +190,18 @@ impl RelationshipGraph {
     /// Extracts both outgoing relationships (references this entity makes)
     /// and incoming relationships (references other entities make to this one).
     pub fn build(entity: &EntityWithSource, all_entities: &[EntityWithSource]) -> Self {
+        Self::build_indexed(entity, all_entities, &EntityIndex::build(all_entities))
+    }
+
+    /// Build a relationship graph as [`RelationshipGraph::build`] does,
+    /// resolving references through `index` — the [`EntityIndex`] of
+    /// `all_entities`, built once per load rather than once per graph.
+    pub fn build_indexed(
+        entity: &EntityWithSource,
+        all_entities: &[EntityWithSource],
+        index: &EntityIndex,
+    ) -> Self {
         let center_ref = entity.entity.ref_key();
-        let entity_map: HashMap<String, &EntityWithSource> = all_entities
-            .iter()
-            .map(|e| (e.entity.ref_key(), e))
-            .collect();
 
         let center = EntityNode {
             ref_key: center_ref.clone(),
@@ -208,10 +214,10 @@ impl RelationshipGraph {
         let mut incoming = Vec::new();
 
         // Extract outgoing relationships from this entity
-        Self::extract_outgoing_relationships(entity, &entity_map, &mut outgoing);
+        Self::extract_outgoing_relationships(entity, index, &mut outgoing);
 
         // Find incoming relationships (other entities pointing to this one)
-        Self::extract_incoming_relationships(&center_ref, all_entities, &entity_map, &mut incoming);
+        Self::extract_incoming_relationships(&center_ref, all_entities, &mut incoming);
 
         RelationshipGraph {
             center,
@@ -228,11 +234,11 @@ impl RelationshipGraph {
         ref_str: &str,
         default_kind: &str,
         fallbacks: &[&str],
-        entity_map: &HashMap<String, &EntityWithSource>,
+        index: &EntityIndex,
     ) -> EntityNode {
         let parsed = EntityRef::parse(ref_str, default_kind);
         let canonical = parsed.canonical();
-        if entity_map.contains_key(&canonical) {
+        if index.contains(&parsed) {
             return EntityNode {
                 ref_key: canonical,
                 display_name: parsed.name,
@@ -244,7 +250,7 @@ impl RelationshipGraph {
             for fallback in fallbacks {
                 let alt = EntityRef::parse(ref_str, fallback);
                 let alt_canonical = alt.canonical();
-                if entity_map.contains_key(&alt_canonical) {
+                if index.contains(&alt) {
                     return EntityNode {
                         ref_key: alt_canonical,
                         display_name: alt.name,
@@ -281,13 +287,13 @@ impl RelationshipGraph {
 
     fn extract_outgoing_relationships(
         entity: &EntityWithSource,
-        entity_map: &HashMap<String, &EntityWithSource>,
+        index: &EntityIndex,
         outgoing: &mut Vec<(RelationType, EntityNode)>,
     ) {
         for r in entity.entity.outgoing_references() {
             if let Some(rel_type) = Self::relation_for(r.field) {
                 let node =
-                    Self::resolve_node(&r.reference, r.default_kind, r.fallback_kinds, entity_map);
+                    Self::resolve_node(&r.reference, r.default_kind, r.fallback_kinds, index);
                 outgoing.push((rel_type, node));
             }
         }
@@ -299,12 +305,11 @@ impl RelationshipGraph {
         center_ref: &str,
         rel_type: RelationType,
         entity: &EntityWithSource,
-        entity_map: &HashMap<String, &EntityWithSource>,
         incoming: &mut Vec<(RelationType, EntityNode)>,
     ) {
         let parsed = EntityRef::parse(ref_str, default_kind);
         if parsed.canonical() == center_ref {
-            incoming.push((rel_type, Self::node_from_entity(entity, entity_map)));
+            incoming.push((rel_type, Self::node_from_entity(entity)));
         }
     }
 
@@ -314,7 +319,6 @@ impl RelationshipGraph {
         center_ref: &str,
         rel_type: RelationType,
         entity: &EntityWithSource,
-        entity_map: &HashMap<String, &EntityWithSource>,
         incoming: &mut Vec<(RelationType, EntityNode)>,
     ) -> bool {
         if let Some(arr) = field_value.as_sequence() {
@@ -322,8 +326,7 @@ impl RelationshipGraph {
                 if let Some(item_str) = item.as_str() {
                     let parsed = EntityRef::parse(item_str, default_kind);
                     if parsed.canonical() == center_ref {
-                        incoming
-                            .push((rel_type.clone(), Self::node_from_entity(entity, entity_map)));
+                        incoming.push((rel_type.clone(), Self::node_from_entity(entity)));
                         return true;
                     }
                 }
@@ -335,7 +338,6 @@ impl RelationshipGraph {
     fn extract_incoming_relationships(
         center_ref: &str,
         all_entities: &[EntityWithSource],
-        entity_map: &HashMap<String, &EntityWithSource>,
         incoming: &mut Vec<(RelationType, EntityNode)>,
     ) {
         for other in all_entities {
@@ -350,7 +352,6 @@ impl RelationshipGraph {
                     center_ref,
                     RelationType::Owner,
                     other,
-                    entity_map,
                     incoming,
                 );
             }
@@ -362,7 +363,6 @@ impl RelationshipGraph {
                     center_ref,
                     RelationType::System,
                     other,
-                    entity_map,
                     incoming,
                 );
             }
@@ -374,7 +374,6 @@ impl RelationshipGraph {
                     center_ref,
                     RelationType::Domain,
                     other,
-                    entity_map,
                     incoming,
                 );
             }
@@ -386,7 +385,6 @@ impl RelationshipGraph {
                     center_ref,
                     RelationType::Child,
                     other,
-                    entity_map,
                     incoming,
                 );
             }
@@ -401,7 +399,6 @@ impl RelationshipGraph {
                     center_ref,
                     RelationType::DependencyOf,
                     other,
-                    entity_map,
                     incoming,
                 );
             }
@@ -413,7 +410,6 @@ impl RelationshipGraph {
                     center_ref,
                     RelationType::ConsumedBy,
                     other,
-                    entity_map,
                     incoming,
                 );
             }
@@ -425,7 +421,6 @@ impl RelationshipGraph {
                     center_ref,
                     RelationType::ProvidedBy,
                     other,
-                    entity_map,
                     incoming,
                 );
             }
@@ -437,17 +432,13 @@ impl RelationshipGraph {
                     center_ref,
                     RelationType::HasMember,
                     other,
-                    entity_map,
                     incoming,
                 );
             }
         }
     }
 
-    fn node_from_entity(
-        entity: &EntityWithSource,
-        _entity_map: &HashMap<String, &EntityWithSource>,
-    ) -> EntityNode {
+    fn node_from_entity(entity: &EntityWithSource) -> EntityNode {
         EntityNode {
             ref_key: entity.entity.ref_key(),
             display_name: entity.entity.display_name(),
@@ -500,6 +491,7 @@ mod tests {
     use super::*;
     use crate::entity::{Entity, EntityKind, Metadata};
     use serde_yaml::Value;
+    use std::collections::HashMap;
     use std::path::PathBuf;
 
     fn create_entity(kind: EntityKind, name: &str, spec: Value) -> EntityWithSource {
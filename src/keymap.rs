@@ -0,0 +1,486 @@
+//! Key bindings: which key runs which [`Action`] in each input mode.
+//!
+//! A [`Keymap`] starts from a preset — `default` (arrows and vim-style
+//! `hjkl`), `vim` (adds `Ctrl-d`/`Ctrl-u`, `G`, …) or `emacs` (`Ctrl-n`/`Ctrl-p`,
+//! `Alt-x`, `Ctrl-g`, …) — and is then adjusted by the `[keys]` table of the
+//! configuration files ([`crate::config`]):
+//!
+//! ```toml
+//! [keys]
+//! preset = "vim"
+//!
+//! [keys.normal]
+//! quit = ["q", "Ctrl-c"]
+//! palette = "Alt-x"   # replaces `:` and Ctrl-P
+//! graph = []          # unbound
+//! ```
+//!
+//! Actions are named by their palette command ([`crate::actions::ACTIONS`]);
+//! keys as `q`, `G`, `Ctrl-p`, `Alt-<`, `Shift-Tab`, `PgDn`, `F5`, `Space`, ….
+
+use crate::actions::{self, Action};
+use crate::config::KeysConfig;
+use anyhow::{bail, Result};
+use crossterm::event::{KeyCode, KeyModifiers};
+use std::fmt;
+use std::str::FromStr;
+
+/// Input modes with their own bindings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// Browsing the tree and detail panel.
+    Normal,
+    /// Typing a `/` search; unbound printable keys are typed into the query.
+    Search,
+    /// The documentation browser.
+    Docs,
+}
+
+/// A key with its modifiers, normalized so that a binding matches however
+/// the terminal reports it (`Shift` is part of a character, `Ctrl-P` is
+/// `Ctrl-p`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        let code = match code {
+            KeyCode::Char(c) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                if modifiers.contains(KeyModifiers::CONTROL) {
+                    KeyCode::Char(c.to_ascii_lowercase())
+                } else {
+                    KeyCode::Char(c)
+                }
+            }
+            // Terminals report Shift-Tab as BackTab, with or without Shift.
+            KeyCode::BackTab => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::BackTab
+            }
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::BackTab
+            }
+            other => other,
+        };
+        Self { code, modifiers }
+    }
+}
+
+impl FromStr for Key {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s.trim();
+        'prefixes: loop {
+            for (prefix, modifier) in [
+                ("ctrl-", KeyModifiers::CONTROL),
+                ("c-", KeyModifiers::CONTROL),
+                ("alt-", KeyModifiers::ALT),
+                ("m-", KeyModifiers::ALT),
+                ("shift-", KeyModifiers::SHIFT),
+                ("s-", KeyModifiers::SHIFT),
+            ] {
+                let matches =
+                    rest.len() > prefix.len() && rest[..prefix.len()].eq_ignore_ascii_case(prefix);
+                if matches {
+                    modifiers |= modifier;
+                    rest = &rest[prefix.len()..];
+                    continue 'prefixes;
+                }
+            }
+            break;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pgup" | "pageup" => KeyCode::PageUp,
+                "pgdn" | "pagedown" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "del" | "delete" => KeyCode::Delete,
+                "ins" | "insert" => KeyCode::Insert,
+                "space" => KeyCode::Char(' '),
+                f if f.len() > 1 && f.starts_with('f') => match f[1..].parse() {
+                    Ok(n @ 1..=12) => KeyCode::F(n),
+                    _ => bail!("Unknown key '{s}'"),
+                },
+                _ => bail!("Unknown key '{s}'"),
+            },
+        };
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) if self.modifiers.contains(KeyModifiers::CONTROL) => {
+                write!(f, "{}", c.to_ascii_uppercase())
+            }
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDn"),
+            KeyCode::BackTab => write!(f, "Shift-Tab"),
+            KeyCode::Delete => write!(f, "Del"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            other => write!(f, "{other:?}"),
+        }
+    }
+}
+
+/// Bindings of a preset: (mode, action, keys).
+type Bindings = &'static [(Mode, Action, &'static [&'static str])];
+
+/// The `default` preset.
+const DEFAULT: Bindings = &[
+    (Mode::Normal, Action::ToggleFocus, &["Tab"]),
+    (Mode::Normal, Action::MoveUp, &["Up", "k"]),
+    (Mode::Normal, Action::MoveDown, &["Down", "j"]),
+    (Mode::Normal, Action::PageUp, &["PgUp"]),
+    (Mode::Normal, Action::PageDown, &["PgDn"]),
+    (Mode::Normal, Action::First, &["Home"]),
+    (Mode::Normal, Action::Last, &["End"]),
+    (Mode::Normal, Action::Collapse, &["Left", "h"]),
+    (Mode::Normal, Action::Expand, &["Right", "l"]),
+    (Mode::Normal, Action::Confirm, &["Enter"]),
+    (Mode::Normal, Action::ExpandAll, &["e"]),
+    (Mode::Normal, Action::CollapseAll, &["c"]),
+    (Mode::Normal, Action::Search, &["/"]),
+    (Mode::Normal, Action::SavedSearches, &["s"]),
+    (Mode::Normal, Action::ClearFilter, &["F"]),
+    (Mode::Normal, Action::ToggleGraph, &["g"]),
+    (Mode::Normal, Action::ToggleRaw, &["y"]),
+    (Mode::Normal, Action::OpenDocs, &["d"]),
+    (Mode::Normal, Action::OpenEditor, &["o"]),
+    (Mode::Normal, Action::EditFields, &["i"]),
+    (Mode::Normal, Action::NewEntity, &["n"]),
+    (Mode::Normal, Action::Reload, &["r"]),
+    (Mode::Normal, Action::AcknowledgeChanges, &["a"]),
+    (Mode::Normal, Action::NextError, &["x"]),
+    (Mode::Normal, Action::PrevError, &["X"]),
+    (Mode::Normal, Action::CommandPalette, &[":", "Ctrl-p"]),
+    (Mode::Normal, Action::Help, &["?"]),
+    (Mode::Normal, Action::Back, &["Esc"]),
+    (Mode::Normal, Action::Quit, &["q"]),
+    (Mode::Search, Action::Back, &["Esc"]),
+    (Mode::Search, Action::Confirm, &["Enter"]),
+    (Mode::Search, Action::Filter, &["Tab"]),
+    (Mode::Search, Action::MoveUp, &["Up"]),
+    (Mode::Search, Action::MoveDown, &["Down"]),
+    (Mode::Docs, Action::Back, &["Esc"]),
+    (Mode::Docs, Action::MoveUp, &["Up", "k"]),
+    (Mode::Docs, Action::MoveDown, &["Down", "j"]),
+    (Mode::Docs, Action::PageUp, &["PgUp"]),
+    (Mode::Docs, Action::PageDown, &["PgDn"]),
+    (Mode::Docs, Action::Confirm, &["Enter"]),
+    (Mode::Docs, Action::Quit, &["q"]),
+];
+
+/// What the `vim` preset adds to `default`.
+const VIM_EXTRA: Bindings = &[
+    (Mode::Normal, Action::PageUp, &["Ctrl-u", "Ctrl-b"]),
+    (Mode::Normal, Action::PageDown, &["Ctrl-d", "Ctrl-f"]),
+    (Mode::Normal, Action::Last, &["G"]),
+    (Mode::Search, Action::MoveUp, &["Ctrl-p", "Ctrl-k"]),
+    (Mode::Search, Action::MoveDown, &["Ctrl-n", "Ctrl-j"]),
+    (Mode::Docs, Action::PageUp, &["Ctrl-u", "Ctrl-b"]),
+    (Mode::Docs, Action::PageDown, &["Ctrl-d", "Ctrl-f"]),
+    (Mode::Docs, Action::Last, &["G"]),
+];
+
+/// The `emacs` preset: no `hjkl`; motion on Ctrl/Alt chords.
+const EMACS: Bindings = &[
+    (Mode::Normal, Action::ToggleFocus, &["Tab"]),
+    (Mode::Normal, Action::MoveUp, &["Up", "Ctrl-p"]),
+    (Mode::Normal, Action::MoveDown, &["Down", "Ctrl-n"]),
+    (Mode::Normal, Action::PageUp, &["PgUp", "Alt-v"]),
+    (Mode::Normal, Action::PageDown, &["PgDn", "Ctrl-v"]),
+    (Mode::Normal, Action::First, &["Home", "Alt-<"]),
+    (Mode::Normal, Action::Last, &["End", "Alt->"]),
+    (Mode::Normal, Action::Collapse, &["Left", "Ctrl-b"]),
+    (Mode::Normal, Action::Expand, &["Right", "Ctrl-f"]),
+    (Mode::Normal, Action::Confirm, &["Enter"]),
+    (Mode::Normal, Action::ExpandAll, &["e"]),
+    (Mode::Normal, Action::CollapseAll, &["c"]),
+    (Mode::Normal, Action::Search, &["Ctrl-s", "/"]),
+    (Mode::Normal, Action::SavedSearches, &["s"]),
+    (Mode::Normal, Action::ClearFilter, &["F"]),
+    (Mode::Normal, Action::ToggleGraph, &["g"]),
+    (Mode::Normal, Action::ToggleRaw, &["y"]),
+    (Mode::Normal, Action::OpenDocs, &["d"]),
+    (Mode::Normal, Action::OpenEditor, &["o"]),
+    (Mode::Normal, Action::EditFields, &["i"]),
+    (Mode::Normal, Action::NewEntity, &["n"]),
+    (Mode::Normal, Action::Reload, &["r"]),
+    (Mode::Normal, Action::AcknowledgeChanges, &["a"]),
+    (Mode::Normal, Action::NextError, &["x"]),
+    (Mode::Normal, Action::PrevError, &["X"]),
+    (Mode::Normal, Action::CommandPalette, &["Alt-x", ":"]),
+    (Mode::Normal, Action::Help, &["?"]),
+    (Mode::Normal, Action::Back, &["Esc", "Ctrl-g"]),
+    (Mode::Normal, Action::Quit, &["q", "Ctrl-x"]),
+    (Mode::Search, Action::Back, &["Esc", "Ctrl-g"]),
+    (Mode::Search, Action::Confirm, &["Enter"]),
+    (Mode::Search, Action::Filter, &["Tab"]),
+    (Mode::Search, Action::MoveUp, &["Up", "Ctrl-p"]),
+    (Mode::Search, Action::MoveDown, &["Down", "Ctrl-n"]),
+    (Mode::Docs, Action::Back, &["Esc", "Ctrl-g"]),
+    (Mode::Docs, Action::MoveUp, &["Up", "Ctrl-p"]),
+    (Mode::Docs, Action::MoveDown, &["Down", "Ctrl-n"]),
+    (Mode::Docs, Action::PageUp, &["PgUp", "Alt-v"]),
+    (Mode::Docs, Action::PageDown, &["PgDn", "Ctrl-v"]),
+    (Mode::Docs, Action::Confirm, &["Enter"]),
+    (Mode::Docs, Action::Quit, &["q"]),
+];
+
+/// Names accepted by `preset`.
+pub const PRESETS: &[&str] = &["default", "vim", "emacs"];
+
+/// The effective key bindings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    /// In preset order, so help lists keys the way the preset does.
+    bindings: Vec<(Mode, Key, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::preset("default").expect("default preset exists")
+    }
+}
+
+impl Keymap {
+    /// A built-in preset by name (see [`PRESETS`]).
+    pub fn preset(name: &str) -> Option<Self> {
+        let tables: &[Bindings] = match name {
+            "default" => &[DEFAULT],
+            "vim" => &[DEFAULT, VIM_EXTRA],
+            "emacs" => &[EMACS],
+            _ => return None,
+        };
+        let mut keymap = Self {
+            bindings: Vec::new(),
+        };
+        for &(mode, action, keys) in tables.iter().flat_map(|table| table.iter()) {
+            for key in keys {
+                let key = key.parse().expect("preset keys parse");
+                keymap.add(mode, key, action);
+            }
+        }
+        Some(keymap)
+    }
+
+    /// Build the keymap described by a `[keys]` table, with a warning for
+    /// each unknown preset, action or key (which are skipped).
+    pub fn from_config(config: &KeysConfig) -> (Self, Vec<String>) {
+        let mut warnings = Vec::new();
+        let preset = config.preset.as_deref().unwrap_or("default");
+        let mut keymap = Self::preset(preset).unwrap_or_else(|| {
+            warnings.push(format!(
+                "Unknown key preset '{preset}' (expected {})",
+                PRESETS.join(", ")
+            ));
+            Self::default()
+        });
+        for (mode, table) in [
+            (Mode::Normal, &config.normal),
+            (Mode::Search, &config.search),
+            (Mode::Docs, &config.docs),
+        ] {
+            for (command, keys) in table {
+                let Some(info) = actions::find(command) else {
+                    warnings.push(format!("Unknown action '{command}' in [keys]"));
+                    continue;
+                };
+                let keys: Vec<Key> = keys
+                    .iter()
+                    .filter_map(|key| match key.parse() {
+                        Ok(key) => Some(key),
+                        Err(e) => {
+                            warnings.push(format!("{e} for '{command}' in [keys]"));
+                            None
+                        }
+                    })
+                    .collect();
+                keymap.bind(mode, info.action, &keys);
+            }
+        }
+        (keymap, warnings)
+    }
+
+    /// Bind `key` to `action` in `mode`, in addition to its other keys and in
+    /// place of whatever `key` ran before.
+    fn add(&mut self, mode: Mode, key: Key, action: Action) {
+        self.bindings.retain(|&(m, k, _)| !(m == mode && k == key));
+        self.bindings.push((mode, key, action));
+    }
+
+    /// Make `keys` the only keys for `action` in `mode` (none unbinds it).
+    pub fn bind(&mut self, mode: Mode, action: Action, keys: &[Key]) {
+        self.bindings
+            .retain(|&(m, _, a)| !(m == mode && a == action));
+        for &key in keys {
+            self.add(mode, key, action);
+        }
+    }
+
+    /// The action `key` runs in `mode`.
+    pub fn action(&self, mode: Mode, key: Key) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|&&(m, k, _)| m == mode && k == key)
+            .map(|&(_, _, action)| action)
+    }
+
+    /// The keys running `action` in `mode`.
+    pub fn keys(&self, mode: Mode, action: Action) -> Vec<Key> {
+        self.bindings
+            .iter()
+            .filter(|&&(m, _, a)| m == mode && a == action)
+            .map(|&(_, key, _)| key)
+            .collect()
+    }
+
+    /// The first key for `action` in `mode`, for hints; `:command` if unbound.
+    pub fn hint(&self, mode: Mode, action: Action) -> String {
+        match self.keys(mode, action).first() {
+            Some(key) => key.to_string(),
+            None => format!(":{}", actions::info(action).command),
+        }
+    }
+
+    /// How to run `action`, for help: its normal-mode keys, else its search
+    /// keys marked `(search)`, else the palette command.
+    pub fn describe(&self, action: Action) -> String {
+        let join = |keys: Vec<Key>| {
+            keys.iter()
+                .map(Key::to_string)
+                .collect::<Vec<_>>()
+                .join(" / ")
+        };
+        let normal = self.keys(Mode::Normal, action);
+        if !normal.is_empty() {
+            return join(normal);
+        }
+        let search = self.keys(Mode::Search, action);
+        if !search.is_empty() {
+            return format!("{} (search)", join(search));
+        }
+        format!(":{}", actions::info(action).command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(s: &str) -> Key {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn keys_parse_and_display_normalized() {
+        for (input, shown) in [
+            ("q", "q"),
+            ("X", "X"),
+            ("Shift-x", "X"),
+            ("ctrl-P", "Ctrl-P"),
+            ("C-p", "Ctrl-P"),
+            ("M-<", "Alt-<"),
+            ("pgdn", "PgDn"),
+            ("Shift-Tab", "Shift-Tab"),
+            ("space", "Space"),
+            ("F5", "F5"),
+            ("-", "-"),
+        ] {
+            assert_eq!(key(input).to_string(), shown, "{input}");
+        }
+        // However the terminal reports them, the same keys match.
+        assert_eq!(Key::new(KeyCode::Char('X'), KeyModifiers::SHIFT), key("X"));
+        assert_eq!(
+            Key::new(KeyCode::Char('P'), KeyModifiers::CONTROL),
+            key("Ctrl-p")
+        );
+        assert!("Hyper-q".parse::<Key>().is_err());
+        assert!("F13".parse::<Key>().is_err());
+    }
+
+    #[test]
+    fn presets_and_overrides() {
+        let default = Keymap::default();
+        assert_eq!(default.action(Mode::Normal, key("k")), Some(Action::MoveUp));
+        assert_eq!(default.action(Mode::Normal, key("G")), None);
+        let vim = Keymap::preset("vim").unwrap();
+        assert_eq!(vim.action(Mode::Normal, key("G")), Some(Action::Last));
+        assert_eq!(vim.action(Mode::Docs, key("G")), Some(Action::Last));
+        let emacs = Keymap::preset("emacs").unwrap();
+        assert_eq!(emacs.action(Mode::Normal, key("k")), None);
+        assert_eq!(
+            emacs.action(Mode::Normal, key("Ctrl-p")),
+            Some(Action::MoveUp)
+        );
+        assert_eq!(emacs.describe(Action::CommandPalette), "Alt-x / :");
+
+        let config: KeysConfig = toml::from_str(
+            r#"
+            preset = "vim"
+            [normal]
+            quit = ["q", "Ctrl-c"]
+            palette = "g"
+            jump = "J"
+            nonsense = "z"
+            help = ["Hyper-h"]
+            "#,
+        )
+        .unwrap();
+        let (keymap, warnings) = Keymap::from_config(&config);
+        assert_eq!(keymap.describe(Action::Quit), "q / Ctrl-C");
+        // `g` moves from the graph to the palette, which loses `:`.
+        assert_eq!(
+            keymap.action(Mode::Normal, key("g")),
+            Some(Action::CommandPalette)
+        );
+        assert_eq!(keymap.action(Mode::Normal, key(":")), None);
+        assert_eq!(keymap.describe(Action::ToggleGraph), ":graph");
+        assert_eq!(keymap.describe(Action::JumpToRelated), "J");
+        assert_eq!(keymap.describe(Action::Help), ":help");
+        assert_eq!(keymap.describe(Action::Filter), "Tab (search)");
+        assert_eq!(warnings.len(), 2, "{warnings:?}");
+    }
+}
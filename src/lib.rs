@@ -9,21 +9,58 @@
 //!
 //! - [`entity`] — entity models, reference parsing, and the lookup index
 //! - [`parser`] — load and validate `catalog-info.yaml` files from disk
+//! - [`remote`] — load a catalog from a Backstage catalog API (`--from-api`)
 //! - [`validator`] — JSON Schema validation of entities
+//! - [`catalog`] — a loaded catalog, kept up to date file by file
+//! - [`cache`] — on-disk cache of parsed and validated catalog files
+//! - [`query`] — catalog query language (`bsv query` and `/` search)
+//! - [`config`] — configuration files (`.bsv.toml`): discovery, lint, output,
+//!   theme, key bindings, saved searches
+//! - [`relations`] — Backstage-style relations computed across the catalog
+//! - [`diff`] — what changed between two loads of a catalog
+//! - [`server`] — Backstage-compatible catalog REST API (`bsv serve`)
+//! - [`lsp`] — language server for catalog files (`bsv lsp`)
+//! - [`yaml_edit`] — format-preserving write-back into catalog YAML files
+//! - [`formatter`] — canonical layout for catalog files (`bsv fmt`)
+//! - [`fix`] — automatic fixes for common catalog problems (`bsv fix`)
 //! - [`tree`] — hierarchical tree built from a flat entity list
 //! - [`graph`] — relationship graph between entities
 //! - [`docs`] — TechDocs / ADR documentation discovery and browsing
+//! - [`edit`] — in-TUI editing of common entity fields
+//! - [`editor`] — open an entity's source file in the user's editor
+//! - [`scaffold`] — the "new entity" wizard behind `bsv new`
+//! - [`actions`] — registry of TUI actions behind the help and command palette
+//! - [`keymap`] — configurable key bindings with vim and emacs presets
 //! - [`app`] — application state and interaction logic
 //! - [`ui`] — ratatui rendering
 
+pub mod actions;
 pub mod app;
+pub mod cache;
+pub mod catalog;
 pub mod cli;
+pub mod config;
+pub mod diff;
 pub mod docs;
+pub mod edit;
+pub mod editor;
 pub mod entity;
+pub mod fix;
+pub mod formatter;
 pub mod graph;
+pub mod keymap;
+pub mod lsp;
 pub mod parser;
+pub mod query;
+pub mod relations;
+pub mod remote;
 pub mod report;
+pub mod scaffold;
+pub mod server;
+#[cfg(test)]
+mod test_support;
 pub mod tree;
 pub mod ui;
 pub mod validator;
 pub mod watcher;
+pub mod yaml_edit;
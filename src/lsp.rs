@@ -0,0 +1,1070 @@
+//! Language server for catalog files (`bsv lsp`).
+//!
+//! Speaks LSP over stdio. The [`Workspace`] keeps the catalog loaded from disk
+//! with any open editor buffers parsed in place of their files, and answers:
+//!
+//! - diagnostics: parse errors, schema errors, unresolved references (with
+//!   "did you mean" suggestions) and duplicate entities
+//! - completion of entity references, `kind`, `lifecycle`, `type` and
+//!   `apiVersion` values
+//! - hover over a reference, showing the referenced entity
+//! - go-to-definition of a reference, across files
+//! - find-references of an entity, via the relationship graph
+
+use crate::edit::LIFECYCLES;
+use crate::entity::{
+    mark_duplicates, EntityIndex, EntityKind, EntityRef, EntityWithSource, ARRAY_REFERENCE_FIELDS,
+    SINGLE_REFERENCE_FIELDS,
+};
+use crate::fix::did_you_mean;
+use crate::graph::RelationshipGraph;
+use crate::parser::{load_catalog, parse_documents, DocumentError};
+use crate::report::{ref_resolves, MAX_SUGGESTIONS};
+use crate::scaffold::{well_known_types, KINDS};
+use crate::validator::allowed_api_versions;
+use crate::yaml_edit::{indent_of, is_blank_or_comment, is_marker, strip_comment};
+use anyhow::Result;
+use lsp_server::{Connection, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+    Notification as _, PublishDiagnostics,
+};
+use lsp_types::request::{Completion, GotoDefinition, HoverRequest, References, Request as _};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, CompletionResponse,
+    CompletionTextEdit, Diagnostic, DiagnosticSeverity, GotoDefinitionParams,
+    GotoDefinitionResponse, Hover, HoverContents, HoverParams, HoverProviderCapability,
+    InitializeParams, Location, MarkupContent, MarkupKind, OneOf, Position,
+    PublishDiagnosticsParams, Range, ReferenceParams, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Uri,
+};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Name diagnostics are published under.
+const SOURCE: &str = "bsv";
+
+/// The catalog as the editor sees it: files on disk, overlaid with open buffers.
+pub struct Workspace {
+    root: PathBuf,
+    disk: Vec<EntityWithSource>,
+    /// Text of open documents, by path.
+    open: HashMap<PathBuf, String>,
+    /// Documents in open buffers that failed to parse, by path.
+    parse_errors: HashMap<PathBuf, Vec<DocumentError>>,
+    entities: Vec<EntityWithSource>,
+    index: EntityIndex,
+}
+
+/// A `key: value` (or list item) under the cursor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FieldAt {
+    /// Key the value belongs to (the parent key for list items).
+    key: String,
+    /// Indentation of the key, to tell top-level `kind` from nested keys.
+    key_indent: usize,
+    /// The whole value token under the cursor.
+    value: String,
+    /// Byte range of `value` on the line (see [`utf16_column`] for the LSP
+    /// column).
+    start: usize,
+    end: usize,
+}
+
+impl Workspace {
+    /// Load the catalog under `root`.
+    pub fn new(root: &Path) -> Self {
+        let mut workspace = Self {
+            root: root.to_path_buf(),
+            disk: Vec::new(),
+            open: HashMap::new(),
+            parse_errors: HashMap::new(),
+            entities: Vec::new(),
+            index: EntityIndex::default(),
+        };
+        workspace.reload_disk();
+        workspace
+    }
+
+    /// Re-read the catalog files (after a save).
+    pub fn reload_disk(&mut self) {
+        self.disk = load_catalog(&self.root).map(|(e, _)| e).unwrap_or_default();
+        self.rebuild();
+    }
+
+    /// Track an open (or changed) buffer.
+    pub fn set_document(&mut self, path: PathBuf, text: String) {
+        self.open.insert(path, text);
+        self.rebuild();
+    }
+
+    /// Stop tracking a closed buffer; its file on disk applies again.
+    pub fn close_document(&mut self, path: &Path) {
+        self.open.remove(path);
+        self.rebuild();
+    }
+
+    /// Paths of the open buffers.
+    pub fn open_documents(&self) -> Vec<PathBuf> {
+        self.open.keys().cloned().collect()
+    }
+
+    fn rebuild(&mut self) {
+        let mut entities: Vec<EntityWithSource> = self
+            .disk
+            .iter()
+            .filter(|e| !self.open.contains_key(&e.source_file))
+            .cloned()
+            .collect();
+        self.parse_errors.clear();
+        for (path, text) in &self.open {
+            let (parsed, errors) = parse_documents(text, path);
+            entities.extend(parsed);
+            self.parse_errors.insert(path.clone(), errors);
+        }
+        mark_duplicates(&mut entities);
+        self.index = EntityIndex::build(&entities);
+        self.entities = entities;
+    }
+
+    /// Current text of `path`: the open buffer, else the file on disk.
+    fn text(&self, path: &Path) -> Option<String> {
+        self.open
+            .get(path)
+            .cloned()
+            .or_else(|| fs::read_to_string(path).ok())
+    }
+
+    /// The entity whose document contains 0-based `line` of `path`.
+    fn entity_at(&self, path: &Path, line: usize) -> Option<&EntityWithSource> {
+        self.entities
+            .iter()
+            .filter(|e| e.source_file == path && e.line <= line + 1)
+            .max_by_key(|e| e.line)
+    }
+
+    fn find(&self, entity_ref: &EntityRef) -> Option<&EntityWithSource> {
+        let key = entity_ref.canonical();
+        self.entities.iter().find(|e| e.entity.ref_key() == key)
+    }
+
+    /// Diagnostics for one document.
+    pub fn diagnostics(&self, path: &Path) -> Vec<Diagnostic> {
+        let Some(text) = self.text(path) else {
+            return Vec::new();
+        };
+        let lines: Vec<String> = text.lines().map(String::from).collect();
+        let mut diagnostics: Vec<Diagnostic> = self
+            .parse_errors
+            .get(path)
+            .into_iter()
+            .flatten()
+            .map(|e| {
+                diagnostic(
+                    line_range(&lines, e.line.saturating_sub(1)),
+                    DiagnosticSeverity::ERROR,
+                    e.message.clone(),
+                )
+            })
+            .collect();
+
+        for ews in self.entities.iter().filter(|e| e.source_file == path) {
+            let (start, end) = document_range(&lines, ews.line.saturating_sub(1));
+            for err in &ews.validation_errors {
+                let key = err.path.rsplit('/').find(|s| !s.is_empty());
+                let line = key
+                    .and_then(|k| key_line(&lines, start, end, k))
+                    .unwrap_or(start);
+                diagnostics.push(diagnostic(
+                    line_range(&lines, line),
+                    DiagnosticSeverity::ERROR,
+                    format!("{}: {}", err.path, err.message),
+                ));
+            }
+            for r in ews.entity.outgoing_references() {
+                if ref_resolves(&self.index, &r.reference, r.default_kind, r.fallback_kinds) {
+                    continue;
+                }
+                let suggestions = did_you_mean(
+                    &self.index,
+                    &r.reference,
+                    r.default_kind,
+                    r.fallback_kinds,
+                    MAX_SUGGESTIONS,
+                );
+                let mut message = format!("{}: {} not found", r.field, r.reference);
+                if !suggestions.is_empty() {
+                    message.push_str(&format!("; did you mean {}?", suggestions.join(", ")));
+                }
+                let range = value_range(&lines, start, end, &r.reference)
+                    .unwrap_or_else(|| line_range(&lines, start));
+                diagnostics.push(diagnostic(range, DiagnosticSeverity::WARNING, message));
+            }
+            if !ews.duplicates.is_empty() {
+                let others: Vec<String> = ews
+                    .duplicates
+                    .iter()
+                    .map(|(file, line)| format!("{}:{line}", file.display()))
+                    .collect();
+                diagnostics.push(diagnostic(
+                    line_range(&lines, start),
+                    DiagnosticSeverity::ERROR,
+                    format!(
+                        "{} is also declared at {}",
+                        ews.entity.ref_key(),
+                        others.join(", ")
+                    ),
+                ));
+            }
+        }
+        diagnostics
+    }
+
+    /// Completions for the value being typed at `position`.
+    pub fn completion(&self, path: &Path, position: Position) -> Vec<CompletionItem> {
+        let Some(text) = self.text(path) else {
+            return Vec::new();
+        };
+        let lines: Vec<String> = text.lines().map(String::from).collect();
+        let line = position.line as usize;
+        let Some(text) = lines.get(line) else {
+            return Vec::new();
+        };
+        let column = byte_column(text, position.character);
+        let Some(field) = field_at(&lines, line, column) else {
+            return Vec::new();
+        };
+        let kind = self
+            .entity_at(path, line)
+            .map(|e| e.entity.kind.clone())
+            .unwrap_or(EntityKind::Component);
+        let replace = Range::new(
+            Position::new(position.line, utf16_column(text, field.start)),
+            Position::new(position.line, utf16_column(text, field.end.max(column))),
+        );
+        let item = |label: String, detail: Option<String>, item_kind| CompletionItem {
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit::new(
+                replace,
+                label.clone(),
+            ))),
+            label,
+            detail,
+            kind: Some(item_kind),
+            ..CompletionItem::default()
+        };
+
+        if let Some((default_kind, fallbacks)) = reference_field(&field.key) {
+            let mut kinds = vec![default_kind];
+            kinds.extend(fallbacks);
+            if field.key == "owner" {
+                kinds.push("user");
+            }
+            return kinds
+                .iter()
+                .flat_map(|k| self.index.refs_of_kind(k))
+                .map(|r| {
+                    let detail = self.find(&r).map(|e| e.entity.display_name());
+                    item(
+                        r.shorthand(default_kind),
+                        detail,
+                        CompletionItemKind::REFERENCE,
+                    )
+                })
+                .collect();
+        }
+
+        let values: Vec<String> = match field.key.as_str() {
+            "kind" if field.key_indent == 0 => KINDS
+                .iter()
+                .map(ToString::to_string)
+                .chain(["Location".to_string()])
+                .collect(),
+            "apiVersion" if field.key_indent == 0 => allowed_api_versions(&kind),
+            "lifecycle" => self.seen(LIFECYCLES, |e| e.entity.lifecycle()),
+            "type" => self.seen(well_known_types(&kind), |e| {
+                (e.entity.kind == kind)
+                    .then(|| e.entity.entity_type())
+                    .flatten()
+            }),
+            _ => Vec::new(),
+        };
+        values
+            .into_iter()
+            .map(|v| item(v, None, CompletionItemKind::ENUM_MEMBER))
+            .collect()
+    }
+
+    /// `known` values followed by other values already used in the catalog.
+    fn seen(
+        &self,
+        known: &[&str],
+        value: impl Fn(&EntityWithSource) -> Option<String>,
+    ) -> Vec<String> {
+        let mut out: Vec<String> = known.iter().map(|s| s.to_string()).collect();
+        let mut extra: Vec<String> = self
+            .entities
+            .iter()
+            .filter_map(value)
+            .filter(|v| !out.contains(v))
+            .collect();
+        extra.sort();
+        extra.dedup();
+        out.extend(extra);
+        out
+    }
+
+    /// The reference under `position`, parsed with its field's default kind,
+    /// and the entity it resolves to (if any).
+    fn reference_at(
+        &self,
+        path: &Path,
+        position: Position,
+    ) -> Option<(EntityRef, Option<&EntityWithSource>)> {
+        let text = self.text(path)?;
+        let lines: Vec<String> = text.lines().map(String::from).collect();
+        let line = position.line as usize;
+        let column = byte_column(lines.get(line)?, position.character);
+        let field = field_at(&lines, line, column)?;
+        let (default_kind, fallbacks) = reference_field(&field.key)?;
+        if field.value.is_empty() {
+            return None;
+        }
+        let parsed = EntityRef::parse(&field.value, default_kind);
+        let candidates = std::iter::once(parsed.clone()).chain(
+            fallbacks
+                .iter()
+                .filter(|_| parsed.kind_inferred)
+                .map(|k| EntityRef::parse(&field.value, k)),
+        );
+        let resolved = candidates
+            .filter_map(|r| self.find(&r).map(|e| (r, e)))
+            .next();
+        Some(match resolved {
+            Some((r, e)) => (r, Some(e)),
+            None => (parsed, None),
+        })
+    }
+
+    /// Details of the referenced entity under `position`.
+    pub fn hover(&self, path: &Path, position: Position) -> Option<Hover> {
+        let (entity_ref, target) = self.reference_at(path, position)?;
+        let value = match target {
+            Some(ews) => entity_markdown(ews),
+            None => {
+                let suggestions = did_you_mean(
+                    &self.index,
+                    &entity_ref.canonical(),
+                    &entity_ref.kind,
+                    &[],
+                    MAX_SUGGESTIONS,
+                );
+                let mut text = format!("`{}` is not in the catalog", entity_ref.canonical());
+                if !suggestions.is_empty() {
+                    text.push_str(&format!("; did you mean `{}`?", suggestions.join("`, `")));
+                }
+                text
+            }
+        };
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: None,
+        })
+    }
+
+    /// Where the referenced entity under `position` is declared.
+    pub fn definition(&self, path: &Path, position: Position) -> Option<Location> {
+        let (_, target) = self.reference_at(path, position)?;
+        target.and_then(|ews| location(&ews.source_file, ews.line.saturating_sub(1)))
+    }
+
+    /// Every place that references the entity under `position` — the
+    /// referenced entity when on a reference, else the entity being edited.
+    pub fn references(
+        &self,
+        path: &Path,
+        position: Position,
+        include_declaration: bool,
+    ) -> Vec<Location> {
+        let target = match self.reference_at(path, position) {
+            Some((_, target)) => target,
+            None => self.entity_at(path, position.line as usize),
+        };
+        let Some(target) = target else {
+            return Vec::new();
+        };
+
+        let mut locations = Vec::new();
+        if include_declaration {
+            locations.extend(location(&target.source_file, target.line.saturating_sub(1)));
+        }
+        let graph = RelationshipGraph::build_indexed(target, &self.entities, &self.index);
+        let target_ref = target.entity.ref_key();
+        for (_, node) in &graph.incoming {
+            for source in self
+                .entities
+                .iter()
+                .filter(|e| e.entity.ref_key() == node.ref_key)
+            {
+                let line = self
+                    .reference_line(source, &target_ref)
+                    .unwrap_or(source.line.saturating_sub(1));
+                locations.extend(location(&source.source_file, line));
+            }
+        }
+        locations.dedup();
+        locations
+    }
+
+    /// 0-based line in `source`'s document holding a reference to `target_ref`.
+    fn reference_line(&self, source: &EntityWithSource, target_ref: &str) -> Option<usize> {
+        let reference = source.entity.outgoing_references().into_iter().find(|r| {
+            let parsed = EntityRef::parse(&r.reference, r.default_kind);
+            parsed.canonical() == target_ref
+                || (parsed.kind_inferred
+                    && r.fallback_kinds
+                        .iter()
+                        .any(|k| EntityRef::parse(&r.reference, k).canonical() == target_ref))
+        })?;
+        let text = self.text(&source.source_file)?;
+        let lines: Vec<String> = text.lines().map(String::from).collect();
+        let (start, end) = document_range(&lines, source.line.saturating_sub(1));
+        value_range(&lines, start, end, &reference.reference).map(|r| r.start.line as usize)
+    }
+}
+
+/// The default and fallback kinds of a reference field.
+fn reference_field(key: &str) -> Option<(&'static str, &'static [&'static str])> {
+    SINGLE_REFERENCE_FIELDS
+        .iter()
+        .find(|(field, _)| *field == key)
+        .map(|&(_, kind)| (kind, &[][..]))
+        .or_else(|| {
+            ARRAY_REFERENCE_FIELDS
+                .iter()
+                .find(|(field, _, _)| *field == key)
+                .map(|&(_, kind, fallbacks)| (kind, fallbacks))
+        })
+}
+
+/// Markdown summary of an entity for hovers.
+fn entity_markdown(ews: &EntityWithSource) -> String {
+    let entity = &ews.entity;
+    let mut out = format!("**{}** `{}`", entity.kind, entity.ref_key());
+    if let Some(title) = &entity.metadata.title {
+        out.push_str(&format!(" — {title}"));
+    }
+    if let Some(description) = &entity.metadata.description {
+        out.push_str(&format!("\n\n{description}"));
+    }
+    out.push('\n');
+    for (label, value) in [
+        ("Type", entity.entity_type()),
+        ("Owner", entity.owner()),
+        ("Lifecycle", entity.lifecycle()),
+        ("System", entity.system()),
+    ] {
+        if let Some(value) = value {
+            out.push_str(&format!("\n- {label}: `{value}`"));
+        }
+    }
+    out.push_str(&format!(
+        "\n\nDefined in `{}:{}`",
+        ews.source_file.display(),
+        ews.line
+    ));
+    out
+}
+
+fn diagnostic(range: Range, severity: DiagnosticSeverity, message: String) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        source: Some(SOURCE.to_string()),
+        message,
+        ..Diagnostic::default()
+    }
+}
+
+/// The `[start, end)` lines of the document starting at 0-based `start`.
+fn document_range(lines: &[String], start: usize) -> (usize, usize) {
+    let end = (start + 1..lines.len())
+        .find(|&i| is_marker(&lines[i]))
+        .unwrap_or(lines.len());
+    (start, end)
+}
+
+/// The content of a whole line, as a range.
+fn line_range(lines: &[String], line: usize) -> Range {
+    let text = lines.get(line).map_or("", String::as_str);
+    let indent = indent_of(text);
+    Range::new(
+        Position::new(line as u32, indent as u32),
+        Position::new(line as u32, utf16_column(text, text.len())),
+    )
+}
+
+/// The byte offset in `text` of an LSP `character`, which counts UTF-16
+/// code units. A column past the end of the line is the line's length.
+fn byte_column(text: &str, character: u32) -> usize {
+    let mut units = 0;
+    for (at, c) in text.char_indices() {
+        if units >= character as usize {
+            return at;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+/// The LSP `character` (in UTF-16 code units) of byte offset `at` in `text`.
+fn utf16_column(text: &str, at: usize) -> u32 {
+    text[..at].encode_utf16().count() as u32
+}
+
+/// The first line in `[start, end)` declaring `key:` at any indentation.
+fn key_line(lines: &[String], start: usize, end: usize, key: &str) -> Option<usize> {
+    (start..end.min(lines.len())).find(|&i| {
+        let line = lines[i].trim_start().trim_start_matches("- ");
+        !is_blank_or_comment(&lines[i])
+            && line.strip_prefix(key).is_some_and(|r| r.starts_with(':'))
+    })
+}
+
+/// Where `value` is written as a field value in `[start, end)`.
+fn value_range(lines: &[String], start: usize, end: usize, value: &str) -> Option<Range> {
+    (start..end.min(lines.len())).find_map(|i| {
+        let field = field_at(lines, i, usize::MAX)?;
+        let line = &lines[i];
+        // A flow list holds several values; look for the one that matches.
+        let found = if field.value == value {
+            Some((field.start, field.end))
+        } else {
+            line.match_indices(value)
+                .map(|(at, _)| (at, at + value.len()))
+                .find(|&(s, e)| {
+                    field_at(lines, i, s).is_some_and(|f| f.value == value && f.end == e)
+                })
+        };
+        found.map(|(s, e)| {
+            Range::new(
+                Position::new(i as u32, utf16_column(line, s)),
+                Position::new(i as u32, utf16_column(line, e)),
+            )
+        })
+    })
+}
+
+/// The field value at 0-based `line` and byte `column`: a `key: value` line, a
+/// `- item` under a key, or one entry of a `key: [a, b]` flow list. A column
+/// past the end of the line picks the last token.
+fn field_at(lines: &[String], line: usize, column: usize) -> Option<FieldAt> {
+    let text = lines.get(line)?;
+    if is_blank_or_comment(text) && !text.trim_start().starts_with('-') && text.trim().is_empty() {
+        // An empty line has no key to complete for.
+        return None;
+    }
+    let indent = indent_of(text);
+    let content = &text[indent..];
+
+    let (key, key_indent, value_offset) = if let Some(item) = content.strip_prefix('-') {
+        let offset = indent + 1 + (item.len() - item.trim_start().len());
+        let (key, key_indent) = parent_key(lines, line, indent)?;
+        (key, key_indent, offset)
+    } else {
+        let colon = content.find(':')?;
+        let key = content[..colon]
+            .trim()
+            .trim_matches(['"', '\''])
+            .to_string();
+        let after = &content[colon + 1..];
+        let offset = indent + colon + 1 + (after.len() - after.trim_start().len());
+        (key, indent, offset)
+    };
+
+    let raw = strip_comment(&text[value_offset..]);
+    let (mut start, mut end) = (value_offset, value_offset + raw.trim_end().len());
+    if raw.starts_with('[') {
+        // Flow list: narrow to the entry around the cursor.
+        let inner_start = value_offset + 1;
+        let inner_end = value_offset + raw.rfind(']').unwrap_or(raw.len());
+        let column = column.clamp(inner_start, inner_end.max(inner_start));
+        start = text[inner_start..column]
+            .rfind(',')
+            .map_or(inner_start, |i| inner_start + i + 1);
+        end = text[column..inner_end]
+            .find(',')
+            .map_or(inner_end, |i| column + i);
+    }
+    let token = &text[start..end];
+    let trimmed_start = start + (token.len() - token.trim_start().len());
+    let token = token.trim();
+    let unquoted = token.trim_matches(['"', '\'']);
+    let quote_offset = (token.len() - unquoted.len()) / 2;
+    let start = trimmed_start + quote_offset;
+    Some(FieldAt {
+        key,
+        key_indent,
+        value: unquoted.to_string(),
+        start,
+        end: start + unquoted.len(),
+    })
+}
+
+/// The `key:` that owns the list item at `line` (indented at `indent`).
+fn parent_key(lines: &[String], line: usize, indent: usize) -> Option<(String, usize)> {
+    (0..line).rev().find_map(|i| {
+        let text = &lines[i];
+        if is_blank_or_comment(text) {
+            return None;
+        }
+        let key_indent = indent_of(text);
+        let content = text[key_indent..].trim_start_matches("- ");
+        if key_indent > indent || (key_indent == indent && text[key_indent..].starts_with('-')) {
+            return None;
+        }
+        let (key, rest) = content.split_once(':')?;
+        strip_comment(rest)
+            .trim()
+            .is_empty()
+            .then(|| (key.trim().to_string(), key_indent))
+    })
+}
+
+/// A location at the start of 0-based `line` in `path`.
+fn location(path: &Path, line: usize) -> Option<Location> {
+    let position = Position::new(line as u32, 0);
+    Some(Location::new(
+        uri_from_path(path)?,
+        Range::new(position, position),
+    ))
+}
+
+/// `file://` URI for a path.
+fn uri_from_path(path: &Path) -> Option<Uri> {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().ok()?.join(path)
+    };
+    let mut encoded = String::new();
+    for byte in absolute.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char);
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    Uri::from_str(&format!("file://{encoded}")).ok()
+}
+
+/// Local path of a `file://` URI.
+fn path_from_uri(uri: &Uri) -> Option<PathBuf> {
+    let rest = uri.as_str().strip_prefix("file://")?;
+    let bytes = rest.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%' && i + 2 < bytes.len())
+            .then(|| std::str::from_utf8(&bytes[i + 1..i + 3]).ok())
+            .flatten()
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(b) => {
+                out.push(b);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    Some(PathBuf::from(String::from_utf8_lossy(&out).into_owned()))
+}
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![":".into(), " ".into(), "/".into()]),
+            ..CompletionOptions::default()
+        }),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        ..ServerCapabilities::default()
+    }
+}
+
+/// Workspace root from the client's initialize request.
+#[allow(deprecated)]
+fn workspace_root(params: &InitializeParams) -> Option<PathBuf> {
+    params
+        .workspace_folders
+        .as_ref()
+        .and_then(|folders| folders.first())
+        .map(|folder| &folder.uri)
+        .or(params.root_uri.as_ref())
+        .and_then(path_from_uri)
+}
+
+/// Run the language server on stdin/stdout until the client shuts it down.
+pub fn run_stdio() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    let params = connection.initialize(serde_json::to_value(capabilities())?)?;
+    let params: InitializeParams = serde_json::from_value(params)?;
+    let root = workspace_root(&params)
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_else(|| PathBuf::from("."));
+    let mut workspace = Workspace::new(&root);
+
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    break;
+                }
+                let response = handle_request(&workspace, request);
+                connection.sender.send(Message::Response(response))?;
+            }
+            Message::Notification(notification) => {
+                let closed = (notification.method == DidCloseTextDocument::METHOD)
+                    .then(|| notification.params["textDocument"]["uri"].clone())
+                    .and_then(|uri| serde_json::from_value::<Uri>(uri).ok());
+                if handle_notification(&mut workspace, notification) {
+                    // The client keeps a file's last diagnostics until they
+                    // are replaced, so clear them once it is closed.
+                    if let Some(uri) = closed {
+                        send_diagnostics(&connection, uri, Vec::new())?;
+                    }
+                    publish_diagnostics(&connection, &workspace)?;
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+    // The writer thread stops once the connection is gone.
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+/// Apply a document notification; returns whether diagnostics should be
+/// republished.
+fn handle_notification(workspace: &mut Workspace, notification: Notification) -> bool {
+    let uri_param = |params: &serde_json::Value| {
+        serde_json::from_value::<Uri>(params["textDocument"]["uri"].clone())
+            .ok()
+            .and_then(|uri| path_from_uri(&uri))
+    };
+    let Some(path) = uri_param(&notification.params) else {
+        return false;
+    };
+    match notification.method.as_str() {
+        DidOpenTextDocument::METHOD => {
+            let text = notification.params["textDocument"]["text"]
+                .as_str()
+                .unwrap_or_default();
+            workspace.set_document(path, text.to_string());
+        }
+        DidChangeTextDocument::METHOD => {
+            // Full sync: the last change carries the whole text.
+            let Some(text) = notification.params["contentChanges"]
+                .as_array()
+                .and_then(|changes| changes.last())
+                .and_then(|change| change["text"].as_str())
+            else {
+                return false;
+            };
+            workspace.set_document(path, text.to_string());
+        }
+        DidSaveTextDocument::METHOD => workspace.reload_disk(),
+        DidCloseTextDocument::METHOD => workspace.close_document(&path),
+        _ => return false,
+    }
+    true
+}
+
+fn publish_diagnostics(connection: &Connection, workspace: &Workspace) -> Result<()> {
+    for path in workspace.open_documents() {
+        let Some(uri) = uri_from_path(&path) else {
+            continue;
+        };
+        send_diagnostics(connection, uri, workspace.diagnostics(&path))?;
+    }
+    Ok(())
+}
+
+/// Replace the client's diagnostics for `uri`.
+fn send_diagnostics(connection: &Connection, uri: Uri, diagnostics: Vec<Diagnostic>) -> Result<()> {
+    let params = PublishDiagnosticsParams::new(uri, diagnostics, None);
+    connection
+        .sender
+        .send(Message::Notification(Notification::new(
+            PublishDiagnostics::METHOD.to_string(),
+            params,
+        )))?;
+    Ok(())
+}
+
+fn handle_request(workspace: &Workspace, request: Request) -> Response {
+    let id = request.id.clone();
+    let result = match request.method.as_str() {
+        Completion::METHOD => serde_json::from_value::<CompletionParams>(request.params).map(|p| {
+            let position = p.text_document_position;
+            path_from_uri(&position.text_document.uri)
+                .map(|path| {
+                    CompletionResponse::Array(workspace.completion(&path, position.position))
+                })
+                .map_or(serde_json::Value::Null, |r| serde_json::json!(r))
+        }),
+        HoverRequest::METHOD => serde_json::from_value::<HoverParams>(request.params).map(|p| {
+            let position = p.text_document_position_params;
+            path_from_uri(&position.text_document.uri)
+                .and_then(|path| workspace.hover(&path, position.position))
+                .map_or(serde_json::Value::Null, |h| serde_json::json!(h))
+        }),
+        GotoDefinition::METHOD => serde_json::from_value::<GotoDefinitionParams>(request.params)
+            .map(|p| {
+                let position = p.text_document_position_params;
+                path_from_uri(&position.text_document.uri)
+                    .and_then(|path| workspace.definition(&path, position.position))
+                    .map_or(serde_json::Value::Null, |l| {
+                        serde_json::json!(GotoDefinitionResponse::Scalar(l))
+                    })
+            }),
+        References::METHOD => serde_json::from_value::<ReferenceParams>(request.params).map(|p| {
+            let position = p.text_document_position;
+            let locations = path_from_uri(&position.text_document.uri)
+                .map(|path| {
+                    workspace.references(&path, position.position, p.context.include_declaration)
+                })
+                .unwrap_or_default();
+            serde_json::json!(locations)
+        }),
+        method => {
+            return Response::new_err(
+                id,
+                lsp_server::ErrorCode::MethodNotFound as i32,
+                format!("unsupported request: {method}"),
+            )
+        }
+    };
+    match result {
+        Ok(value) => Response::new_ok(id, value),
+        Err(e) => Response::new_err(
+            id,
+            lsp_server::ErrorCode::InvalidParams as i32,
+            e.to_string(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    const SERVICES: &str = "\
+apiVersion: backstage.io/v1alpha1
+kind: Component
+metadata:
+  name: checkout
+  title: Checkout
+spec:
+  type: service
+  lifecycle: production
+  owner: payments
+  dependsOn:
+    - orders-db
+    - component:cart
+---
+apiVersion: backstage.io/v1alpha1
+kind: Component
+metadata:
+  name: web
+spec:
+  type: website
+  lifecycle: production
+  owner: paymnets
+  dependsOn: [checkout]
+";
+
+    const TEAMS: &str = "\
+apiVersion: backstage.io/v1alpha1
+kind: Group
+metadata:
+  name: payments
+spec:
+  type: team
+  children: []
+---
+apiVersion: backstage.io/v1alpha1
+kind: Resource
+metadata:
+  name: orders-db
+spec:
+  type: database
+  owner: payments
+";
+
+    /// A workspace over a temp dir holding `teams/catalog-info.yaml`, with
+    /// `SERVICES` open (unsaved) at `services/catalog-info.yaml`. The dir is
+    /// removed when the returned guard drops.
+    fn workspace(name: &str) -> (Workspace, PathBuf, PathBuf, TempDir) {
+        let dir = TempDir::new(&format!("lsp-{name}"));
+        dir.disable_cache();
+        fs::create_dir_all(dir.join("teams")).unwrap();
+        let teams = dir.join("teams/catalog-info.yaml");
+        fs::write(&teams, TEAMS).unwrap();
+        let services = dir.join("services/catalog-info.yaml");
+        let mut workspace = Workspace::new(&dir);
+        workspace.set_document(services.clone(), SERVICES.to_string());
+        (workspace, services, teams, dir)
+    }
+
+    fn at(line: u32, character: u32) -> Position {
+        Position::new(line, character)
+    }
+
+    #[test]
+    fn finds_fields_under_the_cursor() {
+        let lines: Vec<String> = SERVICES.lines().map(String::from).collect();
+        let owner = field_at(&lines, 8, 12).unwrap();
+        assert_eq!(
+            (owner.key.as_str(), owner.value.as_str()),
+            ("owner", "payments")
+        );
+        assert_eq!((owner.start, owner.end), (9, 17));
+
+        let item = field_at(&lines, 11, 8).unwrap();
+        assert_eq!(
+            (item.key.as_str(), item.value.as_str()),
+            ("dependsOn", "component:cart")
+        );
+
+        let flow = field_at(&lines, 21, 15).unwrap();
+        assert_eq!(
+            (flow.key.as_str(), flow.value.as_str()),
+            ("dependsOn", "checkout")
+        );
+        assert_eq!((flow.start, flow.end), (14, 22));
+    }
+
+    #[test]
+    fn reports_unresolved_references_with_suggestions() {
+        let (workspace, services, _, _dir) = workspace("diagnostics");
+        let diagnostics = workspace.diagnostics(&services);
+        let messages: Vec<(u32, &str)> = diagnostics
+            .iter()
+            .map(|d| (d.range.start.line, d.message.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                (11, "dependsOn: component:cart not found"),
+                (20, "owner: paymnets not found; did you mean payments?"),
+            ]
+        );
+        assert_eq!(diagnostics[1].range.start.character, 9);
+        assert_eq!(diagnostics[1].severity, Some(DiagnosticSeverity::WARNING));
+    }
+
+    #[test]
+    fn completes_references_and_enums() {
+        let (workspace, services, _, _dir) = workspace("completion");
+        let labels = |items: Vec<CompletionItem>| -> Vec<String> {
+            items.into_iter().map(|i| i.label).collect()
+        };
+        assert_eq!(
+            labels(workspace.completion(&services, at(8, 12))),
+            vec!["payments"]
+        );
+        assert_eq!(
+            labels(workspace.completion(&services, at(10, 8))),
+            vec!["checkout", "web", "resource:orders-db"]
+        );
+        assert_eq!(
+            labels(workspace.completion(&services, at(7, 13))),
+            vec!["experimental", "production", "deprecated"]
+        );
+        assert!(labels(workspace.completion(&services, at(1, 7))).contains(&"API".to_string()));
+    }
+
+    #[test]
+    fn positions_count_utf16_code_units() {
+        let (mut workspace, services, _, _dir) = workspace("utf16");
+        let text = SERVICES.replace("dependsOn: [checkout]", "dependsOn: [é, wbe]");
+        assert_ne!(text, SERVICES);
+        workspace.set_document(services.clone(), text);
+
+        // `é` is two bytes but one UTF-16 unit: the cursor after it is at 15.
+        let items = workspace.completion(&services, at(21, 15));
+        let Some(CompletionTextEdit::Edit(edit)) = &items[0].text_edit else {
+            panic!("expected a text edit");
+        };
+        assert_eq!(
+            (edit.range.start.character, edit.range.end.character),
+            (14, 15)
+        );
+        let broken = workspace
+            .diagnostics(&services)
+            .into_iter()
+            .find(|d| d.message.starts_with("dependsOn: wbe"))
+            .unwrap();
+        assert_eq!(
+            (broken.range.start.character, broken.range.end.character),
+            (17, 20)
+        );
+        // Hovering and jumping from the same column don't panic either.
+        workspace.hover(&services, at(21, 15));
+        workspace.definition(&services, at(21, 15));
+    }
+
+    #[test]
+    fn hovers_and_jumps_to_definitions_across_files() {
+        let (workspace, services, teams, _dir) = workspace("definition");
+        let hover = workspace.hover(&services, at(8, 12)).unwrap();
+        let HoverContents::Markup(markup) = hover.contents else {
+            panic!("expected markdown");
+        };
+        assert!(markup
+            .value
+            .starts_with("**Group** `group:default/payments`"));
+
+        let definition = workspace.definition(&services, at(10, 8)).unwrap();
+        assert_eq!(path_from_uri(&definition.uri).unwrap(), teams);
+        assert_eq!(definition.range.start.line, 8);
+    }
+
+    #[test]
+    fn finds_references_through_the_graph() {
+        let (workspace, services, teams, _dir) = workspace("references");
+        // Cursor inside the payments group's document.
+        let mut found: Vec<(PathBuf, u32)> = workspace
+            .references(&teams, at(3, 2), false)
+            .into_iter()
+            .map(|l| (path_from_uri(&l.uri).unwrap(), l.range.start.line))
+            .collect();
+        found.sort();
+        assert_eq!(found, vec![(services.clone(), 8), (teams, 14)]);
+
+        // On a reference, the referenced entity's references are found.
+        let web = workspace.references(&services, at(21, 15), true);
+        assert_eq!(web.len(), 2);
+    }
+
+    #[test]
+    fn uris_round_trip() {
+        let path = PathBuf::from("/tmp/my catalog/catalog-info.yaml");
+        let uri = uri_from_path(&path).unwrap();
+        assert_eq!(uri.as_str(), "file:///tmp/my%20catalog/catalog-info.yaml");
+        assert_eq!(path_from_uri(&uri).unwrap(), path);
+    }
+}
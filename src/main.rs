@@ -1,16 +1,31 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use bsv::actions::Action;
 use bsv::app::{App, InputMode};
-use bsv::cli::{parse_args, Command};
-use bsv::parser::load_all_entities;
-use bsv::watcher::CatalogWatcher;
-use bsv::{report, ui};
+use bsv::cli::{parse_args, ApiView, Command, FixMode, OutputFormat};
+use bsv::config::{self, Config, LintConfig};
+use bsv::diff::CatalogDiff;
+use bsv::entity::{EntityIndex, EntityWithSource};
+use bsv::fix;
+use bsv::formatter::{self, FormatOptions};
+use bsv::keymap::{Key, Mode};
+use bsv::lsp;
+use bsv::parser::{discover_catalog_files_with, load_all_entities, Discovery};
+use bsv::query::Query;
+use bsv::remote::load_from_api;
+use bsv::server;
+use bsv::watcher::{CatalogWatcher, RELOAD_DEBOUNCE};
+use bsv::{editor, report, scaffold, ui};
 use crossterm::{
+    cursor::MoveTo,
     event::{
-        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseEvent,
-        MouseEventKind,
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers,
+        MouseEvent, MouseEventKind,
     },
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{
+        disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
 };
 use ratatui::{
     backend::CrosstermBackend,
@@ -18,8 +33,9 @@ use ratatui::{
     Terminal,
 };
 use std::{
-    env, io,
-    path::PathBuf,
+    env, fs,
+    io::{self, IsTerminal, Write},
+    path::{Path, PathBuf},
     process::ExitCode,
     time::{Duration, Instant},
 };
@@ -29,18 +45,61 @@ bsv - Backstage Entity Visualizer
 
 USAGE:
     bsv [PATH]
-    bsv --validate [PATH]
+    bsv --validate [--watch] [PATH]
     bsv --json [PATH]
+    bsv [--validate | --json] --from-api URL
+    bsv new [PATH]
+    bsv fmt [--check] [--refs keep|full|minimal] [PATH]
+    bsv fix [--apply | --interactive] [PATH]
+    bsv query EXPR [--format table|json] [PATH]
+    bsv serve [--port N] [PATH]
+    bsv lsp
+    bsv config show [PATH]
 
 ARGS:
-    PATH    Directory to scan for catalog-info.yaml files, or a single
-            catalog file. Defaults to the current directory.
+    PATH    Directory to scan for catalog-info.yaml files (skipping paths in
+            .gitignore and .bsvignore), or a single catalog file. Defaults
+            to the current directory.
 
 OPTIONS:
     --validate       Validate the catalog and print a report (non-zero exit on errors)
+        -w, --watch  Keep running, printing a fresh report whenever catalog
+                     files change
     --json           Print the parsed catalog as JSON
+    --from-api URL   Load the catalog from a Backstage catalog API
+                     (e.g. http://localhost:7007/api/catalog) instead of files;
+                     set BACKSTAGE_TOKEN to send a bearer token
     -h, --help       Print this help and exit
-    -V, --version    Print version and exit";
+    -V, --version    Print version and exit
+
+COMMANDS:
+    new              Create an entity with an interactive wizard and write it to
+                     a catalog file (or a new catalog-info.yaml)
+    fmt              Rewrite catalog files in canonical form: key order, sorted
+                     tags, `---` separators; comments are kept
+        --check      Only list files that would change (non-zero exit if any)
+        --refs       Rewrite spec references: keep (default), full
+                     (kind:namespace/name) or minimal
+    fix              Propose fixes: near-miss references, missing kind: prefixes,
+                     missing required fields, unsupported apiVersion
+        --apply      Apply every proposed fix
+        -i, --interactive
+                     Ask before applying each fix
+    query            Print the entities matching EXPR, e.g.
+                     'kind:component AND (spec.type=service OR has:techdocs)';
+                     exits 1 if nothing matches
+        -f, --format Output as table (default) or json
+    serve            Serve the catalog over the Backstage catalog REST API at
+                     http://127.0.0.1:PORT/api/catalog, reloading on changes
+        -p, --port   Port to listen on (default 7007)
+    lsp              Run a language server for catalog files over stdio:
+                     diagnostics, completion, hover, go-to-definition and
+                     find-references
+    config show      Print the effective configuration: the nearest .bsv.toml
+                     at or above PATH merged with the user's config.toml
+
+Defaults for discovery, lint severities, --format, --refs, the theme and key
+bindings can be set in .bsv.toml; see `bsv config show`.";
 
 fn main() -> ExitCode {
     let args: Vec<String> = env::args().collect();
@@ -58,8 +117,43 @@ fn main() -> ExitCode {
             eprintln!("{HELP}");
             ExitCode::from(2)
         }
-        Command::Validate(path) => run_validate(resolve_path(path)),
+        Command::Validate { path, watch } => {
+            if watch {
+                run_validate_watch(resolve_path(path))
+            } else {
+                run_validate(resolve_path(path))
+            }
+        }
+        Command::ConfigShow(path) => run_config_show(&resolve_path(path)),
         Command::Json(path) => run_json(resolve_path(path)),
+        Command::New(path) => run_new(resolve_path(path)),
+        Command::Fix { path, mode } => run_fix(resolve_path(path), mode),
+        Command::Fmt { path, check, refs } => {
+            let root = resolve_path(path);
+            let config = load_config(&root);
+            let refs = refs.or(config.output.refs).unwrap_or_default();
+            run_fmt(root, check, &FormatOptions { refs }, &config)
+        }
+        Command::Serve { path, port } => match server::serve(&resolve_path(path), port) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("error: {e:#}");
+                ExitCode::FAILURE
+            }
+        },
+        Command::Query { expr, path, format } => {
+            let root = resolve_path(path);
+            let format = format.or(load_config(&root).output.format);
+            run_query(&expr, root, format.unwrap_or_default())
+        }
+        Command::Lsp => match lsp::run_stdio() {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("error: {e:#}");
+                ExitCode::FAILURE
+            }
+        },
+        Command::FromApi { url, view } => run_from_api(&url, view),
         Command::Run(path) => match run_tui(resolve_path(path)) {
             Ok(()) => ExitCode::SUCCESS,
             Err(e) => {
@@ -74,6 +168,38 @@ fn resolve_path(path: Option<PathBuf>) -> PathBuf {
     path.unwrap_or_else(|| env::current_dir().unwrap_or_else(|_| PathBuf::from(".")))
 }
 
+/// The configuration for the catalog at `root`, printing any problems
+/// reading it.
+fn load_config(root: &Path) -> Config {
+    let (config, warnings) = Config::load(root);
+    for warning in warnings {
+        eprintln!("warning: {warning}");
+    }
+    config
+}
+
+/// Print the merged configuration for the catalog at `root`, with defaults
+/// filled in, preceded by the files it was read from.
+fn run_config_show(root: &Path) -> ExitCode {
+    let files = config::config_files(root);
+    let config = load_config(root).effective();
+    let toml = match config.to_toml() {
+        Ok(toml) => toml,
+        Err(e) => {
+            eprintln!("error: {e:#}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if files.is_empty() {
+        println!("# No configuration files found; showing the defaults.");
+    }
+    for file in files {
+        println!("# From {}", file.display());
+    }
+    println!("\n{toml}");
+    ExitCode::SUCCESS
+}
+
 /// Validate the catalog and print a report; exit non-zero on any problem.
 fn run_validate(root: PathBuf) -> ExitCode {
     let entities = match load_all_entities(&root) {
@@ -83,7 +209,72 @@ fn run_validate(root: PathBuf) -> ExitCode {
             return ExitCode::FAILURE;
         }
     };
-    let report = report::build_report(&entities);
+    validate_entities(&entities, &load_config(&root).lint)
+}
+
+/// Validate the catalog, then validate it again whenever its files change,
+/// each time replacing the previous report (or, when stdout is not a
+/// terminal, printing the new one after it). Runs until interrupted.
+fn run_validate_watch(root: PathBuf) -> ExitCode {
+    let watcher = match CatalogWatcher::new(&root) {
+        Ok(w) => w,
+        Err(e) => {
+            eprintln!("error: cannot watch {}: {e:#}", root.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut previous = validate_again(&root, None);
+    let mut pending_reload: Option<Instant> = None;
+    loop {
+        // Debounced as in the TUI, so one save prints one report.
+        if !watcher.drain().is_empty() {
+            pending_reload = Some(Instant::now());
+        }
+        if pending_reload.is_some_and(|since| since.elapsed() >= RELOAD_DEBOUNCE) {
+            pending_reload = None;
+            previous = validate_again(&root, previous);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// One round of `--validate --watch`: load the catalog and print its report,
+/// headed by what changed since the `previous` round. Returns the entities
+/// to compare the next round against (the previous ones if loading failed).
+fn validate_again(
+    root: &Path,
+    previous: Option<Vec<EntityWithSource>>,
+) -> Option<Vec<EntityWithSource>> {
+    let mut stdout = io::stdout().lock();
+    if stdout.is_terminal() {
+        let _ = execute!(stdout, Clear(ClearType::All), MoveTo(0, 0));
+    } else if previous.is_some() {
+        let _ = writeln!(stdout);
+    }
+    drop(stdout);
+
+    let current = match load_all_entities(root) {
+        Ok(entities) => {
+            if let Some(previous) = &previous {
+                let diff = CatalogDiff::between(previous, &entities);
+                println!("Reloaded: {}\n", diff.summary());
+            }
+            validate_entities(&entities, &load_config(root).lint);
+            Some(entities)
+        }
+        Err(e) => {
+            eprintln!("error: failed to load catalog from {}: {e}", root.display());
+            previous
+        }
+    };
+    println!("\nWatching {} for changes (Ctrl-C to stop)", root.display());
+    current
+}
+
+/// Print the validation report for loaded entities, with the problems
+/// `lint` turns off or into warnings left out or not failing.
+fn validate_entities(entities: &[EntityWithSource], lint: &LintConfig) -> ExitCode {
+    let report = report::build_report(entities).with_lint(lint);
     let mut stdout = io::stdout().lock();
     let _ = report::write_report(&report, &mut stdout);
     if report.has_errors() {
@@ -102,8 +293,13 @@ fn run_json(root: PathBuf) -> ExitCode {
             return ExitCode::FAILURE;
         }
     };
+    print_json(&entities)
+}
+
+/// Print loaded entities as JSON.
+fn print_json(entities: &[EntityWithSource]) -> ExitCode {
     let mut stdout = io::stdout().lock();
-    match report::write_json(&entities, &mut stdout) {
+    match report::write_json(entities, &mut stdout) {
         Ok(()) => ExitCode::SUCCESS,
         Err(e) => {
             eprintln!("error: {e}");
@@ -112,8 +308,241 @@ fn run_json(root: PathBuf) -> ExitCode {
     }
 }
 
+/// Print the entities matching a query; exit 1 if none match.
+fn run_query(expr: &str, root: PathBuf, format: OutputFormat) -> ExitCode {
+    let query = match Query::parse(expr) {
+        Ok(q) => q,
+        Err(e) => {
+            eprintln!("error: invalid query: {e}");
+            return ExitCode::from(2);
+        }
+    };
+    let entities = match load_all_entities(&root) {
+        Ok(e) => e,
+        Err(e) => {
+            eprintln!("error: failed to load catalog from {}: {e}", root.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    let matches: Vec<EntityWithSource> =
+        entities.into_iter().filter(|e| query.matches(e)).collect();
+    let mut stdout = io::stdout().lock();
+    let written = match format {
+        OutputFormat::Table => report::write_table(&matches, &mut stdout),
+        OutputFormat::Json => report::write_json(&matches, &mut stdout),
+    };
+    match written {
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+        Ok(()) if matches.is_empty() => ExitCode::FAILURE,
+        Ok(()) => ExitCode::SUCCESS,
+    }
+}
+
+/// Create an entity with the prompt-driven wizard and write it to disk.
+fn run_new(root: PathBuf) -> ExitCode {
+    let entities = match load_all_entities(&root) {
+        Ok(e) => e,
+        Err(e) => {
+            eprintln!("error: failed to load catalog from {}: {e}", root.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+    let draft = match scaffold::run_prompts(&mut stdin, &mut stdout, &entities, &root) {
+        Ok(Some(draft)) => draft,
+        Ok(None) => {
+            println!("Nothing written.");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let written = draft
+        .to_yaml()
+        .and_then(|yaml| scaffold::write_entity(Path::new(&draft.target), &yaml));
+    match written {
+        Ok((path, line)) => {
+            println!("Created {} in {}:{line}", draft.ref_key(), path.display());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {e:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Propose fixes for the catalog at `root` and apply them per `mode`.
+fn run_fix(root: PathBuf, mode: FixMode) -> ExitCode {
+    let entities = match load_all_entities(&root) {
+        Ok(e) => e,
+        Err(e) => {
+            eprintln!("error: failed to load catalog from {}: {e}", root.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    let fixes = fix::propose_fixes(&entities);
+    if fixes.is_empty() {
+        println!("No fixes to propose.");
+        return ExitCode::SUCCESS;
+    }
+
+    let mut stdin = io::stdin().lock();
+    let mut chosen = Vec::new();
+    for f in &fixes {
+        println!("{}:{} {}", f.source_file.display(), f.line, f.entity);
+        println!("  {f}");
+        match mode {
+            FixMode::DryRun => {}
+            FixMode::Apply => chosen.push(f),
+            FixMode::Interactive => {
+                print!("  Apply? [y/N]: ");
+                let _ = io::Write::flush(&mut io::stdout());
+                let mut answer = String::new();
+                if io::BufRead::read_line(&mut stdin, &mut answer).unwrap_or(0) == 0 {
+                    break;
+                }
+                if matches!(answer.trim(), "y" | "Y" | "yes") {
+                    chosen.push(f);
+                }
+            }
+        }
+    }
+
+    if mode == FixMode::DryRun {
+        println!(
+            "\n{} fix(es) proposed. Run `bsv fix --apply` to apply them, or `--interactive` to choose.",
+            fixes.len()
+        );
+        return ExitCode::SUCCESS;
+    }
+    match fix::apply_fixes(&chosen) {
+        Ok(n) => {
+            println!("\nApplied {n} of {} fix(es).", fixes.len());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {e:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Format every catalog file under `root`. With `check`, nothing is written
+/// and the exit status reports whether any file would change.
+fn run_fmt(root: PathBuf, check: bool, options: &FormatOptions, config: &Config) -> ExitCode {
+    let entities = match load_all_entities(&root) {
+        Ok(e) => e,
+        Err(e) => {
+            eprintln!("error: failed to load catalog from {}: {e}", root.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    let index = EntityIndex::build(&entities);
+    let files = if root.is_file() {
+        vec![root]
+    } else {
+        let (discovery, _) = Discovery::from_config(&config.discovery);
+        discover_catalog_files_with(&root, &discovery)
+    };
+
+    let mut changed = 0;
+    let mut failed = false;
+    for file in &files {
+        let result = fs::read_to_string(file)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| {
+                let formatted = formatter::format_content(&content, &index, options)?;
+                Ok((content, formatted))
+            });
+        match result {
+            Ok((content, formatted)) if content != formatted => {
+                changed += 1;
+                if check {
+                    println!("Would reformat {}", file.display());
+                } else if let Err(e) = fs::write(file, formatted) {
+                    eprintln!("error: {}: {e}", file.display());
+                    failed = true;
+                } else {
+                    println!("Formatted {}", file.display());
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("error: {}: {e}", file.display());
+                failed = true;
+            }
+        }
+    }
+
+    let verb = if check {
+        "would be reformatted"
+    } else {
+        "reformatted"
+    };
+    println!("{changed} of {} file(s) {verb}", files.len());
+    if failed || (check && changed > 0) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Fetch the catalog from a Backstage catalog API and browse, validate, or
+/// print it.
+fn run_from_api(url: &str, view: ApiView) -> ExitCode {
+    if view == ApiView::Tui {
+        let app = match App::from_api(url) {
+            Ok(app) => app,
+            Err(e) => {
+                eprintln!("error: failed to load catalog from {url}: {e:#}");
+                return ExitCode::FAILURE;
+            }
+        };
+        return match run_tui_with(app, None) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("Application error: {e}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    let entities = match load_from_api(url) {
+        Ok((entities, warnings)) => {
+            for warning in warnings {
+                eprintln!("warning: {warning}");
+            }
+            entities
+        }
+        Err(e) => {
+            eprintln!("error: failed to load catalog from {url}: {e:#}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if view == ApiView::Validate {
+        validate_entities(&entities, &load_config(Path::new(".")).lint)
+    } else {
+        print_json(&entities)
+    }
+}
+
 /// Launch the interactive terminal UI.
 fn run_tui(root: PathBuf) -> Result<()> {
+    let app = App::new(&root).context("Error loading entities")?;
+    // Watch the catalog for changes (best-effort; the UI still works without it).
+    let watcher = CatalogWatcher::new(&root).ok();
+    run_tui_with(app, watcher)
+}
+
+/// Set up the terminal and run the event loop for a loaded app.
+fn run_tui_with(app: App, watcher: Option<CatalogWatcher>) -> Result<()> {
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -121,18 +550,7 @@ fn run_tui(root: PathBuf) -> Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // Watch the catalog for changes (best-effort; the UI still works without it).
-    let watcher = CatalogWatcher::new(&root).ok();
-
-    // Create app and run
-    let result = match App::new(&root) {
-        Ok(app) => run_app(&mut terminal, app, watcher),
-        Err(e) => {
-            restore_terminal(&mut terminal)?;
-            eprintln!("Error loading entities: {e}");
-            return Err(e);
-        }
-    };
+    let result = run_app(&mut terminal, app, watcher);
 
     // Restore terminal
     restore_terminal(&mut terminal)?;
@@ -150,9 +568,37 @@ fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Re
     Ok(())
 }
 
-/// How long the catalog must be quiet after a change before we reload, so a
-/// burst of editor writes coalesces into a single reload.
-const RELOAD_DEBOUNCE: Duration = Duration::from_millis(300);
+/// Re-enter the TUI after [`restore_terminal`], forcing a full redraw.
+fn resume_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture
+    )?;
+    terminal.clear()?;
+    Ok(())
+}
+
+/// Suspend the TUI, edit `path` at `line` in the user's editor, then resume and
+/// reload so the edits show up immediately.
+fn edit_in_editor(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut App,
+    path: &Path,
+    line: usize,
+) -> Result<()> {
+    restore_terminal(terminal)?;
+    let result = editor::open_in_editor(path, line);
+    resume_terminal(terminal)?;
+
+    app.reload();
+    if let Err(e) = result {
+        app.load_warnings.push(format!("Editor failed: {e}"));
+    }
+    Ok(())
+}
+
 /// How often the event loop wakes to service the file watcher when idle.
 const POLL_INTERVAL: Duration = Duration::from_millis(200);
 
@@ -162,6 +608,7 @@ fn run_app(
     watcher: Option<CatalogWatcher>,
 ) -> Result<()> {
     let mut pending_reload: Option<Instant> = None;
+    let mut changed_paths: Vec<PathBuf> = Vec::new();
 
     loop {
         terminal.draw(|frame| {
@@ -174,15 +621,18 @@ fn run_app(
             ui::draw_help_footer(frame, &app, chunks[1]);
         })?;
 
-        // Note a filesystem change; the actual reload is debounced below.
+        // Note which files changed; the actual reload is debounced below.
         if let Some(w) = &watcher {
-            if w.drain() {
+            let changed = w.drain();
+            if !changed.is_empty() {
+                changed_paths.extend(changed);
                 pending_reload = Some(Instant::now());
             }
         }
         if let Some(since) = pending_reload {
             if since.elapsed() >= RELOAD_DEBOUNCE {
-                app.reload();
+                app.reload_paths(&changed_paths);
+                changed_paths.clear();
                 pending_reload = None;
             }
         }
@@ -203,10 +653,16 @@ fn run_app(
                     app.show_help = false;
                     continue;
                 }
+                app.status = None;
+                let bound = Key::new(key.code, key.modifiers);
                 match app.input_mode() {
-                    InputMode::Normal => handle_normal_mode(&mut app, key.code, visible_height),
-                    InputMode::Search => handle_search_mode(&mut app, key.code),
-                    InputMode::DocsBrowser => handle_docs_mode(&mut app, key.code, visible_height),
+                    InputMode::Normal => handle_normal_mode(&mut app, bound, visible_height),
+                    InputMode::Search => handle_search_mode(&mut app, bound, visible_height),
+                    InputMode::DocsBrowser => handle_docs_mode(&mut app, bound, visible_height),
+                    InputMode::Edit => handle_edit_mode(&mut app, key.code),
+                    InputMode::NewEntity => handle_new_entity_mode(&mut app, key.code),
+                    InputMode::SavedSearches => handle_saved_searches_mode(&mut app, key.code),
+                    InputMode::Palette => handle_palette_mode(&mut app, key.code, visible_height),
                 }
             }
             Event::Mouse(mouse) => {
@@ -217,6 +673,13 @@ fn run_app(
             _ => {}
         }
 
+        if let Some((path, line)) = app.take_edit_request() {
+            edit_in_editor(terminal, &mut app, &path, line)?;
+            // The editor's writes will also wake the watcher; we already reloaded.
+            pending_reload = None;
+            changed_paths.clear();
+        }
+
         if app.should_quit {
             return Ok(());
         }
@@ -261,57 +724,41 @@ fn rect_contains(rect: Rect, x: u16, y: u16) -> bool {
     x >= rect.x && x < rect.x + rect.width && y >= rect.y && y < rect.y + rect.height
 }
 
-fn handle_normal_mode(app: &mut App, key_code: KeyCode, visible_height: usize) {
-    // Keys that apply regardless of which pane has focus.
-    match key_code {
-        KeyCode::Char('q') => return app.quit(),
-        KeyCode::Char('?') => return app.toggle_help(),
-        KeyCode::Esc => return app.focus_tree_and_clear_search(),
-        KeyCode::Tab => return app.toggle_focus(),
-        KeyCode::Char('/') => return app.start_search(),
-        KeyCode::Char('r') => return app.reload(),
-        KeyCode::Char('x') => return app.next_error(),
-        KeyCode::Char('X') => return app.prev_error(),
-        KeyCode::Char('g') => return app.toggle_graph(),
-        KeyCode::Char('y') => return app.toggle_raw(),
-        KeyCode::Char('d') => return app.open_docs(),
-        _ => {}
+fn handle_normal_mode(app: &mut App, key: Key, visible_height: usize) {
+    let Some(action) = app.keymap.action(Mode::Normal, key) else {
+        return;
+    };
+    if app.is_detail_focused() && scroll_detail(app, action, visible_height) {
+        return;
     }
+    run_action(app, action, visible_height);
+}
 
-    if app.is_detail_focused() {
-        let max = right_panel_max_scroll(app, visible_height);
-        match key_code {
-            // In the graph view, up/down pick a related entity and Enter jumps
-            // to it; PageUp/PageDown still scroll. Elsewhere up/down scroll.
-            KeyCode::Up | KeyCode::Char('k') if app.show_graph => app.graph_select_prev(),
-            KeyCode::Down | KeyCode::Char('j') if app.show_graph => app.graph_select_next(),
-            KeyCode::Enter if app.show_graph => {
-                app.jump_to_related();
-            }
-            KeyCode::Up | KeyCode::Char('k') => app.scroll_detail_up(1),
-            KeyCode::Down | KeyCode::Char('j') => app.scroll_detail_down(1, max),
-            KeyCode::PageUp => app.scroll_detail_up(visible_height as u16),
-            KeyCode::PageDown => app.scroll_detail_down(visible_height as u16, max),
-            KeyCode::Home => app.scroll_detail_home(),
-            KeyCode::End => app.scroll_detail_end(max),
-            KeyCode::Left | KeyCode::Char('h') => app.focus_tree(),
-            _ => {}
-        }
-    } else {
-        // Navigation keys move the tree selection.
-        match key_code {
-            KeyCode::Up | KeyCode::Char('k') => app.move_up(),
-            KeyCode::Down | KeyCode::Char('j') => app.move_down(),
-            KeyCode::PageUp => app.page_up(visible_height),
-            KeyCode::PageDown => app.page_down(visible_height),
-            KeyCode::Home => app.move_home(),
-            KeyCode::End => app.move_end(),
-            KeyCode::Left | KeyCode::Char('h') => app.collapse(),
-            KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter => app.toggle_expand(),
-            KeyCode::Char('e') => app.expand_all(),
-            KeyCode::Char('c') => app.collapse_all(),
-            _ => {}
-        }
+/// Navigation while the detail pane has focus: up/down scroll it, or in the
+/// graph view pick a related entity; PageUp/PageDown still scroll. Returns
+/// whether the action was handled here (tree-only navigation is ignored).
+fn scroll_detail(app: &mut App, action: Action, visible_height: usize) -> bool {
+    let max = right_panel_max_scroll(app, visible_height);
+    match action {
+        Action::MoveUp if app.show_graph => app.graph_select_prev(),
+        Action::MoveDown if app.show_graph => app.graph_select_next(),
+        Action::MoveUp => app.scroll_detail_up(1),
+        Action::MoveDown => app.scroll_detail_down(1, max),
+        Action::PageUp => app.scroll_detail_up(visible_height as u16),
+        Action::PageDown => app.scroll_detail_down(visible_height as u16, max),
+        Action::First => app.scroll_detail_home(),
+        Action::Last => app.scroll_detail_end(max),
+        Action::Collapse => app.focus_tree(),
+        Action::Expand | Action::ExpandAll | Action::CollapseAll => {}
+        _ => return false,
+    }
+    true
+}
+
+/// Run an action, reporting a failure in the footer.
+fn run_action(app: &mut App, action: Action, visible_height: usize) {
+    if let Err(e) = app.perform(action, "", visible_height) {
+        app.status = Some(format!("{e:#}"));
     }
 }
 
@@ -320,45 +767,151 @@ fn right_panel_max_scroll(app: &App, visible_height: usize) -> u16 {
     ui::right_panel_line_count(app).saturating_sub(visible_height) as u16
 }
 
-fn handle_search_mode(app: &mut App, key_code: KeyCode) {
-    match key_code {
-        KeyCode::Esc => app.cancel_search(),
-        KeyCode::Enter => app.confirm_search(),
-        KeyCode::Backspace => app.search_backspace(),
-        KeyCode::Char(c) => app.search_input(c),
-        _ => {}
+fn handle_search_mode(app: &mut App, key: Key, visible_height: usize) {
+    match app.keymap.action(Mode::Search, key) {
+        Some(Action::Back) => app.cancel_search(),
+        Some(Action::Confirm) => app.confirm_search(),
+        Some(action) => run_action(app, action, visible_height),
+        // Anything unbound edits the query.
+        None if key.code == KeyCode::Backspace => app.search_backspace(),
+        None => {
+            if let Some(c) = typed_char(key) {
+                app.search_input(c);
+            }
+        }
     }
 }
 
-fn handle_docs_mode(app: &mut App, key_code: KeyCode, visible_height: usize) {
+/// The character a key types, if it types one (no Ctrl/Alt chord).
+fn typed_char(key: Key) -> Option<char> {
+    match key.code {
+        KeyCode::Char(c)
+            if !key
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+        {
+            Some(c)
+        }
+        _ => None,
+    }
+}
+
+fn handle_edit_mode(app: &mut App, key_code: KeyCode) {
+    let editing = app.edit_form.as_ref().is_some_and(|f| f.is_editing());
     match key_code {
-        KeyCode::Esc => app.close_docs(),
-        KeyCode::Up | KeyCode::Char('k') => {
-            if let Some(browser) = &mut app.docs_browser {
-                browser.move_up();
+        KeyCode::Esc => app.close_edit(),
+        KeyCode::Enter => app.edit_enter(),
+        KeyCode::Char('q') if !editing => app.quit(),
+        _ if !editing => {
+            if let Some(form) = &mut app.edit_form {
+                match key_code {
+                    KeyCode::Up | KeyCode::Char('k') => form.move_up(),
+                    KeyCode::Down | KeyCode::Char('j') => form.move_down(),
+                    _ => {}
+                }
             }
         }
-        KeyCode::Down | KeyCode::Char('j') => {
-            if let Some(browser) = &mut app.docs_browser {
-                browser.move_down(visible_height);
+        _ => {
+            if let Some(form) = &mut app.edit_form {
+                match key_code {
+                    KeyCode::Tab => form.complete(&app.catalog.index),
+                    KeyCode::Backspace => form.backspace(),
+                    KeyCode::Char(c) => form.input_char(c),
+                    _ => {}
+                }
             }
         }
-        KeyCode::PageUp => {
-            if let Some(browser) = &mut app.docs_browser {
-                browser.page_up(visible_height);
+    }
+}
+
+fn handle_new_entity_mode(app: &mut App, key_code: KeyCode) {
+    match key_code {
+        KeyCode::Esc => app.new_entity_back(),
+        KeyCode::Enter => app.new_entity_enter(),
+        KeyCode::Tab => app.new_entity_complete(),
+        _ => {
+            if let Some(wizard) = &mut app.new_wizard {
+                match key_code {
+                    KeyCode::Backspace => wizard.backspace(),
+                    KeyCode::Char(c) => wizard.input_char(c),
+                    _ => {}
+                }
             }
         }
-        KeyCode::PageDown => {
-            if let Some(browser) = &mut app.docs_browser {
-                browser.page_down(visible_height, visible_height);
+    }
+}
+
+fn handle_palette_mode(app: &mut App, key_code: KeyCode, visible_height: usize) {
+    match key_code {
+        KeyCode::Esc => app.close_palette(),
+        KeyCode::Enter => app.palette_confirm(visible_height),
+        KeyCode::Tab => app.palette_complete(),
+        KeyCode::Up => app.palette_move_up(),
+        KeyCode::Down => app.palette_move_down(),
+        KeyCode::Backspace => app.palette_backspace(),
+        KeyCode::Char(c) => app.palette_input(c),
+        _ => {}
+    }
+}
+
+fn handle_saved_searches_mode(app: &mut App, key_code: KeyCode) {
+    let naming = app
+        .search_picker
+        .as_ref()
+        .is_some_and(|p| p.naming.is_some());
+    if naming {
+        match key_code {
+            KeyCode::Esc => {
+                if let Some(picker) = &mut app.search_picker {
+                    picker.naming = None;
+                }
             }
-        }
-        KeyCode::Enter => {
-            if let Some(browser) = &mut app.docs_browser {
-                browser.open_selected();
+            KeyCode::Enter => app.save_filter(),
+            _ => {
+                if let Some(name) = app.search_picker.as_mut().and_then(|p| p.naming.as_mut()) {
+                    match key_code {
+                        KeyCode::Backspace => {
+                            name.pop();
+                        }
+                        KeyCode::Char(c) => name.push(c),
+                        _ => {}
+                    }
+                }
             }
         }
+        return;
+    }
+    match key_code {
+        KeyCode::Esc | KeyCode::Char('s') => app.close_search_picker(),
+        KeyCode::Up | KeyCode::Char('k') => app.picker_move_up(),
+        KeyCode::Down | KeyCode::Char('j') => app.picker_move_down(),
+        KeyCode::Enter => app.apply_saved_search(),
+        KeyCode::Char('a') => app.start_saving_filter(),
         KeyCode::Char('q') => app.quit(),
         _ => {}
     }
 }
+
+fn handle_docs_mode(app: &mut App, key: Key, visible_height: usize) {
+    let Some(action) = app.keymap.action(Mode::Docs, key) else {
+        return;
+    };
+    if action == Action::Back {
+        return app.close_docs();
+    }
+    if action == Action::Quit {
+        return app.quit();
+    }
+    let Some(browser) = &mut app.docs_browser else {
+        return;
+    };
+    match action {
+        Action::MoveUp => browser.move_up(),
+        Action::MoveDown => browser.move_down(visible_height),
+        Action::PageUp => browser.page_up(visible_height),
+        Action::PageDown => browser.page_down(visible_height, visible_height),
+        Action::Last => browser.last(visible_height),
+        Action::Confirm => browser.open_selected(),
+        _ => {}
+    }
+}
@@ -2,8 +2,8 @@
 //!
 //! This module provides functionality to discover `catalog-info.yaml` files in a directory tree
 //! and parse them as multi-document YAML containing Backstage entities. It intelligently excludes
-//! common build directories (like `target/`, `node_modules/`, `.git/`) to avoid scanning
-//! irrelevant files.
+//! common build directories (like `target/`, `node_modules/`, `.git/`) and anything listed in
+//! `.gitignore` or `.bsvignore` files to avoid scanning irrelevant files.
 //!
 //! # Examples
 //!
@@ -64,15 +64,22 @@
 //!
 //! - [`load_all_entities`] - Main entry point: load entities from directory or file
 //! - [`discover_catalog_files`] - Recursively find all catalog-info.yaml files
+//! - [`Discovery`] - Extra file names and include/exclude globs from the configuration
 //! - [`parse_catalog_file`] - Parse multi-document YAML file into entities
 //! - [`should_exclude_dir`] - Check if a directory should be skipped during discovery
 
+use crate::cache::Cache;
+use crate::catalog::LoadedCatalog;
+use crate::config::{Config, DiscoveryConfig};
 use crate::entity::{Entity, EntityWithSource};
 use crate::validator::validate_entity;
 use anyhow::{Context, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::{WalkBuilder, WalkState};
+use rayon::prelude::*;
 use std::fs;
 use std::path::Path;
-use walkdir::WalkDir;
+use std::sync::mpsc;
 
 /// Directories to skip during filesystem scans (build outputs, dependencies, caches)
 pub const EXCLUDED_DIRS: &[&str] = &[
@@ -120,17 +127,128 @@ pub fn should_exclude_dir(name: &str) -> bool {
             .any(|prefix| name.starts_with(prefix))
 }
 
+/// File names that are always catalog files.
+pub const CATALOG_FILENAMES: &[&str] = &["catalog-info.yaml", "catalog-info.yml"];
+
+/// Per-directory ignore file, in `.gitignore` syntax, for paths discovery
+/// should skip without hiding them from git.
+pub const IGNORE_FILE: &str = ".bsvignore";
+
+/// Which files discovery picks up, built from the `[discovery]` configuration
+/// table (see [`crate::config`]).
+///
+/// The default reads `catalog-info.yaml`/`.yml`; the configuration adds file
+/// names, include globs (`catalog/*.yaml`, `*.catalog.yaml`) and exclude
+/// globs. Globs are matched against the path relative to the catalog root and
+/// against the bare file or directory name; `*` stays within one directory
+/// while `**` crosses them.
+#[derive(Debug, Clone)]
+pub struct Discovery {
+    filenames: Vec<String>,
+    include: GlobSet,
+    exclude: GlobSet,
+}
+
+impl Default for Discovery {
+    fn default() -> Self {
+        Self {
+            filenames: CATALOG_FILENAMES.iter().map(|s| s.to_string()).collect(),
+            include: GlobSet::empty(),
+            exclude: GlobSet::empty(),
+        }
+    }
+}
+
+impl Discovery {
+    /// Build discovery options from `config`, with a warning for each glob
+    /// that could not be parsed (and is ignored).
+    pub fn from_config(config: &DiscoveryConfig) -> (Self, Vec<String>) {
+        let mut warnings = Vec::new();
+        let mut filenames = Self::default().filenames;
+        filenames.extend(config.filenames.iter().cloned());
+        let discovery = Self {
+            filenames,
+            include: build_globs("include", &config.include, &mut warnings),
+            exclude: build_globs("exclude", &config.exclude, &mut warnings),
+        };
+        (discovery, warnings)
+    }
+
+    /// Whether the file at `relative` (the path under the root) is a catalog
+    /// file: one of the file names, or matched by an include glob.
+    pub fn is_catalog_file(&self, relative: &Path) -> bool {
+        relative
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| self.filenames.iter().any(|f| f == name))
+            || matches_path(&self.include, relative)
+    }
+}
+
+/// Compile the `[discovery] key` globs, warning about (and skipping) any that
+/// don't parse.
+fn build_globs(key: &str, patterns: &[String], warnings: &mut Vec<String>) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        match GlobBuilder::new(pattern).literal_separator(true).build() {
+            Ok(glob) => {
+                builder.add(glob);
+            }
+            Err(e) => warnings.push(format!("[discovery] {key}: {e}")),
+        }
+    }
+    builder.build().unwrap_or_else(|e| {
+        warnings.push(format!("[discovery] {key}: {e}"));
+        GlobSet::empty()
+    })
+}
+
+/// Whether `globs` match `relative` or its final component.
+fn matches_path(globs: &GlobSet, relative: &Path) -> bool {
+    !globs.is_empty()
+        && (globs.is_match(relative)
+            || relative
+                .file_name()
+                .is_some_and(|name| globs.is_match(name)))
+}
+
 /// Discover all `catalog-info.yaml` and `catalog-info.yml` files recursively.
 ///
 /// Automatically excludes common build directories like `target/`, `node_modules/`,
-/// and `.git/` to avoid scanning irrelevant files.
+/// and `.git/` to avoid scanning irrelevant files, as well as anything listed
+/// in `.gitignore` or [`IGNORE_FILE`] files.
 pub fn discover_catalog_files(root: &Path) -> Vec<std::path::PathBuf> {
-    WalkDir::new(root)
+    discover_catalog_files_with(root, &Discovery::default())
+}
+
+/// Discover catalog files recursively as [`discover_catalog_files`] does,
+/// with the file names and globs of `discovery`.
+///
+/// Directories are walked in parallel; the files are returned sorted by
+/// path, so the order is the same on every run.
+pub fn discover_catalog_files_with(root: &Path, discovery: &Discovery) -> Vec<std::path::PathBuf> {
+    let walk_root = root.to_path_buf();
+    let exclude = discovery.exclude.clone();
+    let (tx, rx) = mpsc::channel();
+    WalkBuilder::new(root)
         .follow_links(true)
-        .into_iter()
-        .filter_entry(|e| {
+        // Hidden directories are skipped by `should_exclude_dir`; hidden
+        // files are read like any other.
+        .hidden(false)
+        // Honour `.gitignore` even in a catalog that isn't a git checkout.
+        .require_git(false)
+        .add_custom_ignore_filename(IGNORE_FILE)
+        .filter_entry(move |e| {
+            // The root itself is never excluded.
+            if e.depth() == 0 {
+                return true;
+            }
+            let relative = e.path().strip_prefix(&walk_root).unwrap_or(e.path());
+            if matches_path(&exclude, relative) {
+                return false;
+            }
             // Allow files, but filter directories
-            if e.file_type().is_dir() {
+            if e.file_type().is_some_and(|t| t.is_dir()) {
                 e.file_name()
                     .to_str()
                     .is_none_or(|name| !should_exclude_dir(name))
@@ -138,15 +256,27 @@ pub fn discover_catalog_files(root: &Path) -> Vec<std::path::PathBuf> {
                 true
             }
         })
-        .filter_map(std::result::Result::ok)
-        .filter(|e| e.file_type().is_file())
-        .filter(|e| {
-            e.file_name()
-                .to_str()
-                .is_some_and(|name| name == "catalog-info.yaml" || name == "catalog-info.yml")
-        })
-        .map(walkdir::DirEntry::into_path)
-        .collect()
+        .build_parallel()
+        .run(|| {
+            let tx = tx.clone();
+            Box::new(move |entry| {
+                if let Ok(e) = entry {
+                    let relative = e.path().strip_prefix(root).unwrap_or(e.path());
+                    if e.file_type().is_some_and(|t| t.is_file())
+                        && discovery.is_catalog_file(relative)
+                    {
+                        // The receiver outlives the walk.
+                        let _ = tx.send(e.into_path());
+                    }
+                }
+                WalkState::Continue
+            })
+        });
+    drop(tx);
+
+    let mut files: Vec<_> = rx.into_iter().collect();
+    files.sort();
+    files
 }
 
 /// Parse a catalog file as multi-document YAML.
@@ -161,66 +291,148 @@ pub fn parse_catalog_file(path: &Path) -> Result<Vec<EntityWithSource>> {
     Ok(parse_multi_document_yaml(&content, path, &mut warnings))
 }
 
-fn parse_multi_document_yaml(
+pub(crate) fn parse_multi_document_yaml(
     content: &str,
     source_path: &Path,
     warnings: &mut Vec<String>,
 ) -> Vec<EntityWithSource> {
+    let (entities, errors) = parse_documents(content, source_path);
+    warnings.extend(errors.into_iter().map(|e| {
+        format!(
+            "Failed to parse entity in {}: {}",
+            source_path.display(),
+            e.message
+        )
+    }));
+    entities
+}
+
+/// A YAML document that could not be read as an entity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentError {
+    /// 1-based line of the error (or of the document when unknown).
+    pub line: usize,
+    pub message: String,
+}
+
+/// Parse and validate every document in `content` as if read from
+/// `source_path`, returning the entities and the documents that failed.
+pub fn parse_documents(
+    content: &str,
+    source_path: &Path,
+) -> (Vec<EntityWithSource>, Vec<DocumentError>) {
     let mut entities = Vec::new();
+    let mut errors = Vec::new();
+    let start_lines = document_start_lines(content);
 
-    for document in serde_yaml::Deserializer::from_str(content) {
+    for (i, document) in serde_yaml::Deserializer::from_str(content).enumerate() {
+        let start = start_lines.get(i).copied().unwrap_or(1);
         match Entity::deserialize(document) {
-            Ok(entity) => {
-                // Validate the entity against JSON Schema
-                let validation_errors = validate_entity(&entity);
-
-                entities.push(
-                    EntityWithSource::new(entity, source_path.to_path_buf())
-                        .with_validation_errors(validation_errors),
-                );
-            }
+            Ok(entity) => entities
+                .push(EntityWithSource::new(entity, source_path.to_path_buf()).with_line(start)),
             Err(e) => {
-                warnings.push(format!(
-                    "Failed to parse entity in {}: {e}",
-                    source_path.display()
-                ));
+                let error = DocumentError {
+                    line: e.location().map_or(start, |l| l.line()),
+                    message: e.to_string(),
+                };
+                // After a syntax error the deserializer yields that same error
+                // forever; nothing past it can be read.
+                if errors.last() == Some(&error) {
+                    break;
+                }
+                errors.push(error);
             }
         }
     }
 
+    // Validate the entities against JSON Schema, a file's documents in parallel.
     entities
+        .par_iter_mut()
+        .for_each(|ews| ews.validation_errors = validate_entity(&ews.entity));
+
+    (entities, errors)
+}
+
+/// The 1-based line on which each YAML document in `content` starts, in the
+/// order the YAML deserializer yields them.
+///
+/// Mirrors how documents are split: a `---` marker always opens a document
+/// (even an empty one), while text before the first marker only counts when it
+/// holds more than comments and blank lines. Each document is located at its
+/// first content line, so an editor lands on `apiVersion:` rather than `---`.
+pub fn document_start_lines(content: &str) -> Vec<usize> {
+    let is_content = |line: &str| {
+        let trimmed = line.trim();
+        !trimmed.is_empty() && !trimmed.starts_with('#')
+    };
+    let is_marker = |line: &str| line == "---" || line.starts_with("--- ");
+
+    let mut starts = Vec::new();
+    // (line the current document opened on, whether it was opened by a marker)
+    let mut current: Option<(usize, bool)> = Some((1, false));
+    let mut found_content = false;
+
+    for (idx, line) in content.lines().enumerate() {
+        let line_no = idx + 1;
+        let line = line.trim_end();
+        if is_marker(line) {
+            if let Some((start, explicit)) = current {
+                if explicit || found_content {
+                    starts.push(start);
+                }
+            }
+            // Content on the marker line itself (`--- {…}`) starts the document.
+            let inline = is_content(&line[3..]);
+            current = Some((if inline { line_no } else { line_no + 1 }, true));
+            found_content = inline;
+        } else if is_content(line) && !found_content {
+            if let Some((start, _)) = current.as_mut() {
+                *start = line_no;
+            }
+            found_content = true;
+        }
+    }
+    if let Some((start, explicit)) = current {
+        if explicit || found_content {
+            starts.push(start);
+        }
+    }
+    starts
 }
 
 /// Load all entities from a directory or single file, along with any non-fatal
 /// warnings (documents or files that could not be parsed and were skipped).
 ///
 /// If the path is a file, loads just that file. If it's a directory,
-/// recursively discovers and parses all catalog-info.yaml files.
+/// recursively discovers and parses its catalog files, as configured by the
+/// `[discovery]` table of the configuration for `root` (problems reading the
+/// configuration are warnings too). Entities declared more than once are
+/// flagged via [`mark_duplicates`], and relations are computed across the
+/// whole catalog.
 pub fn load_catalog(root: &Path) -> Result<(Vec<EntityWithSource>, Vec<String>)> {
-    let mut warnings = Vec::new();
-
-    if root.is_file() {
-        let content = fs::read_to_string(root)
-            .with_context(|| format!("Failed to read file: {}", root.display()))?;
-        let entities = parse_multi_document_yaml(&content, root, &mut warnings);
-        return Ok((entities, warnings));
-    }
-
-    let mut all_entities = Vec::new();
-    for file_path in discover_catalog_files(root) {
-        match fs::read_to_string(&file_path) {
-            Ok(content) => {
-                all_entities.extend(parse_multi_document_yaml(
-                    &content,
-                    &file_path,
-                    &mut warnings,
-                ));
-            }
-            Err(e) => warnings.push(format!("Failed to read {}: {e}", file_path.display())),
-        }
-    }
+    let (config, mut warnings) = Config::load(root);
+    let (discovery, discovery_warnings) = Discovery::from_config(&config.discovery);
+    warnings.extend(discovery_warnings);
+    let mut cache = Cache::open(root, &config.cache);
+    let (entities, load_warnings) = load_catalog_with(root, &discovery, cache.as_mut())?;
+    warnings.extend(load_warnings);
+    Ok((entities, warnings))
+}
 
-    Ok((all_entities, warnings))
+/// Load all entities as [`load_catalog`] does, discovering files with
+/// `discovery` rather than the configuration.
+///
+/// With a `cache`, files whose content is unchanged are taken from it rather
+/// than parsed and validated again, and the cache is updated and saved
+/// (failing to save it is a warning).
+pub fn load_catalog_with(
+    root: &Path,
+    discovery: &Discovery,
+    cache: Option<&mut Cache>,
+) -> Result<(Vec<EntityWithSource>, Vec<String>)> {
+    let catalog = LoadedCatalog::load(root, discovery, cache)?;
+    let warnings = catalog.all_warnings();
+    Ok((catalog.into_entities(), warnings))
 }
 
 /// Load all entities, discarding any non-fatal warnings. See [`load_catalog`].
@@ -233,6 +445,7 @@ use serde::Deserialize;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::TempDir;
     use std::path::PathBuf;
 
     #[test]
@@ -381,6 +594,28 @@ spec:
         }
     }
 
+    #[test]
+    fn test_document_start_lines() {
+        // Leading comments don't form a document; each one starts at its first
+        // content line.
+        let content = "# header\n---\napiVersion: v1\nkind: A\n---\n\n# note\nkind: B\n";
+        assert_eq!(document_start_lines(content), vec![3, 8]);
+
+        // No leading marker: the first document starts at line 1.
+        assert_eq!(document_start_lines("kind: A\n---\nkind: B\n"), vec![1, 3]);
+
+        // A trailing marker still opens an (empty) document, as serde_yaml does.
+        assert_eq!(document_start_lines("kind: A\n---\n"), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_entities_record_their_document_line() {
+        let yaml = "# comment\n---\napiVersion: backstage.io/v1alpha1\nkind: Component\nmetadata:\n  name: a\n---\napiVersion: backstage.io/v1alpha1\nkind: Component\nmetadata:\n  name: b\n";
+        let entities = parse_multi_document_yaml(yaml, Path::new("t.yaml"), &mut Vec::new());
+        let lines: Vec<usize> = entities.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![3, 8]);
+    }
+
     #[test]
     fn test_parse_catalog_file() {
         // Parse the validation-test.yaml file which contains multiple entities
@@ -578,6 +813,18 @@ spec:
         );
     }
 
+    #[test]
+    fn test_syntax_error_ends_the_file() {
+        // After a syntax error the deserializer repeats it for every later
+        // document rather than moving past it.
+        let yaml = "apiVersion: v1\nkind: Group\nmetadata:\n  name: a\n---\nkind: [\n";
+        let (entities, errors) = parse_documents(yaml, Path::new("broken.yaml"));
+        assert_eq!(entities.len(), 1);
+        // The flow sequence fails as a `kind`, then as YAML — once.
+        assert_eq!(errors.len(), 2, "{errors:?}");
+        assert_eq!(errors[1].line, 7);
+    }
+
     #[test]
     fn test_discover_excludes_build_directories() {
         // This test verifies that discovery properly excludes common build directories
@@ -613,6 +860,100 @@ spec:
         }
     }
 
+    #[test]
+    fn test_discovery_configured_from_a_parent_bsv_toml() {
+        let dir = TempDir::new("discovery");
+        let root = dir.join("catalog");
+        let component = |name: &str| {
+            format!("apiVersion: backstage.io/v1alpha1\nkind: Component\nmetadata:\n  name: {name}\nspec:\n  type: service\n  lifecycle: production\n  owner: team-a\n")
+        };
+        for (path, name) in [
+            ("catalog-info.yaml", "kept"),
+            ("services/catalog.yaml", "extra-name"),
+            ("examples/catalog-info.yaml", "example"),
+            ("legacy/old/catalog-info.yaml", "legacy"),
+        ] {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, component(name)).unwrap();
+        }
+        fs::write(
+            dir.join(crate::config::PROJECT_CONFIG),
+            "[discovery]\nexclude = [\"examples\", \"legacy/**\", \"[\"]\nfilenames = [\"catalog.yaml\"]\n[cache]\nenabled = false\n",
+        )
+        .unwrap();
+
+        // Without the configuration, only catalog-info files are found.
+        assert_eq!(discover_catalog_files(&root).len(), 3);
+
+        let (entities, warnings) = load_catalog(&root).unwrap();
+        let mut names: Vec<_> = entities
+            .iter()
+            .map(|e| e.entity.metadata.name.as_str())
+            .collect();
+        names.sort_unstable();
+        assert_eq!(names, ["extra-name", "kept"]);
+        // The malformed glob is reported and skipped.
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(warnings[0].starts_with("[discovery] exclude"));
+    }
+
+    #[test]
+    fn test_discovery_include_globs_and_ignore_files() {
+        let root = TempDir::new("include");
+        for path in [
+            "catalog/users.yaml",
+            "catalog/nested/skipped.yaml",
+            "backstage/entities/api.yml",
+            "services/payments.catalog.yaml",
+            "services/notes.yaml",
+            "generated/catalog-info.yaml",
+            "scratch/catalog-info.yaml",
+            "scratch/keep/catalog-info.yaml",
+            "catalog-info.yaml",
+        ] {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        fs::write(root.join(".gitignore"), "generated/\n").unwrap();
+        fs::write(
+            root.join("scratch").join(IGNORE_FILE),
+            "/catalog-info.yaml\n",
+        )
+        .unwrap();
+
+        let config = DiscoveryConfig {
+            include: vec![
+                "catalog/*.yaml".to_string(),
+                "backstage/entities/*.yml".to_string(),
+                "*.catalog.yaml".to_string(),
+            ],
+            ..DiscoveryConfig::default()
+        };
+        let (discovery, warnings) = Discovery::from_config(&config);
+        assert!(warnings.is_empty());
+        let files: Vec<_> = discover_catalog_files_with(&root, &discovery)
+            .into_iter()
+            .map(|p| {
+                p.strip_prefix(&root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect();
+        assert_eq!(
+            files,
+            [
+                "backstage/entities/api.yml",
+                "catalog/users.yaml",
+                "catalog-info.yaml",
+                "scratch/keep/catalog-info.yaml",
+                "services/payments.catalog.yaml",
+            ]
+        );
+    }
+
     #[test]
     fn test_parse_catalog_file_nonexistent() {
         // Test parsing a non-existent file
@@ -0,0 +1,825 @@
+//! Catalog query language, used by `bsv query` and the TUI's `/` search.
+//!
+//! A query is a list of terms, combined with `AND` (implied between adjacent
+//! terms), `OR` and `NOT`, grouped with parentheses:
+//!
+//! ```text
+//! kind:component AND (lifecycle=production OR has:techdocs) NOT owner:legacy
+//! ```
+//!
+//! | Term                          | Matches                                         |
+//! |-------------------------------|-------------------------------------------------|
+//! | `checkout`, `"billing api"`   | name, title, description, kind, owner or tags   |
+//! | `pmt-svc`                     | fuzzily, the name or title (`payment-service`); the ref if it has `:` or `/` |
+//! | `owner:team`                  | a field containing `team` (case-insensitive)    |
+//! | `kind=component`              | a field equal to `component`                    |
+//! | `name:/^pay.*-api$/`          | a field matching a regular expression           |
+//! | `has:techdocs`                | entities with TechDocs (or any field, annotation, label or relation) |
+//! | `annotation:github.com/project-slug=acme/web` | an annotation (`=value` optional)  |
+//! | `label:tier=1`                | a label (`=value` optional)                     |
+//! | `spec.type=service`           | a value at a `spec.` or `metadata.` path        |
+//! | `dependsOn:database`          | a relation (see [`relation_types`]) whose target matches |
+//!
+//! Fields are `name`, `title`, `kind`, `owner`, `system`, `domain`, `tag`,
+//! `lifecycle`, `type`, `namespace` and `description`. A `field:value` term
+//! whose field is none of the above is free text.
+//!
+//! [`Query::rank_node`] scores matches by how well their free text fuzzily
+//! matches, for the TUI's ranked search results.
+//!
+//! ```
+//! use bsv::query::Query;
+//!
+//! assert!(Query::parse("kind:component AND NOT owner:legacy").is_ok());
+//! assert!(Query::parse("(kind:api").is_err());
+//! ```
+
+use crate::entity::{Entity, EntityWithSource};
+use crate::relations::relation_types;
+use anyhow::{anyhow, bail, Result};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use once_cell::sync::Lazy;
+use regex::{Regex, RegexBuilder};
+
+/// Score added when free text is exactly an entity's name.
+const EXACT_NAME_BONUS: i64 = 1000;
+
+/// Annotation that marks an entity as having TechDocs (`has:techdocs`).
+const TECHDOCS_ANNOTATION: &str = "backstage.io/techdocs-ref";
+/// Annotation pointing at an entity's ADRs (`has:adrs`).
+const ADR_ANNOTATION: &str = "backstage.io/adr-location";
+
+/// Free text shorter than this only matches as a substring; fuzzy matching
+/// one or two characters would match nearly everything.
+const MIN_FUZZY_LEN: usize = 3;
+
+static MATCHER: Lazy<SkimMatcherV2> = Lazy::new(|| SkimMatcherV2::default().ignore_case());
+
+/// Fuzzy score and matched char indices of `needle` in `haystack`.
+fn fuzzy(haystack: &str, needle: &str) -> Option<(i64, Vec<usize>)> {
+    if needle.chars().count() < MIN_FUZZY_LEN {
+        return None;
+    }
+    MATCHER.fuzzy_indices(haystack, needle)
+}
+
+/// The strings `needle` is fuzzily matched against: name and title, and the
+/// full ref when the needle looks like one (every ref contains `default`, so
+/// matching it for any text would match almost anything).
+fn fuzzy_targets(entity: &Entity, needle: &str) -> Vec<String> {
+    let looks_like_ref = needle.contains(':') || needle.contains('/');
+    [
+        Some(entity.metadata.name.clone()),
+        entity.metadata.title.clone(),
+        looks_like_ref.then(|| entity.ref_key()),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// A parsed query.
+#[derive(Debug, Clone)]
+pub struct Query {
+    expr: Expr,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    /// All must match; an empty list matches everything.
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+    Term(Term),
+}
+
+#[derive(Debug, Clone)]
+enum Term {
+    /// Free text across the common fields (and a tree node's label).
+    Text(Pattern),
+    Field {
+        field: String,
+        pattern: Pattern,
+    },
+    Has(String),
+    Annotation {
+        key: String,
+        value: Option<Pattern>,
+    },
+    Label {
+        key: String,
+        value: Option<Pattern>,
+    },
+    Path {
+        path: String,
+        pattern: Pattern,
+    },
+    Relation {
+        relation: &'static str,
+        pattern: Pattern,
+    },
+}
+
+#[derive(Debug, Clone)]
+enum Pattern {
+    /// Case-insensitive substring (stored lowercase).
+    Contains(String),
+    /// Case-insensitive equality (stored lowercase).
+    Exact(String),
+    Regex(Regex),
+}
+
+impl Pattern {
+    fn matches(&self, text: &str) -> bool {
+        match self {
+            Pattern::Contains(needle) => text.to_lowercase().contains(needle),
+            Pattern::Exact(value) => text.to_lowercase() == *value,
+            Pattern::Regex(re) => re.is_match(text),
+        }
+    }
+
+    /// The text to match fuzzily: plain (unquoted) free text only.
+    fn fuzzy_needle(&self) -> Option<&str> {
+        match self {
+            Pattern::Contains(needle) if !needle.contains(' ') => Some(needle),
+            _ => None,
+        }
+    }
+
+    /// Match an entity reference: `=` also accepts the bare name.
+    fn matches_ref(&self, target_ref: &str) -> bool {
+        match self {
+            Pattern::Exact(value) => {
+                let target = target_ref.to_lowercase();
+                target == *value || target.rsplit('/').next() == Some(value.as_str())
+            }
+            _ => self.matches(target_ref),
+        }
+    }
+}
+
+/// A whitespace-separated word of the query. Quoted parts are unquoted in
+/// `text` and never act as operators, keywords or regex delimiters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Word {
+    text: String,
+    /// Byte offset of the first unquoted `:` or `=`.
+    split: Option<usize>,
+    /// Byte offset where the first quoted part starts.
+    quoted_from: Option<usize>,
+}
+
+impl Word {
+    fn keyword(&self) -> Option<&str> {
+        match self.text.as_str() {
+            "AND" | "OR" | "NOT" if self.quoted_from.is_none() => Some(&self.text),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    Word(Word),
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    let mut word: Option<Word> = None;
+    let finish = |word: &mut Option<Word>, tokens: &mut Vec<Token>| {
+        if let Some(w) = word.take() {
+            tokens.push(Token::Word(w));
+        }
+    };
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => finish(&mut word, &mut tokens),
+            '(' if word.is_none() => tokens.push(Token::Open),
+            // Inside a word (e.g. a regex) parentheses pair up literally.
+            ')' if word
+                .as_ref()
+                .is_some_and(|w| w.text.matches('(').count() > w.text.matches(')').count()) =>
+            {
+                if let Some(w) = word.as_mut() {
+                    w.text.push(c);
+                }
+            }
+            ')' => {
+                finish(&mut word, &mut tokens);
+                tokens.push(Token::Close);
+            }
+            '"' => {
+                let w = word.get_or_insert_with(|| Word {
+                    text: String::new(),
+                    split: None,
+                    quoted_from: None,
+                });
+                w.quoted_from.get_or_insert(w.text.len());
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => w.text.push(c),
+                        None => bail!("unterminated quote"),
+                    }
+                }
+            }
+            c => {
+                let w = word.get_or_insert_with(|| Word {
+                    text: String::new(),
+                    split: None,
+                    quoted_from: None,
+                });
+                if (c == ':' || c == '=') && w.split.is_none() {
+                    w.split = Some(w.text.len());
+                }
+                w.text.push(c);
+            }
+        }
+    }
+    finish(&mut word, &mut tokens);
+    Ok(tokens)
+}
+
+/// Recursive-descent parser: `OR` binds loosest, then `AND`, then `NOT`.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_keyword(&self) -> Option<&str> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Word(w)) => w.keyword(),
+            _ => None,
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut items = vec![self.and()?];
+        while self.peek_keyword() == Some("OR") {
+            self.pos += 1;
+            items.push(self.and()?);
+        }
+        Ok(if items.len() == 1 {
+            items.remove(0)
+        } else {
+            Expr::Or(items)
+        })
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut items = vec![self.not()?];
+        loop {
+            match self.tokens.get(self.pos) {
+                None | Some(Token::Close) => break,
+                _ if self.peek_keyword() == Some("OR") => break,
+                _ if self.peek_keyword() == Some("AND") => self.pos += 1,
+                _ => {}
+            }
+            items.push(self.not()?);
+        }
+        Ok(if items.len() == 1 {
+            items.remove(0)
+        } else {
+            Expr::And(items)
+        })
+    }
+
+    fn not(&mut self) -> Result<Expr> {
+        if self.peek_keyword() == Some("NOT") {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Expr> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                match self.tokens.get(self.pos) {
+                    Some(Token::Close) => {
+                        self.pos += 1;
+                        Ok(expr)
+                    }
+                    _ => bail!("missing `)`"),
+                }
+            }
+            Some(Token::Close) => bail!("unexpected `)`"),
+            Some(Token::Word(w)) => match w.keyword() {
+                Some(keyword) => bail!("expected a search term before `{keyword}`"),
+                None => Ok(Expr::Term(term(&w)?)),
+            },
+            None => bail!("expected a search term at the end of the query"),
+        }
+    }
+}
+
+/// Build the pattern for a value, `exact` for `=`. Unquoted `/.../` is a
+/// regular expression.
+fn pattern(value: &str, quoted: bool, exact: bool) -> Result<Pattern> {
+    if !quoted && value.len() >= 2 && value.starts_with('/') && value.ends_with('/') {
+        let source = &value[1..value.len() - 1];
+        return RegexBuilder::new(source)
+            .case_insensitive(true)
+            .build()
+            .map(Pattern::Regex)
+            .map_err(|e| anyhow!("invalid regex /{source}/: {e}"));
+    }
+    let value = value.to_lowercase();
+    Ok(if exact {
+        Pattern::Exact(value)
+    } else {
+        Pattern::Contains(value)
+    })
+}
+
+/// A `key[=value]` annotation or label term.
+fn key_value(value: &str, quoted: bool) -> Result<(String, Option<Pattern>)> {
+    match value.split_once('=') {
+        Some((key, v)) => Ok((key.to_lowercase(), Some(pattern(v, quoted, true)?))),
+        None => Ok((value.to_lowercase(), None)),
+    }
+}
+
+fn term(word: &Word) -> Result<Term> {
+    let free_text = || pattern(&word.text, word.quoted_from.is_some(), false).map(Term::Text);
+    let Some(split) = word.split else {
+        return free_text();
+    };
+    let field = word.text[..split].to_lowercase();
+    let exact = word.text[split..].starts_with('=');
+    let value = &word.text[split + 1..];
+    let quoted = word.quoted_from.is_some_and(|q| q > split);
+
+    Ok(match field.as_str() {
+        "has" => Term::Has(value.to_lowercase()),
+        "annotation" | "annotations" => {
+            let (key, value) = key_value(value, quoted)?;
+            Term::Annotation { key, value }
+        }
+        "label" | "labels" => {
+            let (key, value) = key_value(value, quoted)?;
+            Term::Label { key, value }
+        }
+        f if known_field(f) => Term::Field {
+            field,
+            pattern: pattern(value, quoted, exact)?,
+        },
+        f if f.starts_with("spec.") || f.starts_with("metadata.") => Term::Path {
+            path: word.text[..split].to_string(),
+            pattern: pattern(value, quoted, exact)?,
+        },
+        f => match relation_types()
+            .into_iter()
+            .find(|r| r.eq_ignore_ascii_case(f))
+        {
+            Some(relation) => Term::Relation {
+                relation,
+                pattern: pattern(value, quoted, exact)?,
+            },
+            None => return free_text(),
+        },
+    })
+}
+
+/// Recognized `field:` scopes.
+fn known_field(field: &str) -> bool {
+    matches!(
+        field,
+        "name"
+            | "title"
+            | "kind"
+            | "owner"
+            | "system"
+            | "domain"
+            | "tag"
+            | "tags"
+            | "lifecycle"
+            | "type"
+            | "namespace"
+            | "ns"
+            | "desc"
+            | "description"
+    )
+}
+
+/// The values of a searchable field of an entity; tags yield one value each.
+fn field_values(entity: &Entity, field: &str) -> Vec<String> {
+    let value = match field {
+        "name" => Some(entity.metadata.name.clone()),
+        "title" => entity.metadata.title.clone(),
+        "kind" => Some(entity.kind.to_string()),
+        "owner" => entity.owner(),
+        "system" => entity.system(),
+        "domain" => entity.domain(),
+        "tag" | "tags" => return entity.metadata.tags.clone(),
+        "lifecycle" => entity.lifecycle(),
+        "type" => entity.entity_type(),
+        "namespace" | "ns" => entity.metadata.namespace.clone(),
+        "desc" | "description" => entity.metadata.description.clone(),
+        _ => None,
+    };
+    value.into_iter().collect()
+}
+
+/// The value at a dotted `spec.`/`metadata.` path. Keys may themselves contain
+/// dots (`metadata.annotations.backstage.io/techdocs-ref`): the longest key
+/// present at each level wins.
+fn path_value(entity: &Entity, path: &str) -> Option<serde_yaml::Value> {
+    let (root, rest) = path.split_once('.')?;
+    let mut value = match root {
+        "spec" => entity.spec.clone(),
+        "metadata" => serde_yaml::to_value(&entity.metadata).ok()?,
+        _ => return None,
+    };
+    let segments: Vec<&str> = rest.split('.').collect();
+    let mut i = 0;
+    while i < segments.len() {
+        let map = value.as_mapping()?;
+        let (next, used) = (i + 1..=segments.len()).rev().find_map(|end| {
+            map.get(segments[i..end].join("."))
+                .map(|v| (v.clone(), end - i))
+        })?;
+        value = next;
+        i += used;
+    }
+    Some(value)
+}
+
+/// Scalar strings of a YAML value; a sequence yields each scalar item.
+fn scalars(value: &serde_yaml::Value) -> Vec<String> {
+    match value {
+        serde_yaml::Value::String(s) => vec![s.clone()],
+        serde_yaml::Value::Bool(b) => vec![b.to_string()],
+        serde_yaml::Value::Number(n) => vec![n.to_string()],
+        serde_yaml::Value::Sequence(seq) => seq.iter().flat_map(scalars).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn get_ignore_case<'a>(
+    map: &'a std::collections::HashMap<String, String>,
+    key: &str,
+) -> Option<&'a String> {
+    map.iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .map(|(_, v)| v)
+}
+
+impl Term {
+    fn matches(&self, label: Option<&str>, ews: Option<&EntityWithSource>) -> bool {
+        if let Term::Text(pattern) = self {
+            if label.is_some_and(|l| pattern.matches(l)) {
+                return true;
+            }
+        }
+        let Some(ews) = ews else {
+            return false;
+        };
+        let entity = &ews.entity;
+        match self {
+            Term::Text(pattern) => {
+                [
+                    Some(entity.metadata.name.clone()),
+                    entity.metadata.title.clone(),
+                    entity.metadata.description.clone(),
+                    Some(entity.kind.to_string()),
+                    entity.owner(),
+                ]
+                .iter()
+                .flatten()
+                .chain(&entity.metadata.tags)
+                .any(|h| pattern.matches(h))
+                    || pattern.fuzzy_needle().is_some_and(|needle| {
+                        fuzzy_targets(entity, needle)
+                            .iter()
+                            .any(|t| fuzzy(t, needle).is_some())
+                    })
+            }
+            Term::Field { field, pattern } => field_values(entity, field)
+                .iter()
+                .any(|v| pattern.matches(v)),
+            Term::Has(what) => has(ews, what),
+            Term::Annotation { key, value } => get_ignore_case(&entity.metadata.annotations, key)
+                .is_some_and(|v| value.as_ref().is_none_or(|p| p.matches(v))),
+            Term::Label { key, value } => get_ignore_case(&entity.metadata.labels, key)
+                .is_some_and(|v| value.as_ref().is_none_or(|p| p.matches(v))),
+            Term::Path { path, pattern } => path_value(entity, path)
+                .is_some_and(|v| scalars(&v).iter().any(|s| pattern.matches(s))),
+            Term::Relation { relation, pattern } => ews
+                .relations
+                .iter()
+                .any(|r| r.relation_type == *relation && pattern.matches_ref(&r.target_ref)),
+        }
+    }
+}
+
+/// `has:<what>` — TechDocs, ADRs, problems, or any non-empty field, path,
+/// relation, annotation or label.
+fn has(ews: &EntityWithSource, what: &str) -> bool {
+    let entity = &ews.entity;
+    let annotations = &entity.metadata.annotations;
+    match what {
+        "techdocs" | "docs" => get_ignore_case(annotations, TECHDOCS_ANNOTATION).is_some(),
+        "adr" | "adrs" => get_ignore_case(annotations, ADR_ANNOTATION).is_some(),
+        "errors" => !ews.validation_errors.is_empty(),
+        "duplicates" => !ews.duplicates.is_empty(),
+        "links" => !entity.metadata.links.is_empty(),
+        "annotations" => !annotations.is_empty(),
+        "labels" => !entity.metadata.labels.is_empty(),
+        "relations" => !ews.relations.is_empty(),
+        f if known_field(f) => field_values(entity, f).iter().any(|v| !v.is_empty()),
+        p if p.starts_with("spec.") || p.starts_with("metadata.") => {
+            path_value(entity, p).is_some_and(|v| !v.is_null())
+        }
+        w => {
+            ews.relations
+                .iter()
+                .any(|r| r.relation_type.eq_ignore_ascii_case(w))
+                || get_ignore_case(annotations, w).is_some()
+                || get_ignore_case(&entity.metadata.labels, w).is_some()
+        }
+    }
+}
+
+impl Expr {
+    fn matches(&self, label: Option<&str>, ews: Option<&EntityWithSource>) -> bool {
+        match self {
+            Expr::And(items) => items.iter().all(|e| e.matches(label, ews)),
+            Expr::Or(items) => items.iter().any(|e| e.matches(label, ews)),
+            Expr::Not(inner) => !inner.matches(label, ews),
+            Expr::Term(term) => term.matches(label, ews),
+        }
+    }
+}
+
+impl Expr {
+    /// Free-text terms that must match, i.e. not under a `NOT`.
+    fn positive_text<'a>(&'a self, out: &mut Vec<&'a Pattern>) {
+        match self {
+            Expr::And(items) | Expr::Or(items) => {
+                items.iter().for_each(|e| e.positive_text(out));
+            }
+            Expr::Not(_) => {}
+            Expr::Term(Term::Text(pattern)) => out.push(pattern),
+            Expr::Term(_) => {}
+        }
+    }
+}
+
+/// How well a node matches: higher scores rank first, and `highlights` are
+/// the char indices of its label that matched the free text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ranking {
+    pub score: i64,
+    pub highlights: Vec<usize>,
+}
+
+impl Query {
+    /// Parse a query; an empty query matches everything.
+    pub fn parse(input: &str) -> Result<Self> {
+        let tokens = tokenize(input)?;
+        if tokens.is_empty() {
+            return Ok(Self {
+                expr: Expr::And(Vec::new()),
+            });
+        }
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        if parser.pos < parser.tokens.len() {
+            bail!("unexpected `)`");
+        }
+        Ok(Self { expr })
+    }
+
+    /// A query matching `text` as a plain substring, for input that does not
+    /// parse (yet) while it is being typed.
+    pub fn text(text: &str) -> Self {
+        Self {
+            expr: Expr::Term(Term::Text(Pattern::Contains(text.trim().to_lowercase()))),
+        }
+    }
+
+    /// Whether an entity matches.
+    pub fn matches(&self, ews: &EntityWithSource) -> bool {
+        self.expr.matches(None, Some(ews))
+    }
+
+    /// Rank a tree node, or `None` if it does not match. Each free-text term
+    /// adds its best fuzzy score against the label, name, title and ref, with a
+    /// bonus for an exact name; field terms only filter.
+    pub fn rank_node(&self, label: &str, entity: Option<&EntityWithSource>) -> Option<Ranking> {
+        if !self.matches_node(label, entity) {
+            return None;
+        }
+        let mut texts = Vec::new();
+        self.expr.positive_text(&mut texts);
+        let mut ranking = Ranking {
+            score: 0,
+            highlights: Vec::new(),
+        };
+        for pattern in texts {
+            let Pattern::Contains(needle) = pattern else {
+                continue;
+            };
+            let in_label = fuzzy(label, needle);
+            let best = entity
+                .map(|e| fuzzy_targets(&e.entity, needle))
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|t| fuzzy(t, needle).map(|(score, _)| score))
+                .chain(in_label.as_ref().map(|(score, _)| *score))
+                .max()
+                .unwrap_or(0);
+            let exact = entity.is_some_and(|e| e.entity.metadata.name.eq_ignore_ascii_case(needle));
+            ranking.score += best + if exact { EXACT_NAME_BONUS } else { 0 };
+            match in_label {
+                Some((_, indices)) => ranking.highlights.extend(indices),
+                // Too short to match fuzzily: highlight the substring.
+                None => {
+                    let lower = label.to_lowercase();
+                    if let Some(at) = lower.find(needle.as_str()) {
+                        let start = lower[..at].chars().count();
+                        ranking
+                            .highlights
+                            .extend(start..start + needle.chars().count());
+                    }
+                }
+            }
+        }
+        ranking.highlights.sort_unstable();
+        ranking.highlights.dedup();
+        Some(ranking)
+    }
+
+    /// Whether a tree node matches: free text also searches the node's label,
+    /// and category nodes (without an entity) match on their label only.
+    pub fn matches_node(&self, label: &str, entity: Option<&EntityWithSource>) -> bool {
+        self.expr.matches(Some(label), entity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relations::attach_relations;
+    use serde::Deserialize;
+
+    const CATALOG: &str = "\
+apiVersion: backstage.io/v1alpha1
+kind: Component
+metadata:
+  name: checkout
+  title: Checkout Service
+  description: Takes payments
+  tags: [payments, java]
+  labels:
+    tier: '1'
+  annotations:
+    backstage.io/techdocs-ref: dir:.
+    github.com/project-slug: acme/checkout
+spec:
+  type: service
+  lifecycle: production
+  owner: payments
+  dependsOn: [resource:orders-db]
+---
+apiVersion: backstage.io/v1alpha1
+kind: Component
+metadata:
+  name: web
+  description: The storefront
+spec:
+  type: website
+  lifecycle: experimental
+  owner: storefront
+  dependsOn: [checkout]
+---
+apiVersion: backstage.io/v1alpha1
+kind: Resource
+metadata:
+  name: orders-db
+spec:
+  type: database
+  owner: payments
+";
+
+    fn names(query: &str) -> Vec<String> {
+        let mut entities: Vec<EntityWithSource> = serde_yaml::Deserializer::from_str(CATALOG)
+            .map(|doc| EntityWithSource::new(Entity::deserialize(doc).unwrap(), "c.yaml".into()))
+            .collect();
+        attach_relations(&mut entities);
+        let query = Query::parse(query).unwrap();
+        entities
+            .iter()
+            .filter(|e| query.matches(e))
+            .map(|e| e.entity.metadata.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn boolean_operators_and_grouping() {
+        assert_eq!(names("kind:component owner:payments"), ["checkout"]);
+        assert_eq!(names("kind:component AND owner:payments"), ["checkout"]);
+        assert_eq!(
+            names("owner:storefront OR kind:resource"),
+            ["web", "orders-db"]
+        );
+        assert_eq!(names("NOT kind:component"), ["orders-db"]);
+        assert_eq!(
+            names("kind:component AND (lifecycle=experimental OR has:techdocs)"),
+            ["checkout", "web"]
+        );
+        assert_eq!(names("owner:payments NOT kind:resource"), ["checkout"]);
+        assert_eq!(names(""), ["checkout", "web", "orders-db"]);
+    }
+
+    #[test]
+    fn phrases_exact_values_and_regexes() {
+        assert_eq!(names("\"checkout service\""), ["checkout"]);
+        assert!(names("\"service checkout\"").is_empty());
+        assert_eq!(names("type=service"), ["checkout"]);
+        assert_eq!(names("type:serv"), ["checkout"]);
+        assert_eq!(names("name:/^(web|orders)/"), ["web", "orders-db"]);
+        assert_eq!(names("tag=java"), ["checkout"]);
+        // A quoted value is literal, never a regex.
+        assert!(names("name:\"/^web/\"").is_empty());
+    }
+
+    #[test]
+    fn annotations_labels_paths_and_relations() {
+        assert_eq!(names("has:techdocs"), ["checkout"]);
+        assert_eq!(
+            names("annotation:github.com/project-slug=acme/checkout"),
+            ["checkout"]
+        );
+        assert_eq!(names("annotation:github.com/project-slug"), ["checkout"]);
+        assert_eq!(names("label:tier=1"), ["checkout"]);
+        assert_eq!(names("spec.type=database"), ["orders-db"]);
+        assert_eq!(
+            names("metadata.annotations.backstage.io/techdocs-ref=dir:."),
+            ["checkout"]
+        );
+        assert_eq!(names("dependsOn:orders-db"), ["checkout"]);
+        assert_eq!(names("dependsOn=checkout"), ["web"]);
+        assert_eq!(names("dependencyOf:web"), ["checkout"]);
+        assert_eq!(
+            names("ownedBy:group:default/payments"),
+            ["checkout", "orders-db"]
+        );
+    }
+
+    #[test]
+    fn free_text_matches_fuzzily_and_ranks() {
+        assert_eq!(names("chkout"), ["checkout"]);
+        assert_eq!(names("ordb"), ["orders-db"]);
+        // Too short to match fuzzily.
+        assert!(names("cx").is_empty());
+
+        let entities: Vec<EntityWithSource> = serde_yaml::Deserializer::from_str(CATALOG)
+            .map(|doc| EntityWithSource::new(Entity::deserialize(doc).unwrap(), "c.yaml".into()))
+            .collect();
+        let query = Query::parse("web").unwrap();
+        let rank =
+            |i: usize| query.rank_node(&entities[i].entity.metadata.name, Some(&entities[i]));
+        let web = rank(1).unwrap();
+        assert_eq!(web.highlights, [0, 1, 2]);
+        assert!(rank(0).is_none());
+        let score = |q: &str| {
+            Query::parse(q)
+                .unwrap()
+                .rank_node("checkout", Some(&entities[0]))
+                .map(|r| r.score)
+        };
+        assert!(score("checkout") > score("chkout"));
+        assert_eq!(names("component:default/chk"), ["checkout"]);
+    }
+
+    #[test]
+    fn unknown_fields_are_free_text() {
+        assert!(names("color:red").is_empty());
+        assert_eq!(names("storefront"), ["web"]);
+    }
+
+    #[test]
+    fn malformed_queries_are_errors() {
+        for bad in [
+            "(kind:api",
+            "kind:api)",
+            "OR web",
+            "web AND",
+            "NOT",
+            "\"open",
+            "name:/[/",
+        ] {
+            assert!(Query::parse(bad).is_err(), "{bad} should not parse");
+        }
+    }
+}
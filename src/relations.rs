@@ -0,0 +1,355 @@
+//! Backstage-style `relations` computed from the whole catalog.
+//!
+//! Backstage's catalog processors turn spec references into pairs of
+//! relations — a component `ownedBy` a group, and that group `ownerOf` the
+//! component. [`compute_relations`] does the same for a loaded catalog so
+//! entities can be emitted in the processed shape Backstage serves
+//! ([`WithRelations`]).
+
+use crate::entity::{Entity, EntityIndex, EntityRef, EntityWithSource};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// One relation on an entity, as it appears in a processed Backstage entity.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Relation {
+    #[serde(rename = "type")]
+    pub relation_type: String,
+    #[serde(rename = "targetRef")]
+    pub target_ref: String,
+}
+
+/// An entity serialized the way Backstage serves a processed entity:
+/// `apiVersion`, `kind`, `metadata`, `spec`, then `relations`.
+#[derive(Debug, Serialize)]
+pub struct WithRelations<'a> {
+    #[serde(flatten)]
+    pub entity: &'a Entity,
+    pub relations: &'a [Relation],
+}
+
+impl<'a> From<&'a EntityWithSource> for WithRelations<'a> {
+    fn from(ews: &'a EntityWithSource) -> Self {
+        Self {
+            entity: &ews.entity,
+            relations: &ews.relations,
+        }
+    }
+}
+
+/// Spec fields that produce relations: (spec key, default kind, fallback
+/// kinds, relation on the declaring entity, reverse relation on the target).
+const RELATION_FIELDS: &[(&str, &str, &[&str], &str, &str)] = &[
+    ("owner", "group", &[], "ownedBy", "ownerOf"),
+    ("system", "system", &[], "partOf", "hasPart"),
+    ("domain", "domain", &[], "partOf", "hasPart"),
+    ("subcomponentOf", "component", &[], "partOf", "hasPart"),
+    (
+        "dependsOn",
+        "component",
+        &["resource"],
+        "dependsOn",
+        "dependencyOf",
+    ),
+    (
+        "dependencyOf",
+        "component",
+        &["resource"],
+        "dependencyOf",
+        "dependsOn",
+    ),
+    ("providesApis", "api", &[], "providesApi", "apiProvidedBy"),
+    ("consumesApis", "api", &[], "consumesApi", "apiConsumedBy"),
+    ("memberOf", "group", &[], "memberOf", "hasMember"),
+    ("members", "user", &[], "hasMember", "memberOf"),
+    ("parent", "group", &[], "childOf", "parentOf"),
+    ("children", "group", &[], "parentOf", "childOf"),
+];
+
+/// Every relation type bsv produces, e.g. `ownedBy`, `dependsOn`, `hasPart`.
+pub fn relation_types() -> Vec<&'static str> {
+    let mut types: Vec<&'static str> = RELATION_FIELDS
+        .iter()
+        .flat_map(|&(_, _, _, forward, reverse)| [forward, reverse])
+        .collect();
+    types.sort_unstable();
+    types.dedup();
+    types
+}
+
+/// Resolve `reference` to the entity it names, trying the fallback kinds for
+/// unqualified references when the default kind does not match. Unresolved
+/// references keep their default kind, as Backstage would.
+fn resolve_target(
+    index: &EntityIndex,
+    reference: &str,
+    default_kind: &str,
+    fallbacks: &[&str],
+) -> EntityRef {
+    let parsed = EntityRef::parse(reference, default_kind);
+    if index.contains(&parsed) || !parsed.kind_inferred {
+        return parsed;
+    }
+    fallbacks
+        .iter()
+        .map(|kind| EntityRef::parse(reference, kind))
+        .find(|candidate| index.contains(candidate))
+        .unwrap_or(parsed)
+}
+
+/// The string values of a spec field, whether it holds one reference or a list.
+fn spec_refs(ews: &EntityWithSource, key: &str) -> Vec<String> {
+    match ews.entity.spec.get(key) {
+        Some(serde_yaml::Value::String(s)) => vec![s.clone()],
+        Some(serde_yaml::Value::Sequence(seq)) => seq
+            .iter()
+            .filter_map(|v| v.as_str().map(String::from))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// What one entity's spec references link it to: the relations it declares,
+/// and the reverse relations they give their targets. Kept per entity so a
+/// catalog can withdraw them when the entity changes (see
+/// [`crate::catalog::LoadedCatalog::update`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Links {
+    /// The entity's own relations, one per reference.
+    pub forward: Vec<Relation>,
+    /// The reverse relation each reference gives its target, by target ref.
+    pub reverse: Vec<(String, Relation)>,
+    /// Refs whose presence in the catalog decided where an unqualified
+    /// reference resolved (see [`resolve_target`]): when one of them comes or
+    /// goes, the links must be worked out again.
+    pub consulted: Vec<String>,
+}
+
+impl Links {
+    /// Resolve the references in `ews`'s spec against `index`.
+    pub fn of(ews: &EntityWithSource, index: &EntityIndex) -> Self {
+        let source = ews.entity.ref_key();
+        let mut links = Self::default();
+        for &(field, default_kind, fallbacks, forward, reverse) in RELATION_FIELDS {
+            for reference in spec_refs(ews, field) {
+                let parsed = EntityRef::parse(&reference, default_kind);
+                if parsed.kind_inferred && !fallbacks.is_empty() {
+                    links.consulted.push(parsed.canonical());
+                    links.consulted.extend(
+                        fallbacks
+                            .iter()
+                            .map(|kind| EntityRef::parse(&reference, kind).canonical()),
+                    );
+                }
+                let target_ref =
+                    resolve_target(index, &reference, default_kind, fallbacks).canonical();
+                links.forward.push(Relation {
+                    relation_type: forward.to_string(),
+                    target_ref: target_ref.clone(),
+                });
+                links.reverse.push((
+                    target_ref,
+                    Relation {
+                        relation_type: reverse.to_string(),
+                        target_ref: source.clone(),
+                    },
+                ));
+            }
+        }
+        links
+    }
+}
+
+/// Relations for every entity, parallel to `entities`, each list sorted by
+/// type then target and free of duplicates.
+///
+/// Every spec reference yields a relation on the declaring entity and, when the
+/// target is in the catalog, the reverse relation on the target.
+pub fn compute_relations(entities: &[EntityWithSource]) -> Vec<Vec<Relation>> {
+    let index = EntityIndex::build(entities);
+    let mut positions: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, ews) in entities.iter().enumerate() {
+        positions.entry(ews.entity.ref_key()).or_default().push(i);
+    }
+
+    let mut relations = vec![Vec::new(); entities.len()];
+    for (i, ews) in entities.iter().enumerate() {
+        let links = Links::of(ews, &index);
+        relations[i].extend(links.forward);
+        for (target_ref, relation) in links.reverse {
+            for &j in positions.get(&target_ref).into_iter().flatten() {
+                relations[j].push(relation.clone());
+            }
+        }
+    }
+
+    for list in &mut relations {
+        list.sort();
+        list.dedup();
+    }
+    relations
+}
+
+/// Reverse relation types and the forward type each one mirrors.
+const REVERSE_TYPES: &[(&str, &str)] = &[
+    ("ownerOf", "ownedBy"),
+    ("hasPart", "partOf"),
+    ("dependencyOf", "dependsOn"),
+    ("apiProvidedBy", "providesApi"),
+    ("apiConsumedBy", "consumesApi"),
+    ("hasMember", "memberOf"),
+    ("parentOf", "childOf"),
+];
+
+/// Every relation between entities once, as `(source, type, target)` refs in
+/// the forward direction (`ownedBy` rather than `ownerOf`), sorted. Uses the
+/// relations attached to `entities`.
+pub fn relation_edges(entities: &[EntityWithSource]) -> Vec<(String, String, String)> {
+    let mut edges: Vec<(String, String, String)> = entities
+        .iter()
+        .flat_map(|ews| {
+            let source = ews.entity.ref_key();
+            ews.relations.iter().map(move |relation| {
+                match REVERSE_TYPES
+                    .iter()
+                    .find(|(reverse, _)| *reverse == relation.relation_type)
+                {
+                    Some((_, forward)) => (
+                        relation.target_ref.clone(),
+                        forward.to_string(),
+                        source.clone(),
+                    ),
+                    None => (
+                        source.clone(),
+                        relation.relation_type.clone(),
+                        relation.target_ref.clone(),
+                    ),
+                }
+            })
+        })
+        .collect();
+    edges.sort();
+    edges.dedup();
+    edges
+}
+
+/// Compute relations across `entities` and store each entity's list on it.
+pub fn attach_relations(entities: &mut [EntityWithSource]) {
+    let relations = compute_relations(entities);
+    for (ews, relations) in entities.iter_mut().zip(relations) {
+        ews.relations = relations;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    fn load(yaml: &str) -> Vec<EntityWithSource> {
+        serde_yaml::Deserializer::from_str(yaml)
+            .map(|doc| EntityWithSource::new(Entity::deserialize(doc).unwrap(), "c.yaml".into()))
+            .collect()
+    }
+
+    fn rel(relation_type: &str, target_ref: &str) -> Relation {
+        Relation {
+            relation_type: relation_type.to_string(),
+            target_ref: target_ref.to_string(),
+        }
+    }
+
+    #[test]
+    fn references_produce_both_directions() {
+        let entities = load(
+            "\
+apiVersion: backstage.io/v1alpha1
+kind: Group
+metadata:
+  name: team
+spec:
+  type: team
+  members: [alice]
+---
+apiVersion: backstage.io/v1alpha1
+kind: Component
+metadata:
+  name: web
+spec:
+  owner: team
+  system: shop
+  dependsOn: [db]
+  providesApis: [web-api]
+---
+apiVersion: backstage.io/v1alpha1
+kind: Resource
+metadata:
+  name: db
+spec:
+  owner: user:alice
+---
+apiVersion: backstage.io/v1alpha1
+kind: User
+metadata:
+  name: alice
+spec:
+  memberOf: [team]
+",
+        );
+        let relations = compute_relations(&entities);
+
+        assert_eq!(
+            relations[0],
+            vec![
+                rel("hasMember", "user:default/alice"),
+                rel("ownerOf", "component:default/web"),
+            ]
+        );
+        assert_eq!(
+            relations[1],
+            vec![
+                rel("dependsOn", "resource:default/db"),
+                rel("ownedBy", "group:default/team"),
+                rel("partOf", "system:default/shop"),
+                rel("providesApi", "api:default/web-api"),
+            ]
+        );
+        assert_eq!(
+            relations[2],
+            vec![
+                rel("dependencyOf", "component:default/web"),
+                rel("ownedBy", "user:default/alice"),
+            ]
+        );
+        // memberOf and the group's members both yield the same pair, once.
+        assert_eq!(
+            relations[3],
+            vec![
+                rel("memberOf", "group:default/team"),
+                rel("ownerOf", "resource:default/db"),
+            ]
+        );
+    }
+
+    #[test]
+    fn attached_relations_serialize_after_spec() {
+        let mut entities = load(
+            "\
+apiVersion: backstage.io/v1alpha1
+kind: Component
+metadata:
+  name: web
+spec:
+  owner: team
+",
+        );
+        attach_relations(&mut entities);
+        let yaml = serde_yaml::to_string(&WithRelations::from(&entities[0])).unwrap();
+        assert!(
+            yaml.ends_with(
+                "spec:\n  owner: team\nrelations:\n- type: ownedBy\n  targetRef: group:default/team\n"
+            ),
+            "{yaml}"
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//! Load a catalog from a running Backstage instance (`--from-api`).
+//!
+//! [`load_from_api`] pages through the catalog REST API and turns each entity
+//! into an [`EntityWithSource`] whose source is the entity's `by-name` URL, so
+//! the rest of bsv (tree, validation, JSON output) works on it unchanged. Set
+//! `BACKSTAGE_TOKEN` to send a bearer token with each request.
+
+use crate::entity::{mark_duplicates, Entity, EntityWithSource};
+use crate::relations::attach_relations;
+use crate::validator::validate_entity;
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// Entities requested per page.
+const PAGE_SIZE: usize = 500;
+
+/// Environment variable holding an optional bearer token for the API.
+pub const TOKEN_ENV: &str = "BACKSTAGE_TOKEN";
+
+/// The catalog API base for a user-supplied URL: trailing slashes and a
+/// trailing `/entities` are dropped, so `http://host/api/catalog/` and
+/// `http://host/api/catalog/entities` both mean `http://host/api/catalog`.
+pub fn normalize_base_url(url: &str) -> String {
+    let trimmed = url.trim().trim_end_matches('/');
+    trimmed
+        .strip_suffix("/entities")
+        .unwrap_or(trimmed)
+        .to_string()
+}
+
+/// Fetch every entity from the catalog API at `base_url`, along with warnings
+/// for entities that could not be read.
+///
+/// Pages with `entities/by-query` cursors, falling back to `entities` with
+/// `offset`/`limit` for backends that predate `by-query`.
+pub fn load_from_api(base_url: &str) -> Result<(Vec<EntityWithSource>, Vec<String>)> {
+    let base = normalize_base_url(base_url);
+    let client = Client::new();
+    let raw = match client.by_query(&base)? {
+        Some(items) => items,
+        None => client.by_offset(&base)?,
+    };
+
+    let mut warnings = Vec::new();
+    let mut entities = Vec::new();
+    for item in raw {
+        match serde_json::from_value::<Entity>(item) {
+            Ok(entity) => {
+                let source = source_for(&base, &entity);
+                let validation_errors = validate_entity(&entity);
+                entities.push(
+                    EntityWithSource::new(entity, source).with_validation_errors(validation_errors),
+                );
+            }
+            Err(e) => warnings.push(format!("Failed to parse entity from {base}: {e}")),
+        }
+    }
+    mark_duplicates(&mut entities);
+    attach_relations(&mut entities);
+    Ok((entities, warnings))
+}
+
+/// The synthetic source of a fetched entity: its `by-name` URL.
+fn source_for(base: &str, entity: &Entity) -> PathBuf {
+    PathBuf::from(format!(
+        "{base}/entities/by-name/{}/{}/{}",
+        entity.kind.to_string().to_lowercase(),
+        entity.metadata.namespace.as_deref().unwrap_or("default"),
+        entity.metadata.name
+    ))
+}
+
+/// A thin HTTP client over the catalog endpoints.
+struct Client {
+    agent: ureq::Agent,
+    token: Option<String>,
+}
+
+impl Client {
+    fn new() -> Self {
+        let agent = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .build()
+            .into();
+        let token = std::env::var(TOKEN_ENV).ok().filter(|t| !t.is_empty());
+        Self { agent, token }
+    }
+
+    /// GET `url` with `query`, returning `None` for a 404 and the JSON body
+    /// otherwise.
+    fn get(&self, url: &str, query: &[(&str, String)]) -> Result<Option<Value>> {
+        let mut request = self.agent.get(url).header("Accept", "application/json");
+        for (key, value) in query {
+            request = request.query(*key, value);
+        }
+        if let Some(token) = &self.token {
+            request = request.header("Authorization", format!("Bearer {token}"));
+        }
+        let mut response = request
+            .call()
+            .with_context(|| format!("Failed to reach {url}"))?;
+        match response.status().as_u16() {
+            404 => Ok(None),
+            200..=299 => response
+                .body_mut()
+                .read_json()
+                .map(Some)
+                .with_context(|| format!("Invalid JSON from {url}")),
+            status => bail!("{url} returned HTTP {status}"),
+        }
+    }
+
+    /// All entities via `by-query` cursors, or `None` if the endpoint is missing.
+    fn by_query(&self, base: &str) -> Result<Option<Vec<Value>>> {
+        let url = format!("{base}/entities/by-query");
+        let mut items = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut query = vec![("limit", PAGE_SIZE.to_string())];
+            if let Some(c) = &cursor {
+                query.push(("cursor", c.clone()));
+            }
+            let Some(page) = self.get(&url, &query)? else {
+                return if cursor.is_none() {
+                    Ok(None)
+                } else {
+                    Err(anyhow!("{url} disappeared while paging"))
+                };
+            };
+            let page_items = page
+                .get("items")
+                .and_then(Value::as_array)
+                .ok_or_else(|| anyhow!("{url} did not return an `items` array"))?;
+            items.extend(page_items.iter().cloned());
+            match page.pointer("/pageInfo/nextCursor").and_then(Value::as_str) {
+                Some(next) if !page_items.is_empty() => cursor = Some(next.to_string()),
+                _ => return Ok(Some(items)),
+            }
+        }
+    }
+
+    /// All entities via `entities?offset=&limit=`.
+    fn by_offset(&self, base: &str) -> Result<Vec<Value>> {
+        let url = format!("{base}/entities");
+        let mut items = Vec::new();
+        loop {
+            let query = [
+                ("offset", items.len().to_string()),
+                ("limit", PAGE_SIZE.to_string()),
+            ];
+            let page = self
+                .get(&url, &query)?
+                .ok_or_else(|| anyhow!("{url} not found; is this a Backstage catalog API?"))?;
+            let page = page
+                .as_array()
+                .ok_or_else(|| anyhow!("{url} did not return an array"))?;
+            items.extend(page.iter().cloned());
+            if page.len() < PAGE_SIZE {
+                return Ok(items);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_url_is_normalized() {
+        assert_eq!(
+            normalize_base_url("http://host/api/catalog/"),
+            "http://host/api/catalog"
+        );
+        assert_eq!(
+            normalize_base_url("http://host/api/catalog/entities"),
+            "http://host/api/catalog"
+        );
+    }
+}
@@ -1,10 +1,17 @@
 //! Non-interactive catalog reporting for CI use.
 //!
 //! [`build_report`] collects schema-validation problems (gathered during
-//! parsing) and broken entity references into a [`Report`]. [`write_report`]
-//! renders it as plain text and [`write_json`] dumps the parsed entities.
+//! parsing), broken entity references and entities declared more than once
+//! into a [`Report`], and [`Report::with_lint`] applies the `[lint]`
+//! severities from the configuration. [`write_report`]
+//! renders it as plain text, [`write_json`] dumps the parsed entities,
+//! [`write_table`] lists them one per row and [`write_dot`] draws their
+//! relations as a Graphviz graph.
 
-use crate::entity::{EntityIndex, EntityRef, EntityWithSource};
+use crate::config::{LintConfig, Severity};
+use crate::entity::{duplicate_groups, EntityIndex, EntityRef, EntityWithSource};
+use crate::fix::did_you_mean;
+use crate::relations::{relation_edges, WithRelations};
 use serde::Serialize;
 use std::io::{self, Write};
 
@@ -23,6 +30,16 @@ pub struct BrokenRef {
     pub from: String,
     pub field: &'static str,
     pub reference: String,
+    /// Nearest existing refs the reference may have meant.
+    pub suggestions: Vec<String>,
+}
+
+/// An entity ref declared in more than one place.
+#[derive(Debug)]
+pub struct DuplicateEntity {
+    pub entity: String,
+    /// Every declaring `file:line`, in catalog order.
+    pub locations: Vec<String>,
 }
 
 /// Aggregated validation results for a catalog.
@@ -31,18 +48,43 @@ pub struct Report {
     pub entity_count: usize,
     pub schema_problems: Vec<SchemaProblem>,
     pub broken_refs: Vec<BrokenRef>,
+    pub duplicates: Vec<DuplicateEntity>,
+    /// Severity of each kind of problem; all errors unless configured.
+    pub lint: LintConfig,
 }
 
 impl Report {
-    /// Whether the catalog has any schema problems or broken references.
+    /// Apply configured severities: problems of a kind that is `off` are
+    /// dropped, and those that are `warn` no longer fail the report.
+    pub fn with_lint(mut self, lint: &LintConfig) -> Self {
+        if lint.schema == Some(Severity::Off) {
+            self.schema_problems.clear();
+        }
+        if lint.broken_refs == Some(Severity::Off) {
+            self.broken_refs.clear();
+        }
+        if lint.duplicates == Some(Severity::Off) {
+            self.duplicates.clear();
+        }
+        self.lint = lint.clone();
+        self
+    }
+
+    /// Whether the catalog has any schema problems, broken references or
+    /// duplicate entities that are errors.
     pub fn has_errors(&self) -> bool {
-        !self.schema_problems.is_empty() || !self.broken_refs.is_empty()
+        let failing = |count: usize, severity: Option<Severity>| {
+            count > 0 && severity.unwrap_or_default() == Severity::Error
+        };
+        failing(self.schema_problems.len(), self.lint.schema)
+            || failing(self.broken_refs.len(), self.lint.broken_refs)
+            || failing(self.duplicates.len(), self.lint.duplicates)
     }
 }
 
 /// Does `ref_str` resolve to a loaded entity, trying `default_kind` first and
 /// the `fallbacks` when the kind was inferred (mirrors graph resolution)?
-fn ref_resolves(
+pub(crate) fn ref_resolves(
     index: &EntityIndex,
     ref_str: &str,
     default_kind: &str,
@@ -60,6 +102,9 @@ fn ref_resolves(
     false
 }
 
+/// How many "did you mean" suggestions to list per broken reference.
+pub const MAX_SUGGESTIONS: usize = 3;
+
 /// Build a [`Report`] from already-parsed entities.
 pub fn build_report(entities: &[EntityWithSource]) -> Report {
     let index = EntityIndex::build(entities);
@@ -82,16 +127,36 @@ pub fn build_report(entities: &[EntityWithSource]) -> Report {
                 broken_refs.push(BrokenRef {
                     from: from.clone(),
                     field: r.field,
+                    suggestions: did_you_mean(
+                        &index,
+                        &r.reference,
+                        r.default_kind,
+                        r.fallback_kinds,
+                        MAX_SUGGESTIONS,
+                    ),
                     reference: r.reference,
                 });
             }
         }
     }
 
+    let duplicates = duplicate_groups(entities)
+        .into_iter()
+        .map(|group| DuplicateEntity {
+            entity: entities[group[0]].entity.ref_key(),
+            locations: group
+                .iter()
+                .map(|&i| format!("{}:{}", entities[i].source_file.display(), entities[i].line))
+                .collect(),
+        })
+        .collect();
+
     Report {
         entity_count: entities.len(),
         schema_problems,
         broken_refs,
+        duplicates,
+        lint: LintConfig::default(),
     }
 }
 
@@ -105,7 +170,12 @@ pub fn write_report<W: Write>(report: &Report, w: &mut W) -> io::Result<()> {
     writeln!(w, "Validated {} {entities}", report.entity_count)?;
 
     if !report.schema_problems.is_empty() {
-        writeln!(w, "\nSchema errors ({}):", report.schema_problems.len())?;
+        writeln!(
+            w,
+            "\nSchema errors ({}){}:",
+            report.schema_problems.len(),
+            warning_tag(report.lint.schema)
+        )?;
         for p in &report.schema_problems {
             writeln!(w, "  {} ({})", p.entity, p.source)?;
             writeln!(w, "    - {}: {}", p.path, truncate(&p.message, 160))?;
@@ -113,23 +183,60 @@ pub fn write_report<W: Write>(report: &Report, w: &mut W) -> io::Result<()> {
     }
 
     if !report.broken_refs.is_empty() {
-        writeln!(w, "\nBroken references ({}):", report.broken_refs.len())?;
+        writeln!(
+            w,
+            "\nBroken references ({}){}:",
+            report.broken_refs.len(),
+            warning_tag(report.lint.broken_refs)
+        )?;
         for r in &report.broken_refs {
-            writeln!(
-                w,
-                "  {} -> {}: {} (not found)",
-                r.from, r.field, r.reference
-            )?;
+            if r.suggestions.is_empty() {
+                writeln!(
+                    w,
+                    "  {} -> {}: {} (not found)",
+                    r.from, r.field, r.reference
+                )?;
+            } else {
+                writeln!(
+                    w,
+                    "  {} -> {}: {} (not found; did you mean {}?)",
+                    r.from,
+                    r.field,
+                    r.reference,
+                    r.suggestions.join(", ")
+                )?;
+            }
+        }
+    }
+
+    if !report.duplicates.is_empty() {
+        writeln!(
+            w,
+            "\nDuplicate entities ({}){}:",
+            report.duplicates.len(),
+            warning_tag(report.lint.duplicates)
+        )?;
+        for d in &report.duplicates {
+            writeln!(w, "  {} declared {} times", d.entity, d.locations.len())?;
+            for location in &d.locations {
+                writeln!(w, "    - {location}")?;
+            }
         }
     }
 
     writeln!(
         w,
-        "\nSummary: {} schema error{}, {} broken reference{}",
+        "\nSummary: {} schema error{}, {} broken reference{}, {} duplicate entit{}",
         report.schema_problems.len(),
         plural(report.schema_problems.len()),
         report.broken_refs.len(),
         plural(report.broken_refs.len()),
+        report.duplicates.len(),
+        if report.duplicates.len() == 1 {
+            "y"
+        } else {
+            "ies"
+        },
     )?;
 
     if report.has_errors() {
@@ -140,6 +247,15 @@ pub fn write_report<W: Write>(report: &Report, w: &mut W) -> io::Result<()> {
     Ok(())
 }
 
+/// Marks a section whose problems are configured as warnings.
+fn warning_tag(severity: Option<Severity>) -> &'static str {
+    if severity == Some(Severity::Warn) {
+        " [warning]"
+    } else {
+        ""
+    }
+}
+
 fn plural(n: usize) -> &'static str {
     if n == 1 {
         ""
@@ -161,18 +277,18 @@ fn truncate(s: &str, max: usize) -> String {
 #[derive(Serialize)]
 struct EntityJson<'a> {
     #[serde(flatten)]
-    entity: &'a crate::entity::Entity,
+    entity: WithRelations<'a>,
     #[serde(rename = "sourceFile")]
     source_file: String,
     valid: bool,
 }
 
-/// Dump the parsed entities as pretty JSON.
+/// Dump the parsed entities as pretty JSON, each with its `relations`.
 pub fn write_json<W: Write>(entities: &[EntityWithSource], w: &mut W) -> io::Result<()> {
     let view: Vec<EntityJson> = entities
         .iter()
         .map(|ews| EntityJson {
-            entity: &ews.entity,
+            entity: WithRelations::from(ews),
             source_file: ews.source_file.display().to_string(),
             valid: ews.validation_errors.is_empty(),
         })
@@ -181,6 +297,71 @@ pub fn write_json<W: Write>(entities: &[EntityWithSource], w: &mut W) -> io::Res
     writeln!(w, "{json}")
 }
 
+/// List entities as aligned columns: reference, type, lifecycle, owner and
+/// source location.
+pub fn write_table<W: Write>(entities: &[EntityWithSource], w: &mut W) -> io::Result<()> {
+    let header = ["REF", "TYPE", "LIFECYCLE", "OWNER", "SOURCE"].map(String::from);
+    let rows: Vec<[String; 5]> = entities
+        .iter()
+        .map(|ews| {
+            let e = &ews.entity;
+            [
+                e.ref_key(),
+                e.entity_type().unwrap_or_default(),
+                e.lifecycle().unwrap_or_default(),
+                e.owner().unwrap_or_default(),
+                format!("{}:{}", ews.source_file.display(), ews.line),
+            ]
+        })
+        .collect();
+    let mut widths = header.clone().map(|h| h.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in std::iter::once(&header).chain(&rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect();
+        writeln!(w, "{}", cells.join("  ").trim_end())?;
+    }
+    Ok(())
+}
+
+/// Write the catalog as a Graphviz `digraph`: a node per entity, labelled
+/// with its display name, and an edge per relation (each pair once).
+pub fn write_dot<W: Write>(entities: &[EntityWithSource], w: &mut W) -> io::Result<()> {
+    writeln!(w, "digraph catalog {{")?;
+    writeln!(w, "  rankdir=LR;")?;
+    writeln!(w, "  node [shape=box];")?;
+    let mut seen = std::collections::HashSet::new();
+    for ews in entities {
+        let key = ews.entity.ref_key();
+        if seen.insert(key.clone()) {
+            let label = format!("{}: {}", ews.entity.kind, ews.entity.display_name());
+            writeln!(w, "  {} [label={}];", dot_quote(&key), dot_quote(&label))?;
+        }
+    }
+    for (source, relation, target) in relation_edges(entities) {
+        writeln!(
+            w,
+            "  {} -> {} [label={}];",
+            dot_quote(&source),
+            dot_quote(&target),
+            dot_quote(&relation)
+        )?;
+    }
+    writeln!(w, "}}")
+}
+
+/// A DOT double-quoted string.
+fn dot_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -215,6 +396,107 @@ mod tests {
         assert!(String::from_utf8(buf).unwrap().ends_with("OK\n") || report.has_errors());
     }
 
+    #[test]
+    fn broken_reference_suggests_nearest_entities() {
+        let yaml = "\
+apiVersion: backstage.io/v1alpha1
+kind: Group
+metadata:
+  name: payments
+spec:
+  type: team
+  children: []
+---
+apiVersion: backstage.io/v1alpha1
+kind: System
+metadata:
+  name: checkout
+spec:
+  owner: paymnets
+---
+apiVersion: backstage.io/v1alpha1
+kind: Component
+metadata:
+  name: cart
+spec:
+  type: service
+  lifecycle: production
+  owner: payments
+  system: group:checkout
+";
+        let entities: Vec<EntityWithSource> = serde_yaml::Deserializer::from_str(yaml)
+            .map(|doc| {
+                let entity = serde::Deserialize::deserialize(doc).unwrap();
+                EntityWithSource::new(entity, "catalog-info.yaml".into())
+            })
+            .collect();
+        let report = build_report(&entities);
+        let suggestions: Vec<(&str, &[String])> = report
+            .broken_refs
+            .iter()
+            .map(|r| (r.reference.as_str(), r.suggestions.as_slice()))
+            .collect();
+        assert_eq!(
+            suggestions,
+            vec![
+                ("paymnets", &["payments".to_string()][..]),
+                ("group:checkout", &["system:checkout".to_string()][..]),
+            ]
+        );
+
+        let mut buf = Vec::new();
+        write_report(&report, &mut buf).unwrap();
+        assert!(String::from_utf8(buf)
+            .unwrap()
+            .contains("paymnets (not found; did you mean payments?)"));
+    }
+
+    #[test]
+    fn duplicate_entities_are_errors_with_both_locations() {
+        let doc = "apiVersion: backstage.io/v1alpha1\nkind: Group\nmetadata:\n  name: team\nspec:\n  type: team\n  children: []\n";
+        let entity: crate::entity::Entity = serde_yaml::from_str(doc).unwrap();
+        let entities = vec![
+            EntityWithSource::new(entity.clone(), "a/catalog-info.yaml".into()),
+            EntityWithSource::new(entity, "b/catalog-info.yaml".into()).with_line(9),
+        ];
+        let report = build_report(&entities);
+        assert!(report.has_errors());
+        assert_eq!(report.duplicates.len(), 1);
+        assert_eq!(
+            report.duplicates[0].locations,
+            vec!["a/catalog-info.yaml:1", "b/catalog-info.yaml:9"]
+        );
+
+        let mut buf = Vec::new();
+        write_report(&report, &mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.contains("group:default/team declared 2 times"));
+        assert!(text.contains("    - b/catalog-info.yaml:9"));
+        assert!(text.contains("1 duplicate entity"));
+        assert!(text.ends_with("FAILED\n"));
+
+        // As a warning, duplicates are listed but the report passes.
+        let lint = LintConfig {
+            duplicates: Some(Severity::Warn),
+            ..LintConfig::default()
+        };
+        let report = build_report(&entities).with_lint(&lint);
+        assert!(!report.has_errors());
+        let mut buf = Vec::new();
+        write_report(&report, &mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.contains("Duplicate entities (1) [warning]:"));
+        assert!(text.ends_with("OK\n"));
+
+        // Turned off, they are not reported at all.
+        let lint = LintConfig {
+            duplicates: Some(Severity::Off),
+            ..LintConfig::default()
+        };
+        let report = build_report(&entities).with_lint(&lint);
+        assert!(report.duplicates.is_empty() && !report.has_errors());
+    }
+
     #[test]
     fn json_output_is_valid_json() {
         let entities = load("testdata/catalog-info.yaml");
@@ -224,5 +506,45 @@ mod tests {
         let parsed: serde_json::Value = serde_json::from_str(&text).unwrap();
         assert!(parsed.is_array());
         assert_eq!(parsed.as_array().unwrap().len(), entities.len());
+        assert_eq!(
+            parsed[0]["relations"],
+            serde_json::json!([{ "type": "ownedBy", "targetRef": "group:default/team-test" }])
+        );
+    }
+
+    #[test]
+    fn dot_output_draws_each_relation_once_forward() {
+        let entities = load("testdata/large-catalog.yaml");
+        let mut buf = Vec::new();
+        write_dot(&entities, &mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.starts_with("digraph catalog {\n"));
+        assert!(text.contains(
+            "  \"component:default/payment-gateway\" [label=\"Component: Payment Gateway\"];"
+        ));
+        // Declared on the component, mirrored on the group: drawn once.
+        let owned = "  \"component:default/payment-gateway\" -> \"group:default/payments-squad\" [label=\"ownedBy\"];";
+        assert_eq!(text.matches(owned).count(), 1);
+        assert!(text.contains(
+            "  \"component:default/payment-gateway\" -> \"resource:default/payments-db\" [label=\"dependsOn\"];"
+        ));
+        assert!(!text.contains("ownerOf") && !text.contains("hasPart"));
+    }
+
+    #[test]
+    fn table_output_aligns_columns() {
+        let entities = load("testdata/catalog-info.yaml");
+        let mut buf = Vec::new();
+        write_table(&entities, &mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines[0],
+            "REF                               TYPE     LIFECYCLE   OWNER      SOURCE"
+        );
+        assert_eq!(
+            lines[1],
+            "component:default/test-component  service  production  team-test  testdata/catalog-info.yaml:2"
+        );
     }
 }
@@ -0,0 +1,763 @@
+//! Scaffolding new entities (`bsv new` and the TUI wizard).
+//!
+//! A [`Wizard`] walks through the [`Step`]s that apply to the chosen kind —
+//! kind, name, type, owner, system, lifecycle and the file to write to —
+//! offering choices drawn from the loaded catalog at each step. The finished
+//! [`EntityDraft`] is checked with [`validate_entity`] before anything is
+//! written, and [`write_entity`] appends it to an existing catalog file (as a
+//! new `---` document) or creates a new `catalog-info.yaml`.
+//!
+//! The CLI drives the same wizard over stdin/stdout with [`run_prompts`].
+
+use crate::edit::LIFECYCLES;
+use crate::entity::{Entity, EntityIndex, EntityKind, EntityRef, EntityWithSource, Metadata};
+use crate::validator::validate_entity;
+use anyhow::{bail, Context, Result};
+use serde_yaml::{Mapping, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// File name used when the wizard creates a new catalog file.
+pub const DEFAULT_FILE_NAME: &str = "catalog-info.yaml";
+
+/// Kinds the wizard can create.
+pub const KINDS: [EntityKind; 7] = [
+    EntityKind::Component,
+    EntityKind::Api,
+    EntityKind::Resource,
+    EntityKind::System,
+    EntityKind::Domain,
+    EntityKind::Group,
+    EntityKind::User,
+];
+
+/// One question asked by the wizard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Kind,
+    Name,
+    Type,
+    Owner,
+    System,
+    Lifecycle,
+    Target,
+}
+
+impl Step {
+    pub fn label(self) -> &'static str {
+        match self {
+            Step::Kind => "Kind",
+            Step::Name => "Name",
+            Step::Type => "Type",
+            Step::Owner => "Owner",
+            Step::System => "System",
+            Step::Lifecycle => "Lifecycle",
+            Step::Target => "File",
+        }
+    }
+
+    /// Whether the step may be left empty.
+    pub fn optional(self) -> bool {
+        matches!(self, Step::System)
+    }
+
+    /// The step responsible for a schema error at `path` (e.g. `/spec/owner`).
+    fn for_error_path(path: &str) -> Option<Step> {
+        match path {
+            "/metadata/name" | "/metadata" => Some(Step::Name),
+            "/spec/type" => Some(Step::Type),
+            "/spec/owner" => Some(Step::Owner),
+            "/spec/system" => Some(Step::System),
+            "/spec/lifecycle" => Some(Step::Lifecycle),
+            _ => None,
+        }
+    }
+}
+
+/// The steps that apply to `kind`, in order.
+pub fn steps_for(kind: &EntityKind) -> Vec<Step> {
+    let mut steps = vec![Step::Kind, Step::Name];
+    match kind {
+        EntityKind::Component | EntityKind::Api => {
+            steps.extend([Step::Type, Step::Owner, Step::System, Step::Lifecycle]);
+        }
+        EntityKind::Resource => steps.extend([Step::Type, Step::Owner, Step::System]),
+        EntityKind::System | EntityKind::Domain => steps.push(Step::Owner),
+        EntityKind::Group => steps.push(Step::Type),
+        _ => {}
+    }
+    steps.push(Step::Target);
+    steps
+}
+
+/// Well-known `spec.type` values per kind (from the Backstage schema examples).
+pub(crate) fn well_known_types(kind: &EntityKind) -> &'static [&'static str] {
+    match kind {
+        EntityKind::Component => &["service", "website", "library"],
+        EntityKind::Api => &["openapi", "asyncapi", "graphql", "grpc"],
+        EntityKind::Resource => &["database", "s3-bucket", "cluster"],
+        EntityKind::Group => &["team", "business-unit", "product-area", "root"],
+        _ => &[],
+    }
+}
+
+/// Answers collected by the wizard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityDraft {
+    pub kind: EntityKind,
+    pub name: String,
+    pub entity_type: String,
+    pub owner: String,
+    pub system: String,
+    pub lifecycle: String,
+    /// File (or directory) to write the entity to.
+    pub target: String,
+}
+
+impl Default for EntityDraft {
+    fn default() -> Self {
+        Self {
+            kind: EntityKind::Component,
+            name: String::new(),
+            entity_type: String::new(),
+            owner: String::new(),
+            system: String::new(),
+            lifecycle: String::new(),
+            target: String::new(),
+        }
+    }
+}
+
+impl EntityDraft {
+    /// The answer currently recorded for `step`.
+    pub fn value(&self, step: Step) -> String {
+        match step {
+            Step::Kind => self.kind.to_string(),
+            Step::Name => self.name.clone(),
+            Step::Type => self.entity_type.clone(),
+            Step::Owner => self.owner.clone(),
+            Step::System => self.system.clone(),
+            Step::Lifecycle => self.lifecycle.clone(),
+            Step::Target => self.target.clone(),
+        }
+    }
+
+    /// Record the answer for `step`. Kinds are matched case-insensitively.
+    pub fn set(&mut self, step: Step, value: &str) -> Result<()> {
+        let value = value.trim().to_string();
+        match step {
+            Step::Kind => {
+                self.kind = KINDS
+                    .iter()
+                    .find(|k| k.to_string().eq_ignore_ascii_case(&value))
+                    .cloned()
+                    .with_context(|| format!("unknown kind `{value}`"))?;
+            }
+            Step::Name => self.name = value,
+            Step::Type => self.entity_type = value,
+            Step::Owner => self.owner = value,
+            Step::System => self.system = value,
+            Step::Lifecycle => self.lifecycle = value,
+            Step::Target => self.target = value,
+        }
+        Ok(())
+    }
+
+    /// The canonical reference of the entity being created.
+    pub fn ref_key(&self) -> String {
+        self.to_entity().ref_key()
+    }
+
+    /// Build the entity, including the placeholders some kinds require
+    /// (`children` for groups, `memberOf` for users, an API `definition`).
+    pub fn to_entity(&self) -> Entity {
+        let steps = steps_for(&self.kind);
+        let mut spec = Mapping::new();
+        let mut put = |key: &str, value: Value| {
+            spec.insert(Value::String(key.to_string()), value);
+        };
+        for (step, key, value) in [
+            (Step::Type, "type", &self.entity_type),
+            (Step::Lifecycle, "lifecycle", &self.lifecycle),
+            (Step::Owner, "owner", &self.owner),
+            (Step::System, "system", &self.system),
+        ] {
+            if steps.contains(&step) && !(step.optional() && value.is_empty()) {
+                put(key, Value::String(value.clone()));
+            }
+        }
+        match self.kind {
+            EntityKind::Api => put(
+                "definition",
+                Value::String(format!("TODO: add the {} definition", self.entity_type)),
+            ),
+            EntityKind::Group => put("children", Value::Sequence(Vec::new())),
+            EntityKind::User => put("memberOf", Value::Sequence(Vec::new())),
+            _ => {}
+        }
+
+        Entity {
+            api_version: "backstage.io/v1alpha1".to_string(),
+            kind: self.kind.clone(),
+            metadata: Metadata {
+                name: self.name.clone(),
+                title: None,
+                namespace: None,
+                description: None,
+                labels: HashMap::new(),
+                annotations: HashMap::new(),
+                tags: Vec::new(),
+                links: Vec::new(),
+            },
+            spec: Value::Mapping(spec),
+        }
+    }
+
+    /// The entity as a YAML document (without a leading `---`).
+    pub fn to_yaml(&self) -> Result<String> {
+        Ok(serde_yaml::to_string(&self.to_entity())?)
+    }
+
+    /// Problems that would make the entity invalid: schema errors from
+    /// [`validate_entity`] plus a clash with an entity already in the catalog.
+    /// Each problem is paired with the step that can fix it, if any.
+    pub fn problems(&self, index: &EntityIndex) -> Vec<(Option<Step>, String)> {
+        let mut problems: Vec<(Option<Step>, String)> = validate_entity(&self.to_entity())
+            .into_iter()
+            .map(|e| {
+                (
+                    Step::for_error_path(&e.path),
+                    format!("{}: {}", e.path, e.message),
+                )
+            })
+            .collect();
+        if !is_valid_name(&self.name) {
+            problems.push((
+                Some(Step::Name),
+                format!(
+                    "`{}` is not a valid name: use up to 63 letters and digits, \
+                     separated by single `-`, `_` or `.`",
+                    self.name
+                ),
+            ));
+        }
+        let own_ref = EntityRef::parse(&self.name, &self.kind.to_string().to_lowercase());
+        if index.contains(&own_ref) {
+            problems.push((
+                Some(Step::Name),
+                format!("{} already exists", self.ref_key()),
+            ));
+        }
+        problems
+    }
+}
+
+/// Backstage's rule for `metadata.name`: 1–63 characters, alphanumerics
+/// separated by single `-`, `_` or `.`. (The JSON Schema only requires a
+/// non-empty string; the catalog backend rejects anything else.)
+fn is_valid_name(name: &str) -> bool {
+    let bytes = name.as_bytes();
+    !bytes.is_empty()
+        && bytes.len() <= 63
+        && bytes.first().is_some_and(u8::is_ascii_alphanumeric)
+        && bytes.last().is_some_and(u8::is_ascii_alphanumeric)
+        && bytes
+            .iter()
+            .all(|b| b.is_ascii_alphanumeric() || b"-_.".contains(b))
+        && !bytes
+            .windows(2)
+            .any(|w| !w[0].is_ascii_alphanumeric() && !w[1].is_ascii_alphanumeric())
+}
+
+/// Choices offered at `step`, drawn from the loaded catalog (plus well-known
+/// values), filtered by `input`.
+pub fn choices(
+    step: Step,
+    draft: &EntityDraft,
+    entities: &[EntityWithSource],
+    index: &EntityIndex,
+    root: &Path,
+    input: &str,
+) -> Vec<String> {
+    let candidates: Vec<String> = match step {
+        Step::Kind => KINDS.iter().map(|k| k.to_string()).collect(),
+        Step::Name => Vec::new(),
+        Step::Type => known_then_seen(
+            well_known_types(&draft.kind),
+            entities
+                .iter()
+                .filter(|e| e.entity.kind == draft.kind)
+                .filter_map(|e| e.entity.entity_type()),
+        ),
+        Step::Owner => ["group", "user"]
+            .iter()
+            .flat_map(|kind| index.refs_of_kind(kind))
+            .map(|r| r.shorthand("group"))
+            .collect(),
+        Step::System => index
+            .refs_of_kind("system")
+            .into_iter()
+            .map(|r| r.shorthand("system"))
+            .collect(),
+        Step::Lifecycle => known_then_seen(
+            LIFECYCLES,
+            entities.iter().filter_map(|e| e.entity.lifecycle()),
+        ),
+        Step::Target => target_choices(entities, root),
+    };
+    let needle = input.trim().to_lowercase();
+    candidates
+        .into_iter()
+        .filter(|c| c.to_lowercase().contains(&needle))
+        .collect()
+}
+
+/// Well-known values first, then any others used in the catalog (sorted).
+fn known_then_seen(known: &[&str], seen: impl Iterator<Item = String>) -> Vec<String> {
+    let mut values: Vec<String> = known.iter().map(|s| s.to_string()).collect();
+    let extra: BTreeSet<String> = seen.filter(|v| !known.contains(&v.as_str())).collect();
+    values.extend(extra);
+    values
+}
+
+/// Files the entity can be written to: a new `catalog-info.yaml` next to the
+/// catalog root (if there isn't one yet), then every file already in the catalog.
+fn target_choices(entities: &[EntityWithSource], root: &Path) -> Vec<String> {
+    let files: BTreeSet<PathBuf> = entities.iter().map(|e| e.source_file.clone()).collect();
+    let default = default_target(root);
+    let mut targets = Vec::new();
+    if !files.contains(&default) {
+        targets.push(default.display().to_string());
+    }
+    targets.extend(files.iter().map(|f| f.display().to_string()));
+    targets
+}
+
+/// `catalog-info.yaml` in the catalog root (or beside it, for a single file).
+fn default_target(root: &Path) -> PathBuf {
+    if root.is_file() {
+        root.with_file_name(DEFAULT_FILE_NAME)
+    } else {
+        root.join(DEFAULT_FILE_NAME)
+    }
+}
+
+/// Write `yaml` to `target` — appended as a new document if the file exists,
+/// a new file otherwise (a directory gets a `catalog-info.yaml`). Returns the
+/// file written and the 1-based line where the entity's document starts.
+pub fn write_entity(target: &Path, yaml: &str) -> Result<(PathBuf, usize)> {
+    let path = if target.is_dir() {
+        target.join(DEFAULT_FILE_NAME)
+    } else {
+        target.to_path_buf()
+    };
+
+    let existing = if path.exists() {
+        fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read file: {}", path.display()))?
+    } else {
+        String::new()
+    };
+
+    let (content, line) = if existing.trim().is_empty() {
+        (yaml.to_string(), 1)
+    } else {
+        let mut content = existing;
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str("---\n");
+        let line = content.lines().count() + 1;
+        content.push_str(yaml);
+        (content, line)
+    };
+
+    fs::write(&path, content)
+        .with_context(|| format!("Failed to write file: {}", path.display()))?;
+    Ok((path, line))
+}
+
+/// Interaction state of the TUI "new entity" wizard.
+#[derive(Debug, Default)]
+pub struct Wizard {
+    pub draft: EntityDraft,
+    /// Index into [`steps_for`] of the current step.
+    pub step: usize,
+    /// Text typed for the current step.
+    pub input: String,
+    /// What was typed before cycling completions with Tab.
+    pub typed: String,
+    /// Index of the completion last inserted with Tab, if cycling.
+    pub completion: Option<usize>,
+    /// Validation problems from the last attempt to finish.
+    pub errors: Vec<String>,
+}
+
+impl Wizard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn steps(&self) -> Vec<Step> {
+        steps_for(&self.draft.kind)
+    }
+
+    pub fn current(&self) -> Step {
+        let steps = self.steps();
+        steps[self.step.min(steps.len() - 1)]
+    }
+
+    pub fn is_last_step(&self) -> bool {
+        self.step + 1 >= self.steps().len()
+    }
+
+    /// Start editing the current step from its recorded answer.
+    fn load_step(&mut self) {
+        self.input = self.draft.value(self.current());
+        self.typed = self.input.clone();
+        self.completion = None;
+    }
+
+    fn go_to(&mut self, step: Step) {
+        if let Some(i) = self.steps().iter().position(|&s| s == step) {
+            self.step = i;
+            self.load_step();
+        }
+    }
+
+    pub fn input_char(&mut self, c: char) {
+        self.input.push(c);
+        self.typed = self.input.clone();
+        self.completion = None;
+    }
+
+    pub fn backspace(&mut self) {
+        self.input.pop();
+        self.typed = self.input.clone();
+        self.completion = None;
+    }
+
+    /// Insert the next candidate from `candidates` (Tab), cycling.
+    pub fn complete(&mut self, candidates: &[String]) {
+        if candidates.is_empty() {
+            return;
+        }
+        let next = self.completion.map_or(0, |i| (i + 1) % candidates.len());
+        self.input = candidates[next].clone();
+        self.completion = Some(next);
+    }
+
+    /// Go back one step (Esc). Returns false when already at the first step.
+    pub fn back(&mut self) -> bool {
+        if self.step == 0 {
+            return false;
+        }
+        self.step -= 1;
+        self.load_step();
+        true
+    }
+
+    /// Accept the current input (Enter). An empty input takes the first
+    /// candidate for steps that have one. Returns `Ok(true)` when the last step
+    /// was accepted and the draft is ready to validate.
+    pub fn accept(&mut self, candidates: &[String]) -> Result<bool> {
+        let step = self.current();
+        let mut value = self.input.trim().to_string();
+        if value.is_empty() && !step.optional() {
+            match candidates.first() {
+                Some(first) => value = first.clone(),
+                None => bail!("{} is required", step.label().to_lowercase()),
+            }
+        }
+        self.draft.set(step, &value)?;
+        self.errors.clear();
+        if self.is_last_step() {
+            return Ok(true);
+        }
+        self.step += 1;
+        self.load_step();
+        Ok(false)
+    }
+
+    /// Validate the finished draft. On problems, record them and return to
+    /// the first step that can fix one.
+    pub fn check(&mut self, index: &EntityIndex) -> bool {
+        let problems = self.draft.problems(index);
+        if problems.is_empty() {
+            return true;
+        }
+        let first_step = problems.iter().find_map(|(step, _)| *step);
+        self.errors = problems.into_iter().map(|(_, message)| message).collect();
+        if let Some(step) = first_step {
+            self.go_to(step);
+        }
+        false
+    }
+}
+
+/// Run the wizard over a line-based prompt (the `bsv new` CLI), returning
+/// the validated draft, or `None` if the user declined to write it.
+///
+/// Each step lists its choices by number; an answer may be a number, free
+/// text, or empty for the first choice.
+pub fn run_prompts<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    entities: &[EntityWithSource],
+    root: &Path,
+) -> Result<Option<EntityDraft>> {
+    const SHOWN: usize = 12;
+
+    let index = EntityIndex::build(entities);
+    let mut wizard = Wizard::new();
+    loop {
+        let step = wizard.current();
+        let candidates = choices(step, &wizard.draft, entities, &index, root, "");
+        for (n, choice) in candidates.iter().take(SHOWN).enumerate() {
+            writeln!(output, "  {:>2}) {choice}", n + 1)?;
+        }
+        if candidates.len() > SHOWN {
+            writeln!(
+                output,
+                "      … {} more (type to enter any value)",
+                candidates.len() - SHOWN
+            )?;
+        }
+        let default = match wizard.input.as_str() {
+            "" if step.optional() => "none".to_string(),
+            "" => candidates.first().cloned().unwrap_or_default(),
+            current => current.to_string(),
+        };
+        if default.is_empty() {
+            write!(output, "{}: ", step.label())?;
+        } else {
+            write!(output, "{} [{default}]: ", step.label())?;
+        }
+        output.flush()?;
+
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            bail!("input ended before the wizard finished");
+        }
+        let answer = line.trim();
+        wizard.input = match answer.parse::<usize>() {
+            Ok(n) if (1..=candidates.len()).contains(&n) => candidates[n - 1].clone(),
+            _ if answer.is_empty() => wizard.input.clone(),
+            _ => answer.to_string(),
+        };
+
+        match wizard.accept(&candidates) {
+            Ok(false) => {}
+            Ok(true) => {
+                if wizard.check(&index) {
+                    break;
+                }
+                writeln!(output, "\nThe entity is not valid yet:")?;
+                for error in &wizard.errors {
+                    writeln!(output, "  - {error}")?;
+                }
+                if wizard.is_last_step() {
+                    bail!("cannot create an invalid entity");
+                }
+                writeln!(output)?;
+            }
+            Err(e) => writeln!(output, "  {e}")?,
+        }
+    }
+
+    let yaml = wizard.draft.to_yaml()?;
+    writeln!(output, "\n{yaml}")?;
+    write!(output, "Write to {}? [Y/n]: ", wizard.draft.target)?;
+    output.flush()?;
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    let confirmed = matches!(line.trim().to_lowercase().as_str(), "" | "y" | "yes");
+    Ok(confirmed.then_some(wizard.draft))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use serde::Deserialize;
+    use std::io::Cursor;
+
+    fn catalog() -> Vec<EntityWithSource> {
+        let yaml = "\
+apiVersion: backstage.io/v1alpha1
+kind: Group
+metadata:
+  name: team-a
+spec:
+  type: team
+  children: []
+---
+apiVersion: backstage.io/v1alpha1
+kind: System
+metadata:
+  name: billing
+spec:
+  owner: team-a
+---
+apiVersion: backstage.io/v1alpha1
+kind: Component
+metadata:
+  name: payments
+spec:
+  type: batch-job
+  lifecycle: sunset
+  owner: team-a
+";
+        serde_yaml::Deserializer::from_str(yaml)
+            .map(|doc| {
+                let entity = Entity::deserialize(doc).unwrap();
+                EntityWithSource::new(entity, PathBuf::from("catalog/all.yaml"))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn steps_depend_on_kind() {
+        use Step::*;
+        assert_eq!(
+            steps_for(&EntityKind::Component),
+            vec![Kind, Name, Type, Owner, System, Lifecycle, Target]
+        );
+        assert_eq!(
+            steps_for(&EntityKind::Domain),
+            vec![Kind, Name, Owner, Target]
+        );
+        assert_eq!(steps_for(&EntityKind::User), vec![Kind, Name, Target]);
+    }
+
+    #[test]
+    fn choices_come_from_the_catalog() {
+        let entities = catalog();
+        let index = EntityIndex::build(&entities);
+        let draft = EntityDraft::default();
+        let root = Path::new("catalog");
+        let choose = |step, input| choices(step, &draft, &entities, &index, root, input);
+
+        assert_eq!(choose(Step::Owner, ""), vec!["team-a"]);
+        assert_eq!(choose(Step::System, ""), vec!["billing"]);
+        assert!(choose(Step::Type, "").contains(&"batch-job".to_string()));
+        assert_eq!(choose(Step::Lifecycle, "sun"), vec!["sunset"]);
+        assert_eq!(
+            choose(Step::Target, ""),
+            vec!["catalog/catalog-info.yaml", "catalog/all.yaml"]
+        );
+    }
+
+    #[test]
+    fn drafts_of_every_kind_are_schema_valid() {
+        let index = EntityIndex::build(&[]);
+        for kind in KINDS {
+            let draft = EntityDraft {
+                kind: kind.clone(),
+                name: "new-thing".into(),
+                entity_type: "service".into(),
+                owner: "team-a".into(),
+                lifecycle: "production".into(),
+                ..EntityDraft::default()
+            };
+            assert_eq!(draft.problems(&index), vec![], "{kind} draft is valid");
+        }
+    }
+
+    #[test]
+    fn api_kind_is_written_as_api() {
+        let draft = EntityDraft {
+            kind: EntityKind::Api,
+            name: "orders-api".into(),
+            entity_type: "openapi".into(),
+            owner: "team-a".into(),
+            lifecycle: "production".into(),
+            ..EntityDraft::default()
+        };
+        let yaml = draft.to_yaml().unwrap();
+        assert!(yaml.starts_with("apiVersion: backstage.io/v1alpha1\nkind: API\n"));
+        assert!(
+            !yaml.contains("system"),
+            "empty optional fields are omitted"
+        );
+    }
+
+    #[test]
+    fn invalid_or_duplicate_drafts_point_at_the_step_to_fix() {
+        let entities = catalog();
+        let index = EntityIndex::build(&entities);
+        let mut wizard = Wizard::new();
+        wizard.draft = EntityDraft {
+            name: "payments".into(),
+            entity_type: "service".into(),
+            owner: "team-a".into(),
+            lifecycle: "production".into(),
+            ..EntityDraft::default()
+        };
+        wizard.step = wizard.steps().len() - 1;
+        assert!(!wizard.check(&index));
+        assert_eq!(wizard.current(), Step::Name);
+        assert_eq!(wizard.input, "payments");
+        assert!(wizard.errors[0].contains("already exists"));
+
+        wizard.draft.name = "Not A Valid Name!".into();
+        assert!(!wizard.check(&index));
+        assert_eq!(wizard.current(), Step::Name);
+    }
+
+    #[test]
+    fn names_follow_backstage_rules() {
+        for ok in ["a", "payments-api", "team_a.v2", "A1"] {
+            assert!(is_valid_name(ok), "{ok}");
+        }
+        for bad in ["", "-a", "a-", "a--b", "a b", "ä", &"x".repeat(64)] {
+            assert!(!is_valid_name(bad), "{bad}");
+        }
+    }
+
+    #[test]
+    fn write_appends_a_document_or_creates_a_file() {
+        let dir = TempDir::new("scaffold-test");
+
+        let (path, line) = write_entity(&dir, "kind: Domain\n").unwrap();
+        assert_eq!(path, dir.join(DEFAULT_FILE_NAME));
+        assert_eq!(line, 1);
+
+        let (_, line) = write_entity(&path, "kind: System\n").unwrap();
+        assert_eq!(line, 3);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "kind: Domain\n---\nkind: System\n"
+        );
+    }
+
+    #[test]
+    fn prompts_accept_numbers_text_and_defaults() {
+        let entities = catalog();
+        // Kind: 1 (Component), name, type: default, owner: 1, system: skip,
+        // lifecycle: typed, file: 2 (the existing file), confirm.
+        let answers = "1\nrefunds\n\n1\n\nexperimental\n2\ny\n";
+        let mut output = Vec::new();
+        let draft = run_prompts(
+            &mut Cursor::new(answers),
+            &mut output,
+            &entities,
+            Path::new("catalog"),
+        )
+        .unwrap()
+        .expect("confirmed");
+
+        assert_eq!(draft.kind, EntityKind::Component);
+        assert_eq!(draft.name, "refunds");
+        assert_eq!(draft.entity_type, "service");
+        assert_eq!(draft.owner, "team-a");
+        assert_eq!(draft.system, "");
+        assert_eq!(draft.lifecycle, "experimental");
+        assert_eq!(draft.target, "catalog/all.yaml");
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Owner [team-a]: "));
+        assert!(output.contains("System [none]: "));
+    }
+}
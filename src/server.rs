@@ -0,0 +1,765 @@
+//! A local stand-in for the Backstage catalog REST API (`bsv serve`).
+//!
+//! [`Catalog`] holds the loaded entities in their processed shape (with
+//! computed `relations`) and [`Catalog::handle`] answers the read endpoints a
+//! Backstage frontend or script uses, mounted under `/api/catalog`:
+//!
+//! - `GET /entities` — all entities, with `filter`, `fields`, `limit`, `offset`
+//! - `GET /entities/by-name/:kind/:namespace/:name`
+//! - `GET /entities/by-query` — paged `{items, totalItems, pageInfo}`
+//! - `GET /entity-facets` — value counts per `facet`
+//!
+//! Filters follow Backstage: conditions in one `filter` parameter are
+//! comma-separated and all must hold; repeated `filter` parameters are
+//! alternatives. A condition is `key=value` or just `key` (the field exists),
+//! matched case-insensitively against the entity's flattened fields,
+//! including `relations.<type>`. [`serve`] runs the HTTP loop and reloads the
+//! catalog when files change.
+
+use crate::entity::EntityWithSource;
+use crate::parser::load_catalog;
+use crate::watcher::{CatalogWatcher, RELOAD_DEBOUNCE};
+use anyhow::{anyhow, Result};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Path prefix all endpoints are served under, as in a Backstage backend.
+pub const API_PREFIX: &str = "/api/catalog";
+
+/// Default port, matching the Backstage backend.
+pub const DEFAULT_PORT: u16 = 7007;
+
+/// Annotation Backstage sets to the location an entity was read from.
+const MANAGED_BY_LOCATION: &str = "backstage.io/managed-by-location";
+
+/// Page size for `by-query` when the request gives no `limit`.
+const DEFAULT_PAGE_SIZE: usize = 20;
+
+/// A JSON response: status code and body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    /// An error in Backstage's `{error, request, response}` envelope.
+    fn error(status: u16, name: &str, message: String, url: &str) -> Self {
+        Self {
+            status,
+            body: json!({
+                "error": { "name": name, "message": message },
+                "request": { "method": "GET", "url": url },
+                "response": { "statusCode": status },
+            }),
+        }
+    }
+}
+
+/// One entity in processed form, with its flattened search fields.
+struct Processed {
+    json: Value,
+    search: Vec<(String, String)>,
+}
+
+/// The served catalog.
+pub struct Catalog {
+    entities: Vec<Processed>,
+}
+
+impl Catalog {
+    /// Process loaded entities into the shape Backstage serves them in. Each
+    /// entity's `relations` are served as attached by the loader (see
+    /// [`crate::relations::attach_relations`]).
+    pub fn new(entities: &[EntityWithSource]) -> Self {
+        let entities = entities
+            .iter()
+            .map(|ews| {
+                let json = processed_entity(ews);
+                let mut search = Vec::new();
+                flatten(&json, String::new(), &mut search);
+                Processed { json, search }
+            })
+            .collect();
+        Self { entities }
+    }
+
+    /// Load and process the catalog at `root`.
+    pub fn load(root: &Path) -> Result<Self> {
+        let (entities, _warnings) = load_catalog(root)?;
+        Ok(Self::new(&entities))
+    }
+
+    /// Number of entities served.
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Whether the catalog is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Answer a `GET` for `url` (path plus query string).
+    pub fn handle(&self, url: &str) -> Response {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let params = parse_query(query);
+        let Some(route) = path.strip_prefix(API_PREFIX) else {
+            return Response::error(404, "NotFoundError", format!("Not found: {path}"), url);
+        };
+        let segments: Vec<String> = route
+            .trim_matches('/')
+            .split('/')
+            .map(percent_decode)
+            .collect();
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+        let result = match segments.as_slice() {
+            ["entities"] => self.list(&params),
+            ["entities", "by-query"] => self.query(&params),
+            ["entities", "by-name", kind, namespace, name] => {
+                return self.by_name(kind, namespace, name, url)
+            }
+            ["entity-facets"] => self.facets(&params),
+            _ => return Response::error(404, "NotFoundError", format!("Not found: {path}"), url),
+        };
+        result.unwrap_or_else(|e| Response::error(400, "InputError", e.to_string(), url))
+    }
+
+    /// `GET /entities`: matching entities in catalog order.
+    fn list(&self, params: &Params) -> Result<Response> {
+        let filter = Filter::parse(params);
+        let offset = number_param(params, "offset")?.unwrap_or(0);
+        let limit = number_param(params, "limit")?.unwrap_or(usize::MAX);
+        let fields = fields_param(params);
+        let items: Vec<Value> = self
+            .entities
+            .iter()
+            .filter(|e| filter.matches(e))
+            .skip(offset)
+            .take(limit)
+            .map(|e| select_fields(&e.json, &fields))
+            .collect();
+        Ok(Response::ok(Value::Array(items)))
+    }
+
+    /// `GET /entities/by-query`: a page of matching entities plus a cursor.
+    ///
+    /// Cursors are opaque to clients; here they carry the next offset.
+    fn query(&self, params: &Params) -> Result<Response> {
+        let filter = Filter::parse(params);
+        let term = first_param(params, "fullTextFilterTerm")
+            .map(str::to_lowercase)
+            .unwrap_or_default();
+        let text_fields: Vec<String> = first_param(params, "fullTextFilterFields").map_or_else(
+            || vec!["metadata.name".to_string(), "metadata.title".to_string()],
+            |f| f.split(',').map(|s| s.trim().to_lowercase()).collect(),
+        );
+        let mut matched: Vec<&Processed> = self
+            .entities
+            .iter()
+            .filter(|e| filter.matches(e))
+            .filter(|e| {
+                term.is_empty()
+                    || e.search
+                        .iter()
+                        .any(|(k, v)| text_fields.contains(k) && v.contains(&term))
+            })
+            .collect();
+
+        let order = params
+            .iter()
+            .filter(|(k, _)| k == "orderField")
+            .map(|(_, v)| match v.split_once(',') {
+                Some((field, dir)) => (field.to_lowercase(), dir.eq_ignore_ascii_case("desc")),
+                None => (v.to_lowercase(), false),
+            })
+            .collect::<Vec<_>>();
+        matched.sort_by(|a, b| {
+            for (field, desc) in &order {
+                let ordering = search_value(a, field).cmp(&search_value(b, field));
+                let ordering = if *desc { ordering.reverse() } else { ordering };
+                if ordering.is_ne() {
+                    return ordering;
+                }
+            }
+            entity_ref(&a.json).cmp(&entity_ref(&b.json))
+        });
+
+        let offset = match first_param(params, "cursor") {
+            Some(cursor) => cursor
+                .parse()
+                .map_err(|_| anyhow!("Malformed cursor: {cursor}"))?,
+            None => number_param(params, "offset")?.unwrap_or(0),
+        };
+        let limit = number_param(params, "limit")?.unwrap_or(DEFAULT_PAGE_SIZE);
+        let fields = fields_param(params);
+        let total = matched.len();
+        let items: Vec<Value> = matched
+            .iter()
+            .skip(offset)
+            .take(limit)
+            .map(|e| select_fields(&e.json, &fields))
+            .collect();
+
+        let mut page_info = Map::new();
+        let next = offset.saturating_add(limit);
+        if next < total {
+            page_info.insert("nextCursor".into(), json!(next.to_string()));
+        }
+        if offset > 0 {
+            page_info.insert(
+                "prevCursor".into(),
+                json!(offset.saturating_sub(limit).to_string()),
+            );
+        }
+        Ok(Response::ok(json!({
+            "items": items,
+            "totalItems": total,
+            "pageInfo": page_info,
+        })))
+    }
+
+    /// `GET /entities/by-name/:kind/:namespace/:name` (case-insensitive).
+    fn by_name(&self, kind: &str, namespace: &str, name: &str, url: &str) -> Response {
+        let wanted = format!("{kind}:{namespace}/{name}").to_lowercase();
+        self.entities
+            .iter()
+            .find(|e| entity_ref(&e.json) == wanted)
+            .map_or_else(
+                || {
+                    Response::error(
+                        404,
+                        "NotFoundError",
+                        format!(
+                            "No entity named '{name}' found, with kind '{kind}' in namespace '{namespace}'"
+                        ),
+                        url,
+                    )
+                },
+                |e| Response::ok(e.json.clone()),
+            )
+    }
+
+    /// `GET /entity-facets`: for each `facet`, how many matching entities
+    /// carry each value, most common first.
+    fn facets(&self, params: &Params) -> Result<Response> {
+        let filter = Filter::parse(params);
+        let mut facets = Map::new();
+        for (_, facet) in params.iter().filter(|(k, _)| k == "facet") {
+            let key = facet.to_lowercase();
+            let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+            for e in self.entities.iter().filter(|e| filter.matches(e)) {
+                let mut values: Vec<String> = raw_values(&e.json, &key);
+                values.sort();
+                values.dedup();
+                for value in values {
+                    *counts.entry(value).or_default() += 1;
+                }
+            }
+            let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+            counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            let values: Vec<Value> = counts
+                .into_iter()
+                .map(|(value, count)| json!({ "value": value, "count": count }))
+                .collect();
+            facets.insert(facet.clone(), Value::Array(values));
+        }
+        Ok(Response::ok(json!({ "facets": facets })))
+    }
+}
+
+/// Decoded query parameters in request order (keys may repeat).
+type Params = Vec<(String, String)>;
+
+fn first_param<'a>(params: &'a Params, key: &str) -> Option<&'a str> {
+    params
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+}
+
+fn number_param(params: &Params, key: &str) -> Result<Option<usize>> {
+    first_param(params, key)
+        .map(|v| {
+            v.parse()
+                .map_err(|_| anyhow!("Invalid {key}, not a number: {v}"))
+        })
+        .transpose()
+}
+
+/// The `fields` selections, from any number of comma-separated parameters.
+fn fields_param(params: &Params) -> Vec<String> {
+    params
+        .iter()
+        .filter(|(k, _)| k == "fields")
+        .flat_map(|(_, v)| v.split(','))
+        .map(|f| f.trim().to_string())
+        .filter(|f| !f.is_empty())
+        .collect()
+}
+
+/// Split a query string into decoded key/value pairs.
+fn parse_query(query: &str) -> Params {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(k), percent_decode(v))
+        })
+        .collect()
+}
+
+/// Decode `%XX` escapes and `+` (as a space) in a URL component.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    Some(b) => {
+                        out.push(b);
+                        i += 2;
+                    }
+                    None => out.push(b'%'),
+                }
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// A parsed `filter`: alternatives, each a list of `(key, optional value)`
+/// conditions that must all hold. No filter matches everything.
+struct Filter(Vec<Vec<(String, Option<String>)>>);
+
+impl Filter {
+    fn parse(params: &Params) -> Self {
+        Self(
+            params
+                .iter()
+                .filter(|(k, _)| k == "filter")
+                .map(|(_, v)| {
+                    v.split(',')
+                        .map(str::trim)
+                        .filter(|c| !c.is_empty())
+                        .map(|c| match c.split_once('=') {
+                            Some((k, v)) => {
+                                (k.trim().to_lowercase(), Some(v.trim().to_lowercase()))
+                            }
+                            None => (c.to_lowercase(), None),
+                        })
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    fn matches(&self, entity: &Processed) -> bool {
+        self.0.is_empty()
+            || self.0.iter().any(|conditions| {
+                conditions.iter().all(|(key, value)| {
+                    entity
+                        .search
+                        .iter()
+                        .any(|(k, v)| k == key && value.as_ref().is_none_or(|value| v == value))
+                })
+            })
+    }
+}
+
+/// Flatten an entity into lowercase `(dotted.key, value)` pairs the way
+/// Backstage's search table does: arrays repeat their key, and each relation
+/// becomes `relations.<type>` = target ref.
+fn flatten(value: &Value, key: String, out: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map {
+                if key.is_empty() && k == "relations" {
+                    for relation in v.as_array().into_iter().flatten() {
+                        if let (Some(t), Some(target)) = (
+                            relation.get("type").and_then(Value::as_str),
+                            relation.get("targetRef").and_then(Value::as_str),
+                        ) {
+                            out.push((
+                                format!("relations.{}", t.to_lowercase()),
+                                target.to_lowercase(),
+                            ));
+                        }
+                    }
+                    continue;
+                }
+                let child = if key.is_empty() {
+                    k.to_lowercase()
+                } else {
+                    format!("{key}.{}", k.to_lowercase())
+                };
+                flatten(v, child, out);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                flatten(item, key.clone(), out);
+            }
+        }
+        Value::String(s) => out.push((key, s.to_lowercase())),
+        Value::Bool(_) | Value::Number(_) => out.push((key, value.to_string())),
+        Value::Null => {}
+    }
+}
+
+/// The first flattened value of `key`, for ordering.
+fn search_value<'a>(entity: &'a Processed, key: &str) -> Option<&'a str> {
+    entity
+        .search
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+}
+
+/// Original-case scalar values at a dotted `key` (case-insensitive), for facets.
+fn raw_values(json: &Value, key: &str) -> Vec<String> {
+    let mut flat = Vec::new();
+    flatten_raw(json, String::new(), &mut flat);
+    flat.into_iter()
+        .filter(|(k, _)| k == key)
+        .map(|(_, v)| v)
+        .collect()
+}
+
+fn flatten_raw(value: &Value, key: String, out: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map {
+                let child = if key.is_empty() {
+                    k.to_lowercase()
+                } else {
+                    format!("{key}.{}", k.to_lowercase())
+                };
+                flatten_raw(v, child, out);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                flatten_raw(item, key.clone(), out);
+            }
+        }
+        Value::String(s) => out.push((key, s.clone())),
+        Value::Bool(_) | Value::Number(_) => out.push((key, value.to_string())),
+        Value::Null => {}
+    }
+}
+
+/// `kind:namespace/name` of a processed entity, lowercased for
+/// case-insensitive lookup and ordering.
+fn entity_ref(json: &Value) -> String {
+    let kind = json["kind"].as_str().unwrap_or_default();
+    let namespace = json["metadata"]["namespace"].as_str().unwrap_or("default");
+    let name = json["metadata"]["name"].as_str().unwrap_or_default();
+    format!("{kind}:{namespace}/{name}").to_lowercase()
+}
+
+/// Keep only the dotted `fields` of an entity (all of it when none are given).
+fn select_fields(json: &Value, fields: &[String]) -> Value {
+    if fields.is_empty() {
+        return json.clone();
+    }
+    let mut out = Value::Object(Map::new());
+    for field in fields {
+        let path: Vec<&str> = field.split('.').collect();
+        let mut source = json;
+        let mut found = true;
+        for part in &path {
+            match source.get(part) {
+                Some(v) => source = v,
+                None => {
+                    found = false;
+                    break;
+                }
+            }
+        }
+        if !found {
+            continue;
+        }
+        let mut target = &mut out;
+        for part in &path[..path.len() - 1] {
+            target = target
+                .as_object_mut()
+                .expect("intermediate fields are objects")
+                .entry(part.to_string())
+                .or_insert_with(|| Value::Object(Map::new()));
+        }
+        if let Some(obj) = target.as_object_mut() {
+            obj.insert(path[path.len() - 1].to_string(), source.clone());
+        }
+    }
+    out
+}
+
+/// An entity as the Backstage catalog serves it: namespace filled in, the
+/// source recorded in `backstage.io/managed-by-location`, and `relations`.
+fn processed_entity(ews: &EntityWithSource) -> Value {
+    let mut json = serde_json::to_value(&ews.entity).unwrap_or(Value::Null);
+    if let Some(metadata) = json.get_mut("metadata").and_then(Value::as_object_mut) {
+        metadata
+            .entry("namespace")
+            .or_insert_with(|| json!("default"));
+        let annotations = metadata
+            .entry("annotations")
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Some(annotations) = annotations.as_object_mut() {
+            annotations
+                .entry(MANAGED_BY_LOCATION)
+                .or_insert_with(|| json!(format!("file:{}", ews.source_file.display())));
+        }
+    }
+    if let Some(obj) = json.as_object_mut() {
+        obj.insert("relations".into(), json!(ews.relations));
+    }
+    json
+}
+
+/// Serve the catalog at `root` on `127.0.0.1:port` until the process exits,
+/// reloading it whenever catalog files change.
+pub fn serve(root: &Path, port: u16) -> Result<()> {
+    let server = tiny_http::Server::http(("127.0.0.1", port))
+        .map_err(|e| anyhow!("failed to listen on port {port}: {e}"))?;
+    let mut catalog = Catalog::load(root)?;
+    eprintln!(
+        "Serving {} entities from {} at http://{}{API_PREFIX}",
+        catalog.len(),
+        root.display(),
+        server.server_addr()
+    );
+    let watcher = CatalogWatcher::new(root).ok();
+    let mut pending_reload: Option<Instant> = None;
+
+    loop {
+        if watcher.as_ref().is_some_and(|w| !w.drain().is_empty()) {
+            pending_reload = Some(Instant::now());
+        }
+        if pending_reload.is_some_and(|since| since.elapsed() >= RELOAD_DEBOUNCE) {
+            pending_reload = None;
+            match Catalog::load(root) {
+                Ok(reloaded) => {
+                    catalog = reloaded;
+                    eprintln!("Reloaded {} entities", catalog.len());
+                }
+                Err(e) => {
+                    eprintln!("error: reload failed, still serving the previous catalog: {e:#}")
+                }
+            }
+        }
+        let Some(request) = server.recv_timeout(Duration::from_millis(250))? else {
+            continue;
+        };
+        let response = if *request.method() == tiny_http::Method::Get {
+            catalog.handle(request.url())
+        } else {
+            let mut response = Response::error(
+                405,
+                "NotAllowedError",
+                format!(
+                    "{} is not supported; this catalog is read-only",
+                    request.method()
+                ),
+                request.url(),
+            );
+            response.body["request"]["method"] = json!(request.method().as_str());
+            response
+        };
+        let body = serde_json::to_string(&response.body)?;
+        let reply = tiny_http::Response::from_string(body)
+            .with_status_code(response.status)
+            .with_header(header("Content-Type", "application/json; charset=utf-8"))
+            .with_header(header("Access-Control-Allow-Origin", "*"));
+        // A client that hung up is not a server error.
+        let _ = request.respond(reply);
+    }
+}
+
+fn header(name: &str, value: &str) -> tiny_http::Header {
+    tiny_http::Header::from_bytes(name.as_bytes(), value.as_bytes())
+        .expect("static header is valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::Entity;
+    use crate::relations::attach_relations;
+    use serde::Deserialize;
+
+    const CATALOG: &str = "\
+apiVersion: backstage.io/v1alpha1
+kind: Group
+metadata:
+  name: payments
+spec:
+  type: team
+  children: []
+---
+apiVersion: backstage.io/v1alpha1
+kind: Component
+metadata:
+  name: checkout
+  tags: [java]
+spec:
+  type: service
+  lifecycle: production
+  owner: payments
+---
+apiVersion: backstage.io/v1alpha1
+kind: Component
+metadata:
+  name: storefront
+  title: Store Front
+spec:
+  type: website
+  lifecycle: experimental
+  owner: payments
+  dependsOn: [checkout]
+";
+
+    fn catalog() -> Catalog {
+        let mut entities: Vec<EntityWithSource> = serde_yaml::Deserializer::from_str(CATALOG)
+            .map(|doc| {
+                EntityWithSource::new(
+                    Entity::deserialize(doc).unwrap(),
+                    "catalog-info.yaml".into(),
+                )
+            })
+            .collect();
+        attach_relations(&mut entities);
+        Catalog::new(&entities)
+    }
+
+    fn names(body: &Value) -> Vec<&str> {
+        body.as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["metadata"]["name"].as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn lists_and_filters_entities() {
+        let catalog = catalog();
+        let all = catalog.handle("/api/catalog/entities");
+        assert_eq!(all.status, 200);
+        assert_eq!(names(&all.body), vec!["payments", "checkout", "storefront"]);
+
+        let services =
+            catalog.handle("/api/catalog/entities?filter=kind=component,spec.type=service");
+        assert_eq!(names(&services.body), vec!["checkout"]);
+
+        // Repeated filters are alternatives; a bare key tests existence.
+        let either =
+            catalog.handle("/api/catalog/entities?filter=kind=group&filter=metadata.title");
+        assert_eq!(names(&either.body), vec!["payments", "storefront"]);
+
+        let related = catalog.handle(
+            "/api/catalog/entities?filter=relations.dependencyOf=component:default/storefront",
+        );
+        assert_eq!(names(&related.body), vec!["checkout"]);
+
+        let fields =
+            catalog.handle("/api/catalog/entities?filter=kind=group&fields=kind,metadata.name");
+        assert_eq!(
+            fields.body,
+            json!([{ "kind": "Group", "metadata": { "name": "payments" } }])
+        );
+    }
+
+    #[test]
+    fn fetches_by_name_with_relations() {
+        let catalog = catalog();
+        let found = catalog.handle("/api/catalog/entities/by-name/Component/default/checkout");
+        assert_eq!(found.status, 200);
+        assert_eq!(found.body["metadata"]["namespace"], "default");
+        assert_eq!(
+            found.body["metadata"]["annotations"][MANAGED_BY_LOCATION],
+            "file:catalog-info.yaml"
+        );
+        assert_eq!(
+            found.body["relations"],
+            json!([
+                { "type": "dependencyOf", "targetRef": "component:default/storefront" },
+                { "type": "ownedBy", "targetRef": "group:default/payments" },
+            ])
+        );
+
+        let missing = catalog.handle("/api/catalog/entities/by-name/component/default/nope");
+        assert_eq!(missing.status, 404);
+        assert_eq!(missing.body["error"]["name"], "NotFoundError");
+    }
+
+    #[test]
+    fn queries_page_with_cursors() {
+        let catalog = catalog();
+        let first = catalog.handle(
+            "/api/catalog/entities/by-query?filter=kind=component&limit=1&orderField=metadata.name,desc",
+        );
+        assert_eq!(first.body["totalItems"], 2);
+        assert_eq!(first.body["items"][0]["metadata"]["name"], "storefront");
+        let cursor = first.body["pageInfo"]["nextCursor"].as_str().unwrap();
+
+        let second = catalog.handle(&format!(
+            "/api/catalog/entities/by-query?filter=kind=component&limit=1&orderField=metadata.name,desc&cursor={cursor}"
+        ));
+        assert_eq!(second.body["items"][0]["metadata"]["name"], "checkout");
+        assert!(second.body["pageInfo"].get("nextCursor").is_none());
+
+        let text = catalog.handle("/api/catalog/entities/by-query?fullTextFilterTerm=store%20f");
+        assert_eq!(text.body["totalItems"], 1);
+
+        let bad = catalog.handle("/api/catalog/entities/by-query?limit=lots");
+        assert_eq!(bad.status, 400);
+
+        // Huge pages don't overflow into a bogus cursor.
+        let huge =
+            catalog.handle("/api/catalog/entities/by-query?limit=18446744073709551615&cursor=1");
+        assert_eq!(huge.status, 200);
+        assert_eq!(huge.body["items"].as_array().unwrap().len(), 2);
+        assert!(huge.body["pageInfo"].get("nextCursor").is_none());
+    }
+
+    #[test]
+    fn counts_facets() {
+        let catalog = catalog();
+        let facets = catalog.handle("/api/catalog/entity-facets?facet=kind&facet=spec.lifecycle");
+        assert_eq!(
+            facets.body["facets"]["kind"],
+            json!([
+                { "value": "Component", "count": 2 },
+                { "value": "Group", "count": 1 },
+            ])
+        );
+        assert_eq!(
+            facets.body["facets"]["spec.lifecycle"],
+            json!([
+                { "value": "experimental", "count": 1 },
+                { "value": "production", "count": 1 },
+            ])
+        );
+    }
+
+    #[test]
+    fn unknown_paths_are_not_found() {
+        assert_eq!(catalog().handle("/api/catalog/locations").status, 404);
+        assert_eq!(catalog().handle("/").status, 404);
+    }
+}
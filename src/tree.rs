@@ -34,7 +34,7 @@
 //!     EntityWithSource::new(domain, PathBuf::from("catalog.yaml")),
 //! ];
 //!
-//! let tree = EntityTree::build(&entities);
+//! let tree = EntityTree::build(entities);
 //! let mut state = TreeState::new();
 //!
 //! // Initially only root categories visible
@@ -72,7 +72,7 @@
 //! #     spec: serde_yaml::Value::Null,
 //! # };
 //! # let entities = vec![EntityWithSource::new(component, PathBuf::from("catalog.yaml"))];
-//! let tree = EntityTree::build(&entities);
+//! let tree = EntityTree::build(entities);
 //! let mut state = TreeState::new();
 //! state.expand_all(&tree);
 //! let visible = tree.visible_nodes(&state);
@@ -89,8 +89,8 @@
 //! # use bsv::tree::{EntityTree, TreeState};
 //! # use std::path::PathBuf;
 //! # use std::collections::HashMap;
-//! # let entities = vec![];
-//! let tree = EntityTree::build(&entities);
+//! # let entities: Vec<EntityWithSource> = vec![];
+//! let tree = EntityTree::build(entities);
 //! let mut state = TreeState::new();
 //!
 //! // Toggle expansion state
@@ -110,7 +110,8 @@
 //! - [`TreeNode`] - Single node in the tree (category or entity)
 //! - [`TreeState`] - Tracks which nodes are expanded and selected
 
-use crate::entity::{EntityKind, EntityRef, EntityWithSource};
+use crate::entity::{EntityKind, EntityRef, EntityStore, EntityWithSource, SharedEntity};
+use crate::query::{Query, Ranking};
 use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone)]
@@ -118,17 +119,25 @@ pub struct TreeNode {
     pub id: usize,
     pub label: String,
     pub depth: usize,
-    pub entity: Option<EntityWithSource>,
+    /// The entity shown, shared with the app's store; `None` for categories.
+    pub entity: Option<SharedEntity>,
     pub children: Vec<usize>,
     pub is_category: bool,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct EntityTree {
     pub nodes: Vec<TreeNode>,
     pub root_children: Vec<usize>,
 }
 
+/// An entity node ranked by a search, with the label characters that matched.
+#[derive(Debug)]
+pub struct SearchHit<'a> {
+    pub node: &'a TreeNode,
+    pub ranking: Ranking,
+}
+
 /// A visible tree node paired with its rendered branch-connector prefix.
 #[derive(Debug)]
 pub struct VisibleRow<'a> {
@@ -183,19 +192,37 @@ impl EntityTree {
     /// Organizes entities as: Domain → System → Components/APIs/Resources.
     /// Entities without a system go under "Other Entities".
     /// Systems without a domain go under "Systems".
-    pub fn build(entities: &[EntityWithSource]) -> Self {
+    /// Users are nested under each Group they belong to (via `spec.memberOf` or
+    /// the group's `spec.members`); users without a group go under "Other Entities".
+    pub fn build(entities: impl Into<EntityStore>) -> Self {
+        Self::build_matching(entities, |_| true)
+    }
+
+    /// Build a tree as [`EntityTree::build`] does, from only the entities
+    /// `keep` accepts. Nodes refer into the store rather than copying entities.
+    pub fn build_matching(
+        entities: impl Into<EntityStore>,
+        keep: impl Fn(&EntityWithSource) -> bool,
+    ) -> Self {
+        let store = entities.into();
+        let entities: Vec<SharedEntity> = (0..store.len())
+            .filter(|&i| keep(&store[i]))
+            .map(|i| SharedEntity::new(&store, i))
+            .collect();
+
         let mut nodes: Vec<TreeNode> = Vec::new();
         let mut root_children: Vec<usize> = Vec::new();
 
         // Group entities by kind, then by domain/system relationships
-        let mut domains: HashMap<String, Vec<&EntityWithSource>> = HashMap::new();
-        let mut systems: HashMap<String, Vec<&EntityWithSource>> = HashMap::new();
+        let mut domains: HashMap<String, Vec<&SharedEntity>> = HashMap::new();
+        let mut systems: HashMap<String, Vec<&SharedEntity>> = HashMap::new();
         let mut system_to_domain: HashMap<String, String> = HashMap::new();
-        let mut components_by_system: HashMap<String, Vec<&EntityWithSource>> = HashMap::new();
-        let mut ungrouped: Vec<&EntityWithSource> = Vec::new();
+        let mut components_by_system: HashMap<String, Vec<&SharedEntity>> = HashMap::new();
+        let mut users: Vec<&SharedEntity> = Vec::new();
+        let mut ungrouped: Vec<&SharedEntity> = Vec::new();
 
         // First pass: collect domains and systems
-        for ews in entities {
+        for ews in &entities {
             match ews.entity.kind {
                 EntityKind::Domain => {
                     domains
@@ -217,7 +244,7 @@ impl EntityTree {
         }
 
         // Second pass: group components/APIs/resources by system
-        for ews in entities {
+        for ews in &entities {
             match ews.entity.kind {
                 EntityKind::Domain | EntityKind::System => {}
                 EntityKind::Component | EntityKind::Api | EntityKind::Resource => {
@@ -229,6 +256,8 @@ impl EntityTree {
                 }
                 // Groups are organised into their own parent/child hierarchy below.
                 EntityKind::Group => {}
+                // Users are placed under the groups they belong to, if any.
+                EntityKind::User => users.push(ews),
                 _ => {
                     ungrouped.push(ews);
                 }
@@ -369,7 +398,7 @@ impl EntityTree {
         }
 
         // Groups: nest child groups under their parents (spec.parent / spec.children).
-        let group_map: HashMap<String, EntityWithSource> = entities
+        let group_map: HashMap<String, SharedEntity> = entities
             .iter()
             .filter(|ews| ews.entity.kind == EntityKind::Group)
             .map(|ews| (ews.entity.metadata.name.clone(), ews.clone()))
@@ -408,6 +437,42 @@ impl EntityTree {
                 kids.sort();
             }
 
+            // Resolve membership from both sides: a user's spec.memberOf and a
+            // group's spec.members. A user in several groups appears under each.
+            let mut members_of: HashMap<String, Vec<&SharedEntity>> = HashMap::new();
+            let mut placed: HashSet<String> = HashSet::new();
+            for user in &users {
+                for group in user.entity.member_of() {
+                    let group_name = EntityRef::parse(&group, "group").name;
+                    if group_map.contains_key(&group_name) {
+                        members_of.entry(group_name).or_default().push(*user);
+                    }
+                }
+            }
+            for (group_name, ews) in &group_map {
+                for member in ews.entity.members() {
+                    let member_name = EntityRef::parse(&member, "user").name;
+                    if let Some(user) = users.iter().find(|u| u.entity.metadata.name == member_name)
+                    {
+                        members_of
+                            .entry(group_name.clone())
+                            .or_default()
+                            .push(*user);
+                    }
+                }
+            }
+            for members in members_of.values_mut() {
+                members.sort_by_key(|u| (u.entity.display_name(), u.entity.ref_key()));
+                members.dedup_by_key(|u| u.entity.ref_key());
+                placed.extend(members.iter().map(|u| u.entity.ref_key()));
+            }
+            ungrouped.extend(
+                users
+                    .iter()
+                    .copied()
+                    .filter(|u| !placed.contains(&u.entity.ref_key())),
+            );
+
             // Root groups have no resolved parent within the catalog.
             let mut roots: Vec<String> = group_map
                 .keys()
@@ -433,6 +498,7 @@ impl EntityTree {
                     &mut nodes,
                     &group_map,
                     &children_of,
+                    &members_of,
                     root_name,
                     1,
                     &mut visited,
@@ -440,6 +506,8 @@ impl EntityTree {
                     nodes[group_cat_id].children.push(child_id);
                 }
             }
+        } else {
+            ungrouped.extend(users);
         }
 
         // Ungrouped entities
@@ -477,11 +545,14 @@ impl EntityTree {
 
     /// Recursively build a group node and its descendants, returning the new node id.
     ///
-    /// `visited` guards against cycles in malformed parent/child references.
+    /// Child groups come first, followed by the group's member users. The label
+    /// carries the direct member count. `visited` guards against cycles in
+    /// malformed parent/child references.
     fn build_group_subtree(
         nodes: &mut Vec<TreeNode>,
-        group_map: &HashMap<String, EntityWithSource>,
+        group_map: &HashMap<String, SharedEntity>,
         children_of: &HashMap<String, Vec<String>>,
+        members_of: &HashMap<String, Vec<&SharedEntity>>,
         name: &str,
         depth: usize,
         visited: &mut HashSet<String>,
@@ -490,11 +561,22 @@ impl EntityTree {
             return None;
         }
         let ews = group_map.get(name)?;
+        let members = members_of.get(name).map_or(&[][..], Vec::as_slice);
+
+        let mut label = format!("{}: {}", EntityKind::Group, ews.entity.display_name());
+        if !members.is_empty() {
+            let noun = if members.len() == 1 {
+                "member"
+            } else {
+                "members"
+            };
+            label.push_str(&format!(" ({} {noun})", members.len()));
+        }
 
         let id = nodes.len();
         nodes.push(TreeNode {
             id,
-            label: format!("{}: {}", EntityKind::Group, ews.entity.display_name()),
+            label,
             depth,
             entity: Some(ews.clone()),
             children: Vec::new(),
@@ -507,6 +589,7 @@ impl EntityTree {
                     nodes,
                     group_map,
                     children_of,
+                    members_of,
                     kid,
                     depth + 1,
                     visited,
@@ -516,6 +599,19 @@ impl EntityTree {
             }
         }
 
+        for user in members {
+            let user_id = nodes.len();
+            nodes.push(TreeNode {
+                id: user_id,
+                label: format!("{}: {}", EntityKind::User, user.entity.display_name()),
+                depth: depth + 1,
+                entity: Some((*user).clone()),
+                children: Vec::new(),
+                is_category: false,
+            });
+            nodes[id].children.push(user_id);
+        }
+
         Some(id)
     }
 
@@ -625,23 +721,51 @@ impl EntityTree {
         }
     }
 
-    /// Filter visible nodes by a search query.
+    /// Filter visible nodes by a search query (see [`crate::query`]).
     ///
-    /// A bare query matches across the label, name, title, description, kind,
-    /// owner, and tags. A `field:value` query (e.g. `owner:team-a`, `tag:web`,
-    /// `kind:component`) restricts the match to that field of entity nodes.
+    /// A bare word matches across the label, name, title, description, kind,
+    /// owner, and tags; `field:value` terms (e.g. `owner:team-a`, `tag:web`,
+    /// `kind:component`) restrict the match to entity nodes. Input that does
+    /// not parse, such as an unclosed quote mid-typing, is matched as plain text.
     pub fn filter_by_search<'a>(nodes: Vec<&'a TreeNode>, search_query: &str) -> Vec<&'a TreeNode> {
-        let query = search_query.trim().to_lowercase();
-        if query.is_empty() {
+        if search_query.trim().is_empty() {
             return nodes;
         }
+        let query = Query::parse(search_query).unwrap_or_else(|_| Query::text(search_query));
         nodes
             .into_iter()
-            .filter(|n| node_matches(n, &query))
+            .filter(|n| query.matches_node(&n.label, n.entity.as_deref()))
             .collect()
     }
 }
 
+impl EntityTree {
+    /// Rank every entity in the tree, expanded or not, against a search query:
+    /// best match first, then by label. An entity shown in several places (a
+    /// user in several groups) is listed once, at its first node.
+    pub fn search(&self, search_query: &str) -> Vec<SearchHit<'_>> {
+        let query = Query::parse(search_query).unwrap_or_else(|_| Query::text(search_query));
+        let mut seen = HashSet::new();
+        let mut hits: Vec<SearchHit> = self
+            .nodes
+            .iter()
+            .filter_map(|node| {
+                let ews = node.entity.as_deref()?;
+                let ranking = query.rank_node(&node.label, Some(ews))?;
+                seen.insert(ews.entity.ref_key())
+                    .then_some(SearchHit { node, ranking })
+            })
+            .collect();
+        hits.sort_by(|a, b| {
+            b.ranking
+                .score
+                .cmp(&a.ranking.score)
+                .then_with(|| a.node.label.cmp(&b.node.label))
+        });
+        hits
+    }
+}
+
 /// Keys of a string-keyed map, sorted, for deterministic iteration.
 fn sorted_keys<V>(map: &HashMap<String, V>) -> Vec<&String> {
     let mut keys: Vec<&String> = map.keys().collect();
@@ -650,8 +774,8 @@ fn sorted_keys<V>(map: &HashMap<String, V>) -> Vec<&String> {
 }
 
 /// Entities sorted by kind then display name, for stable tree ordering.
-fn sorted_entities<'a>(entities: &[&'a EntityWithSource]) -> Vec<&'a EntityWithSource> {
-    let mut sorted: Vec<&'a EntityWithSource> = entities.to_vec();
+fn sorted_entities<'a>(entities: &[&'a SharedEntity]) -> Vec<&'a SharedEntity> {
+    let mut sorted: Vec<&'a SharedEntity> = entities.to_vec();
     sorted.sort_by(|a, b| {
         a.entity
             .kind
@@ -662,86 +786,12 @@ fn sorted_entities<'a>(entities: &[&'a EntityWithSource]) -> Vec<&'a EntityWithS
     sorted
 }
 
-/// Recognized `field:` scopes for search.
-fn known_field(field: &str) -> bool {
-    matches!(
-        field,
-        "name"
-            | "title"
-            | "kind"
-            | "owner"
-            | "system"
-            | "domain"
-            | "tag"
-            | "tags"
-            | "lifecycle"
-            | "type"
-            | "namespace"
-            | "ns"
-            | "desc"
-            | "description"
-    )
-}
-
-/// Resolve a single searchable field of an entity to a string, if present.
-fn field_value(entity: &crate::entity::Entity, field: &str) -> Option<String> {
-    match field {
-        "name" => Some(entity.metadata.name.clone()),
-        "title" => entity.metadata.title.clone(),
-        "kind" => Some(entity.kind.to_string()),
-        "owner" => entity.owner(),
-        "system" => entity.system(),
-        "domain" => entity.domain(),
-        "tag" | "tags" => Some(entity.metadata.tags.join(" ")),
-        "lifecycle" => entity.lifecycle(),
-        "type" => entity.entity_type(),
-        "namespace" | "ns" => entity.metadata.namespace.clone(),
-        "desc" | "description" => entity.metadata.description.clone(),
-        _ => None,
-    }
-}
-
-/// Whether a node matches a (already-lowercased, non-empty) query.
-fn node_matches(node: &TreeNode, query: &str) -> bool {
-    // Field-scoped query: `field:value` restricted to entity nodes.
-    if let Some((field, value)) = query.split_once(':') {
-        if known_field(field) {
-            let value = value.trim();
-            return match &node.entity {
-                Some(ews) => field_value(&ews.entity, field)
-                    .is_some_and(|v| v.to_lowercase().contains(value)),
-                None => false,
-            };
-        }
-    }
-
-    // Free-text query: match the label or any common entity field.
-    if node.label.to_lowercase().contains(query) {
-        return true;
-    }
-    let Some(ews) = &node.entity else {
-        return false;
-    };
-    let e = &ews.entity;
-    let haystacks = [
-        Some(e.metadata.name.clone()),
-        e.metadata.title.clone(),
-        e.metadata.description.clone(),
-        Some(e.kind.to_string()),
-        e.owner(),
-        Some(e.metadata.tags.join(" ")),
-    ];
-    haystacks
-        .iter()
-        .flatten()
-        .any(|h| h.to_lowercase().contains(query))
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::entity::{Entity, EntityKind, Metadata};
+    use crate::entity::{Entity, EntityKind, EntityStore, Metadata};
     use std::path::PathBuf;
+    use std::rc::Rc;
 
     fn create_test_entity(
         kind: EntityKind,
@@ -789,7 +839,10 @@ mod tests {
                 spec,
             },
             source_file: PathBuf::from("/test/catalog-info.yaml"),
+            line: 1,
             validation_errors: Vec::new(),
+            duplicates: Vec::new(),
+            relations: Vec::new(),
         }
     }
 
@@ -825,6 +878,29 @@ mod tests {
         ews
     }
 
+    #[test]
+    fn test_nodes_share_the_entity_store() {
+        let store: EntityStore = vec![
+            create_test_entity(EntityKind::System, "auth-system", None, None),
+            create_test_entity(EntityKind::Component, "login", Some("auth-system"), None),
+            create_test_entity(EntityKind::Component, "signup", Some("auth-system"), None),
+        ]
+        .into();
+
+        let tree = EntityTree::build_matching(Rc::clone(&store), |ews| {
+            ews.entity.metadata.name != "signup"
+        });
+
+        let mut indices: Vec<usize> = tree
+            .nodes
+            .iter()
+            .filter_map(|node| node.entity.as_ref().map(SharedEntity::index))
+            .collect();
+        indices.sort_unstable();
+        assert_eq!(indices, [0, 1], "signup is filtered out");
+        assert_eq!(Rc::strong_count(&store), 3, "one handle per entity node");
+    }
+
     #[test]
     fn test_tree_building_hierarchy() {
         // Create a complete hierarchy: Domain -> System -> Component
@@ -840,7 +916,7 @@ mod tests {
             create_test_entity(EntityKind::Api, "user-api", Some("auth-system"), None),
         ];
 
-        let tree = EntityTree::build(&entities);
+        let tree = EntityTree::build(entities);
 
         // Verify tree structure
         assert_eq!(tree.root_children.len(), 1, "Should have 1 root category");
@@ -898,7 +974,7 @@ mod tests {
             create_test_entity(EntityKind::Api, "user-api", None, None),
         ];
 
-        let tree = EntityTree::build(&entities);
+        let tree = EntityTree::build(entities);
         let mut state = TreeState::new();
 
         // Expand the "Other Entities" category to see the actual entities
@@ -933,6 +1009,40 @@ mod tests {
         assert_eq!(filtered.len(), 1, "Should match 'Other Entities' category");
     }
 
+    #[test]
+    fn test_search_ranks_all_entities() {
+        let entities = vec![
+            create_test_entity(EntityKind::Component, "payment-service", None, None),
+            create_test_entity(EntityKind::Component, "pmt-svc-legacy", None, None),
+            create_test_entity(EntityKind::Component, "order-service", None, None),
+        ];
+        let tree = EntityTree::build(entities);
+
+        // Nothing is expanded, yet every entity is searched.
+        let labels = |q: &str| -> Vec<String> {
+            tree.search(q)
+                .iter()
+                .map(|h| h.node.label.clone())
+                .collect()
+        };
+        assert_eq!(
+            labels("pmt-svc"),
+            vec!["Component: pmt-svc-legacy", "Component: payment-service"]
+        );
+        assert_eq!(labels("order"), vec!["Component: order-service"]);
+
+        let hits = tree.search("pmt-svc");
+        let highlighted: String = hits[1]
+            .node
+            .label
+            .chars()
+            .enumerate()
+            .filter(|(i, _)| hits[1].ranking.highlights.contains(i))
+            .map(|(_, c)| c)
+            .collect();
+        assert_eq!(highlighted, "pmt-svc");
+    }
+
     #[test]
     fn test_tree_ordering_is_sorted() {
         // Build with domains, systems and components deliberately out of order.
@@ -945,7 +1055,7 @@ mod tests {
             create_test_entity(EntityKind::Api, "api-a", Some("sys-a"), None),
         ];
 
-        let tree = EntityTree::build(&entities);
+        let tree = EntityTree::build(entities);
 
         // Domains category lists domains alphabetically.
         let domains_cat = tree
@@ -1013,7 +1123,7 @@ mod tests {
         );
         let reports_api = create_test_entity(EntityKind::Api, "reports-api", None, None);
 
-        let tree = EntityTree::build(&[billing, reports_api]);
+        let tree = EntityTree::build(vec![billing, reports_api]);
         let mut state = TreeState::new();
         state.expand_all(&tree);
         let visible = tree.visible_nodes(&state);
@@ -1052,7 +1162,7 @@ mod tests {
             ),
         ];
 
-        let tree = EntityTree::build(&entities);
+        let tree = EntityTree::build(entities);
         let mut state = TreeState::new();
 
         // Initially, only root category should be visible (not expanded)
@@ -1118,7 +1228,7 @@ mod tests {
             create_test_entity(EntityKind::Resource, "database", Some("auth-system"), None),
         ];
 
-        let tree = EntityTree::build(&entities);
+        let tree = EntityTree::build(entities);
 
         // Category should be depth 0
         let domains_node = &tree.nodes[tree.root_children[0]];
@@ -1149,7 +1259,7 @@ mod tests {
             create_test_entity(EntityKind::Location, "github-org", None, None),
         ];
 
-        let tree = EntityTree::build(&entities);
+        let tree = EntityTree::build(entities);
 
         // Groups now get their own category, so we expect "Groups" + "Other Entities".
         let categories: Vec<String> = tree
@@ -1214,7 +1324,7 @@ mod tests {
             create_test_group("team-b", Some("parent-team")),
         ];
 
-        let tree = EntityTree::build(&entities);
+        let tree = EntityTree::build(entities);
 
         // Single root category: "Groups"
         assert_eq!(tree.root_children.len(), 1);
@@ -1252,7 +1362,7 @@ mod tests {
             create_test_group("team-a", None),
         ];
 
-        let tree = EntityTree::build(&entities);
+        let tree = EntityTree::build(entities);
 
         let groups_node = &tree.nodes[tree.root_children[0]];
         assert_eq!(groups_node.label, "Groups");
@@ -1267,6 +1377,146 @@ mod tests {
         assert!(tree.nodes[parent_node.children[0]].label.contains("team-a"));
     }
 
+    fn create_test_user(name: &str, member_of: &[&str]) -> EntityWithSource {
+        let mut map = serde_yaml::Mapping::new();
+        let seq: Vec<serde_yaml::Value> = member_of
+            .iter()
+            .map(|g| serde_yaml::Value::String(g.to_string()))
+            .collect();
+        map.insert(
+            serde_yaml::Value::String("memberOf".to_string()),
+            serde_yaml::Value::Sequence(seq),
+        );
+
+        let mut ews = create_test_entity(EntityKind::User, name, None, None);
+        ews.entity.spec = serde_yaml::Value::Mapping(map);
+        ews
+    }
+
+    /// Labels of a node's children, in order.
+    fn child_labels(tree: &EntityTree, id: usize) -> Vec<String> {
+        tree.nodes[id]
+            .children
+            .iter()
+            .map(|&c| tree.nodes[c].label.clone())
+            .collect()
+    }
+
+    fn find_node<'a>(tree: &'a EntityTree, needle: &str) -> &'a TreeNode {
+        tree.nodes
+            .iter()
+            .find(|n| n.label.contains(needle))
+            .unwrap_or_else(|| panic!("node containing {needle}"))
+    }
+
+    #[test]
+    fn test_users_nested_under_groups() {
+        let entities = vec![
+            create_test_group("platform", None),
+            create_test_group("team-a", Some("platform")),
+            create_test_user("alice", &["team-a"]),
+            create_test_user("bob", &["group:default/team-a"]),
+            create_test_user("carol", &["platform"]),
+        ];
+
+        let tree = EntityTree::build(entities);
+
+        // No user falls through to "Other Entities".
+        assert!(!tree
+            .root_children
+            .iter()
+            .any(|&id| tree.nodes[id].label == "Other Entities"));
+
+        // Child groups come before member users; users are sorted.
+        let platform = find_node(&tree, "platform");
+        let labels = child_labels(&tree, platform.id);
+        assert!(labels[0].contains("team-a"), "child group first");
+        assert!(labels[1].contains("carol"));
+
+        let team_a = find_node(&tree, "team-a");
+        assert_eq!(
+            child_labels(&tree, team_a.id),
+            vec!["User: alice", "User: bob"]
+        );
+        assert_eq!(tree.nodes[team_a.children[0]].depth, team_a.depth + 1);
+    }
+
+    #[test]
+    fn test_group_labels_show_member_counts() {
+        let entities = vec![
+            create_test_group("solo", None),
+            create_test_group("pair", None),
+            create_test_group("empty", None),
+            create_test_user("alice", &["solo", "pair"]),
+            create_test_user("bob", &["pair"]),
+        ];
+
+        let tree = EntityTree::build(entities);
+
+        assert_eq!(find_node(&tree, "solo").label, "Group: solo (1 member)");
+        assert_eq!(find_node(&tree, "pair").label, "Group: pair (2 members)");
+        assert_eq!(find_node(&tree, "empty").label, "Group: empty");
+    }
+
+    #[test]
+    fn test_user_in_several_groups_appears_under_each() {
+        let entities = vec![
+            create_test_group("team-a", None),
+            create_test_group("team-b", None),
+            create_test_user("alice", &["team-a", "team-b"]),
+        ];
+
+        let tree = EntityTree::build(entities);
+
+        let alice_nodes: Vec<&TreeNode> = tree
+            .nodes
+            .iter()
+            .filter(|n| n.label == "User: alice")
+            .collect();
+        assert_eq!(alice_nodes.len(), 2, "one node per membership");
+        let parents: Vec<String> = alice_nodes
+            .iter()
+            .map(|n| tree.nodes[tree.parent_of(n.id).unwrap()].label.clone())
+            .collect();
+        assert!(parents.iter().any(|p| p.contains("team-a")));
+        assert!(parents.iter().any(|p| p.contains("team-b")));
+    }
+
+    #[test]
+    fn test_group_spec_members_places_users() {
+        let mut map = serde_yaml::Mapping::new();
+        map.insert(
+            serde_yaml::Value::String("members".to_string()),
+            serde_yaml::Value::Sequence(vec![
+                serde_yaml::Value::String("alice".to_string()),
+                serde_yaml::Value::String("user:default/bob".to_string()),
+            ]),
+        );
+        let mut group = create_test_group("team-a", None);
+        group.entity.spec = serde_yaml::Value::Mapping(map);
+
+        let entities = vec![
+            group,
+            // alice also lists the group herself; she must appear only once.
+            create_test_user("alice", &["team-a"]),
+            create_test_entity(EntityKind::User, "bob", None, None),
+            create_test_entity(EntityKind::User, "dave", None, None),
+        ];
+
+        let tree = EntityTree::build(entities);
+
+        let team_a = find_node(&tree, "team-a");
+        assert_eq!(
+            child_labels(&tree, team_a.id),
+            vec!["User: alice", "User: bob"]
+        );
+        assert!(team_a.label.ends_with("(2 members)"));
+
+        // A user with no membership stays under "Other Entities".
+        let other = find_node(&tree, "Other Entities");
+        assert_eq!(child_labels(&tree, other.id), vec!["User: dave"]);
+    }
+
     #[test]
     fn test_visible_rows_have_connectors() {
         let entities = vec![
@@ -1275,7 +1525,7 @@ mod tests {
             create_test_group("team-b", Some("parent-team")),
         ];
 
-        let tree = EntityTree::build(&entities);
+        let tree = EntityTree::build(entities);
         let mut state = TreeState::new();
         state.expand_all(&tree);
 
@@ -1312,7 +1562,7 @@ mod tests {
             ),
         ];
 
-        let tree = EntityTree::build(&entities);
+        let tree = EntityTree::build(entities);
 
         // Should have one root category: "Systems"
         assert_eq!(tree.root_children.len(), 1);
@@ -1357,7 +1607,7 @@ mod tests {
             ),
         ];
 
-        let tree = EntityTree::build(&entities);
+        let tree = EntityTree::build(entities);
         let mut state = TreeState::new();
 
         state.expand_all(&tree);
@@ -1393,7 +1643,7 @@ mod tests {
             create_test_entity(EntityKind::User, "alice", None, None),
         ];
 
-        let tree = EntityTree::build(&entities);
+        let tree = EntityTree::build(entities);
 
         // Should have 3 root categories: Domains, Systems, Other Entities
         assert_eq!(tree.root_children.len(), 3, "Should have 3 root categories");
@@ -1417,7 +1667,7 @@ mod tests {
             None,
         )];
 
-        let tree = EntityTree::build(&entities);
+        let tree = EntityTree::build(entities);
 
         // Valid node ID
         let node = tree.get_node(0);
@@ -1431,7 +1681,7 @@ mod tests {
     #[test]
     fn test_empty_tree() {
         let entities: Vec<EntityWithSource> = vec![];
-        let tree = EntityTree::build(&entities);
+        let tree = EntityTree::build(entities);
 
         assert_eq!(tree.nodes.len(), 0, "Tree should have no nodes");
         assert_eq!(tree.root_children.len(), 0, "Tree should have no root");
@@ -1449,7 +1699,7 @@ mod tests {
             create_test_entity(EntityKind::Component, "comp1", Some("system1"), None),
         ];
 
-        let tree = EntityTree::build(&entities);
+        let tree = EntityTree::build(entities);
 
         // System should be in "Systems" category (not under "Domains")
         assert_eq!(tree.root_children.len(), 1);
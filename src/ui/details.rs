@@ -1,5 +1,9 @@
 use crate::app::App;
 use crate::entity::{EntityIndex, EntityKind, EntityRef, EntityWithSource};
+use crate::fix::did_you_mean;
+use crate::relations::WithRelations;
+use crate::report::ref_resolves;
+use crate::report::MAX_SUGGESTIONS;
 use crate::ui::theme::*;
 use ratatui::{
     layout::Rect,
@@ -14,16 +18,18 @@ use std::collections::HashSet;
 /// `None` when no entity is selected. Used both for rendering and to measure
 /// content height for scrolling.
 pub fn detail_lines(app: &App) -> Option<Vec<Line<'static>>> {
+    let theme = &app.theme;
     app.selected_entity().map(|ews| {
         if app.show_raw {
-            format_entity_raw(&ews.entity)
+            format_entity_raw(ews, theme)
         } else {
-            format_entity_details(ews, &app.entity_index, &app.entities)
+            format_entity_details(ews, &app.catalog.index, &app.catalog.entities, theme)
         }
     })
 }
 
 pub fn draw_details(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
     let title = if app.show_raw {
         " Raw YAML "
     } else {
@@ -34,9 +40,9 @@ pub fn draw_details(frame: &mut Frame, app: &App, area: Rect) {
         .title(title)
         .borders(Borders::ALL)
         .border_style(if focused {
-            focused_border_style()
+            theme.focused_border_style()
         } else {
-            border_style()
+            theme.border_style()
         });
 
     if let Some(content) = detail_lines(app) {
@@ -51,7 +57,9 @@ pub fn draw_details(frame: &mut Frame, app: &App, area: Rect) {
             Some(n) if n.is_category => "Category node - select an entity to view details",
             _ => "No entity selected",
         };
-        let paragraph = Paragraph::new(text).block(block).style(dimmed_style());
+        let paragraph = Paragraph::new(text)
+            .block(block)
+            .style(theme.dimmed_style());
         frame.render_widget(paragraph, area);
     }
 }
@@ -60,13 +68,14 @@ fn format_entity_details(
     ews: &EntityWithSource,
     index: &EntityIndex,
     all_entities: &[EntityWithSource],
+    theme: &Theme,
 ) -> Vec<Line<'static>> {
     let entity = &ews.entity;
     let mut lines = Vec::new();
 
     // Header
     lines.push(Line::from(vec![
-        Span::styled("Kind: ", label_style()),
+        Span::styled("Kind: ", theme.label_style()),
         Span::styled(
             entity.kind.to_string(),
             Style::default().add_modifier(Modifier::BOLD),
@@ -74,7 +83,7 @@ fn format_entity_details(
     ]));
 
     lines.push(Line::from(vec![
-        Span::styled("Name: ", label_style()),
+        Span::styled("Name: ", theme.label_style()),
         Span::styled(
             entity.metadata.name.clone(),
             Style::default().add_modifier(Modifier::BOLD),
@@ -83,14 +92,14 @@ fn format_entity_details(
 
     if let Some(title) = &entity.metadata.title {
         lines.push(Line::from(vec![
-            Span::styled("Title: ", label_style()),
+            Span::styled("Title: ", theme.label_style()),
             Span::raw(title.clone()),
         ]));
     }
 
     if let Some(ns) = &entity.metadata.namespace {
         lines.push(Line::from(vec![
-            Span::styled("Namespace: ", label_style()),
+            Span::styled("Namespace: ", theme.label_style()),
             Span::raw(ns.clone()),
         ]));
     }
@@ -99,34 +108,37 @@ fn format_entity_details(
 
     // Description
     if let Some(desc) = &entity.metadata.description {
-        lines.push(Line::from(Span::styled("Description:", label_style())));
+        lines.push(Line::from(Span::styled(
+            "Description:",
+            theme.label_style(),
+        )));
         lines.push(Line::from(desc.clone()));
         lines.push(Line::from(""));
     }
 
     // Spec details with reference validation
     if let Some(owner) = entity.owner() {
-        let ref_line = format_entity_ref(&owner, "group", index);
+        let ref_line = format_entity_ref(&owner, "group", &[], index, theme);
         lines.push(Line::from(
-            std::iter::once(Span::styled("Owner: ", label_style()))
+            std::iter::once(Span::styled("Owner: ", theme.label_style()))
                 .chain(ref_line)
                 .collect::<Vec<_>>(),
         ));
     }
 
     if let Some(system) = entity.system() {
-        let ref_line = format_entity_ref(&system, "system", index);
+        let ref_line = format_entity_ref(&system, "system", &[], index, theme);
         lines.push(Line::from(
-            std::iter::once(Span::styled("System: ", label_style()))
+            std::iter::once(Span::styled("System: ", theme.label_style()))
                 .chain(ref_line)
                 .collect::<Vec<_>>(),
         ));
     }
 
     if let Some(domain) = entity.domain() {
-        let ref_line = format_entity_ref(&domain, "domain", index);
+        let ref_line = format_entity_ref(&domain, "domain", &[], index, theme);
         lines.push(Line::from(
-            std::iter::once(Span::styled("Domain: ", label_style()))
+            std::iter::once(Span::styled("Domain: ", theme.label_style()))
                 .chain(ref_line)
                 .collect::<Vec<_>>(),
         ));
@@ -134,58 +146,75 @@ fn format_entity_details(
 
     if let Some(lifecycle) = entity.lifecycle() {
         lines.push(Line::from(vec![
-            Span::styled("Lifecycle: ", label_style()),
+            Span::styled("Lifecycle: ", theme.label_style()),
             Span::raw(lifecycle),
         ]));
     }
 
     if let Some(etype) = entity.entity_type() {
         lines.push(Line::from(vec![
-            Span::styled("Type: ", label_style()),
+            Span::styled("Type: ", theme.label_style()),
             Span::raw(etype),
         ]));
     }
 
     // Group-specific information
     if matches!(entity.kind, EntityKind::Group) {
-        format_group_details(entity, index, all_entities, &mut lines);
+        format_group_details(entity, index, all_entities, &mut lines, theme);
     }
 
     // Relationships declared in the spec (dependencies, APIs, sub-components).
-    format_relationships(entity, index, &mut lines);
+    format_relationships(entity, index, &mut lines, theme);
 
     // Labels
     if !entity.metadata.labels.is_empty() {
-        format_key_values("Labels:", &entity.metadata.labels, &mut lines);
+        format_key_values("Labels:", &entity.metadata.labels, &mut lines, theme);
     }
 
     // Tags
     if !entity.metadata.tags.is_empty() {
         lines.push(Line::from(""));
-        lines.push(Line::from(Span::styled("Tags:", label_style())));
+        lines.push(Line::from(Span::styled("Tags:", theme.label_style())));
         lines.push(Line::from(entity.metadata.tags.join(", ")));
     }
 
     // Links
     if !entity.metadata.links.is_empty() {
-        format_links(&entity.metadata.links, &mut lines);
+        format_links(&entity.metadata.links, &mut lines, theme);
     }
 
     // Annotations
     if !entity.metadata.annotations.is_empty() {
-        format_annotations(&entity.metadata.annotations, &mut lines);
+        format_annotations(&entity.metadata.annotations, &mut lines, theme);
     }
 
     // Source file
     lines.push(Line::from(""));
     lines.push(Line::from(vec![
-        Span::styled("Source: ", dimmed_style()),
-        Span::styled(ews.source_file.display().to_string(), dimmed_style()),
+        Span::styled("Source: ", theme.dimmed_style()),
+        Span::styled(
+            format!("{}:{}", ews.source_file.display(), ews.line),
+            theme.dimmed_style(),
+        ),
     ]));
 
     // Validation errors
     if !ews.validation_errors.is_empty() {
-        format_validation_errors(&ews.validation_errors, &mut lines);
+        format_validation_errors(&ews.validation_errors, &mut lines, theme);
+    }
+
+    if !ews.duplicates.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("⧉ Also declared in ({}):", ews.duplicates.len()),
+            theme.duplicate_style(),
+        )));
+        for (file, line) in &ews.duplicates {
+            lines.push(Line::from(Span::styled(
+                format!("  {}:{line}", file.display()),
+                theme.normal_style(),
+            )));
+        }
     }
 
     lines
@@ -198,6 +227,7 @@ fn format_relationships(
     entity: &crate::entity::Entity,
     index: &EntityIndex,
     lines: &mut Vec<Line<'static>>,
+    theme: &Theme,
 ) {
     // Which spec reference fields to show here and how, in display order. The
     // default kinds and the references themselves come from the central
@@ -233,9 +263,10 @@ fn format_relationships(
 
         if single {
             let r = entries[0];
-            let ref_line = format_entity_ref(&r.reference, r.default_kind, index);
+            let ref_line =
+                format_entity_ref(&r.reference, r.default_kind, r.fallback_kinds, index, theme);
             lines.push(Line::from(
-                std::iter::once(Span::styled(format!("{heading}: "), label_style()))
+                std::iter::once(Span::styled(format!("{heading}: "), theme.label_style()))
                     .chain(ref_line)
                     .collect::<Vec<_>>(),
             ));
@@ -244,14 +275,15 @@ fn format_relationships(
 
         lines.push(Line::from(Span::styled(
             format!("{heading} ({}):", entries.len()),
-            label_style(),
+            theme.label_style(),
         )));
         let last = entries.len() - 1;
         for (i, r) in entries.iter().enumerate() {
             let connector = if i == last { "└─ " } else { "├─ " };
-            let ref_line = format_entity_ref(&r.reference, r.default_kind, index);
+            let ref_line =
+                format_entity_ref(&r.reference, r.default_kind, r.fallback_kinds, index, theme);
             lines.push(Line::from(
-                std::iter::once(Span::styled(connector.to_string(), dimmed_style()))
+                std::iter::once(Span::styled(connector.to_string(), theme.dimmed_style()))
                     .chain(ref_line)
                     .collect::<Vec<_>>(),
             ));
@@ -264,31 +296,36 @@ fn format_key_values(
     heading: &str,
     map: &std::collections::HashMap<String, String>,
     lines: &mut Vec<Line<'static>>,
+    theme: &Theme,
 ) {
     lines.push(Line::from(""));
-    lines.push(Line::from(Span::styled(heading.to_string(), label_style())));
+    lines.push(Line::from(Span::styled(
+        heading.to_string(),
+        theme.label_style(),
+    )));
 
     let mut sorted: Vec<_> = map.iter().collect();
     sorted.sort_by_key(|(k, _)| *k);
     for (key, value) in sorted {
         lines.push(Line::from(vec![
-            Span::styled(format!("  {key}: "), dimmed_style()),
+            Span::styled(format!("  {key}: "), theme.dimmed_style()),
             Span::raw(value.clone()),
         ]));
     }
 }
 
-/// Render the entity as its raw YAML definition, re-serialized from the parsed
-/// model. Returns a single error line if serialization somehow fails.
-fn format_entity_raw(entity: &crate::entity::Entity) -> Vec<Line<'static>> {
-    match serde_yaml::to_string(entity) {
+/// Render the entity as YAML in Backstage's processed shape: the parsed
+/// definition with its computed `relations` included. Returns a single error
+/// line if serialization somehow fails.
+fn format_entity_raw(ews: &EntityWithSource, theme: &Theme) -> Vec<Line<'static>> {
+    match serde_yaml::to_string(&WithRelations::from(ews)) {
         Ok(yaml) => yaml
             .lines()
             .map(|line| Line::from(Span::raw(line.to_string())))
             .collect(),
         Err(e) => vec![Line::from(Span::styled(
             format!("Failed to serialize entity: {e}"),
-            error_style(),
+            theme.error_style(),
         ))],
     }
 }
@@ -298,6 +335,7 @@ fn format_group_details(
     index: &EntityIndex,
     all_entities: &[EntityWithSource],
     lines: &mut Vec<Line<'static>>,
+    theme: &Theme,
 ) {
     lines.push(Line::from(""));
     lines.push(Line::from(Span::styled(
@@ -307,16 +345,16 @@ fn format_group_details(
 
     // Parent group
     if let Some(parent) = entity.get_spec_string("parent") {
-        let ref_line = format_entity_ref(&parent, "group", index);
+        let ref_line = format_entity_ref(&parent, "group", &[], index, theme);
         lines.push(Line::from(
-            std::iter::once(Span::styled("Parent: ", label_style()))
+            std::iter::once(Span::styled("Parent: ", theme.label_style()))
                 .chain(ref_line)
                 .collect::<Vec<_>>(),
         ));
     } else {
         lines.push(Line::from(Span::styled(
             "Parent: (none - root group)",
-            dimmed_style(),
+            theme.dimmed_style(),
         )));
     }
 
@@ -326,16 +364,24 @@ fn format_group_details(
         lines.push(Line::from(""));
         lines.push(Line::from(Span::styled(
             format!("Child Groups ({}):", children.len()),
-            label_style(),
+            theme.label_style(),
         )));
 
         let mut visited: HashSet<String> = HashSet::new();
         visited.insert(entity.ref_key());
-        format_child_group_tree(&children, index, all_entities, "", &mut visited, lines);
+        format_child_group_tree(
+            &children,
+            index,
+            all_entities,
+            "",
+            &mut visited,
+            lines,
+            theme,
+        );
     }
 
     // Members (users who have memberOf pointing to this group)
-    format_group_members(entity, all_entities, lines);
+    format_group_members(entity, all_entities, lines, theme);
 }
 
 /// Render a group's child hierarchy recursively with box-drawing connectors,
@@ -349,17 +395,21 @@ fn format_child_group_tree(
     prefix: &str,
     visited: &mut HashSet<String>,
     lines: &mut Vec<Line<'static>>,
+    theme: &Theme,
 ) {
     let last = children.len().saturating_sub(1);
     for (i, child) in children.iter().enumerate() {
         let is_last = i == last;
         let connector = if is_last { "└─ " } else { "├─ " };
 
-        let ref_line = format_entity_ref(child, "group", index);
+        let ref_line = format_entity_ref(child, "group", &[], index, theme);
         lines.push(Line::from(
-            std::iter::once(Span::styled(format!("{prefix}{connector}"), dimmed_style()))
-                .chain(ref_line)
-                .collect::<Vec<_>>(),
+            std::iter::once(Span::styled(
+                format!("{prefix}{connector}"),
+                theme.dimmed_style(),
+            ))
+            .chain(ref_line)
+            .collect::<Vec<_>>(),
         ));
 
         // Recurse into the child's own children, if we can resolve it and have
@@ -379,6 +429,7 @@ fn format_child_group_tree(
                         &child_prefix,
                         visited,
                         lines,
+                        theme,
                     );
                 }
             }
@@ -390,24 +441,24 @@ fn format_group_members(
     entity: &crate::entity::Entity,
     all_entities: &[EntityWithSource],
     lines: &mut Vec<Line<'static>>,
+    theme: &Theme,
 ) {
     let group_ref = entity.ref_key();
+    // Members come from either side: an entity's `memberOf`, or the group's
+    // own `spec.members` list of users.
+    let listed: HashSet<String> = entity
+        .members()
+        .iter()
+        .map(|m| EntityRef::parse(m, "user").canonical())
+        .collect();
     let mut members: Vec<&EntityWithSource> = all_entities
         .iter()
         .filter(|e| {
-            if let Some(member_of) = e.entity.spec.get("memberOf") {
-                if let Some(member_of_arr) = member_of.as_sequence() {
-                    return member_of_arr.iter().any(|m| {
-                        if let Some(m_str) = m.as_str() {
-                            let parsed = EntityRef::parse(m_str, "group");
-                            parsed.canonical() == group_ref
-                        } else {
-                            false
-                        }
-                    });
-                }
-            }
-            false
+            listed.contains(&e.entity.ref_key())
+                || e.entity
+                    .member_of()
+                    .iter()
+                    .any(|m| EntityRef::parse(m, "group").canonical() == group_ref)
         })
         .collect();
 
@@ -423,13 +474,13 @@ fn format_group_members(
     lines.push(Line::from(""));
     if members.is_empty() {
         lines.push(Line::from(vec![
-            Span::styled("Members: ", label_style()),
-            Span::styled("(none)", dimmed_style()),
+            Span::styled("Members: ", theme.label_style()),
+            Span::styled("(none)", theme.dimmed_style()),
         ]));
     } else {
         lines.push(Line::from(Span::styled(
             format!("Members ({}):", members.len()),
-            label_style(),
+            theme.label_style(),
         )));
 
         // Group members by kind for better organization
@@ -445,8 +496,8 @@ fn format_group_members(
 
             let kind_label = format!("[{}]", kind_str.to_lowercase());
             lines.push(Line::from(vec![
-                Span::styled("  • ", dimmed_style()),
-                Span::styled(kind_label, dimmed_style()),
+                Span::styled("  • ", theme.dimmed_style()),
+                Span::styled(kind_label, theme.dimmed_style()),
                 Span::raw(" "),
                 Span::styled(
                     member.entity.display_name(),
@@ -457,9 +508,9 @@ fn format_group_members(
     }
 }
 
-fn format_links(links: &[crate::entity::Link], lines: &mut Vec<Line<'static>>) {
+fn format_links(links: &[crate::entity::Link], lines: &mut Vec<Line<'static>>, theme: &Theme) {
     lines.push(Line::from(""));
-    lines.push(Line::from(Span::styled("Links:", label_style())));
+    lines.push(Line::from(Span::styled("Links:", theme.label_style())));
     for link in links {
         let title = link
             .title
@@ -473,9 +524,9 @@ fn format_links(links: &[crate::entity::Link], lines: &mut Vec<Line<'static>>) {
             .map(|i| format!("[{i}] "))
             .unwrap_or_default();
         lines.push(Line::from(vec![
-            Span::styled(format!("  {icon}"), dimmed_style()),
+            Span::styled(format!("  {icon}"), theme.dimmed_style()),
             Span::styled(title.to_string(), Style::default().fg(Color::Cyan)),
-            Span::styled(format!(" ({url})"), dimmed_style()),
+            Span::styled(format!(" ({url})"), theme.dimmed_style()),
         ]));
     }
 }
@@ -483,9 +534,13 @@ fn format_links(links: &[crate::entity::Link], lines: &mut Vec<Line<'static>>) {
 fn format_annotations(
     annotations: &std::collections::HashMap<String, String>,
     lines: &mut Vec<Line<'static>>,
+    theme: &Theme,
 ) {
     lines.push(Line::from(""));
-    lines.push(Line::from(Span::styled("Annotations:", label_style())));
+    lines.push(Line::from(Span::styled(
+        "Annotations:",
+        theme.label_style(),
+    )));
 
     let mut sorted_annotations: Vec<_> = annotations.iter().collect();
     sorted_annotations.sort_by_key(|(k, _)| *k);
@@ -495,12 +550,12 @@ fn format_annotations(
         let key_style = if is_docs_annotation {
             Style::default().fg(Color::Magenta)
         } else {
-            dimmed_style()
+            theme.dimmed_style()
         };
         let value_style = if is_docs_annotation {
             Style::default().fg(Color::Magenta)
         } else {
-            normal_style()
+            theme.normal_style()
         };
         let doc_hint = if is_docs_annotation {
             Span::styled(" [d to view]", Style::default().fg(Color::Green))
@@ -519,39 +574,44 @@ fn format_annotations(
 fn format_validation_errors(
     errors: &[crate::entity::ValidationError],
     lines: &mut Vec<Line<'static>>,
+    theme: &Theme,
 ) {
     lines.push(Line::from(""));
     lines.push(Line::from(Span::styled(
         format!("⚠ Validation Errors ({}):", errors.len()),
-        error_style(),
+        theme.error_style(),
     )));
 
     for (idx, error) in errors.iter().enumerate() {
         lines.push(Line::from(""));
         lines.push(Line::from(vec![
             Span::styled(format!("  {}. ", idx + 1), Style::default().fg(Color::Red)),
-            Span::styled(format!("Field: {}", error.path), label_style()),
+            Span::styled(format!("Field: {}", error.path), theme.label_style()),
         ]));
         lines.push(Line::from(vec![
             Span::styled("     ", Style::default()),
-            Span::styled(error.message.clone(), normal_style()),
+            Span::styled(error.message.clone(), theme.normal_style()),
         ]));
     }
 }
 
 /// Format an entity reference with resolved kind/namespace and validation
 ///
-/// Explicit parts shown in bright colors, inferred parts shown dim in \[brackets\]
+/// Explicit parts shown in bright colors, inferred parts shown dim in \[brackets\].
+/// An unqualified reference also resolves under the field's `fallbacks`, as in
+/// the relationship graph.
 fn format_entity_ref(
     reference: &str,
     default_kind: &str,
+    fallbacks: &[&str],
     index: &EntityIndex,
+    theme: &Theme,
 ) -> Vec<Span<'static>> {
     let entity_ref = EntityRef::parse(reference, default_kind);
     let mut spans = Vec::new();
 
     // Check for errors
-    let exists = index.contains(&entity_ref);
+    let exists = ref_resolves(index, reference, default_kind, fallbacks);
     let known_kind = entity_ref.is_known_kind();
 
     // Determine base color based on validation status
@@ -615,6 +675,16 @@ fn format_entity_ref(
         ));
     }
 
+    if known_kind && !exists {
+        let suggestions = did_you_mean(index, reference, default_kind, fallbacks, MAX_SUGGESTIONS);
+        if !suggestions.is_empty() {
+            spans.push(Span::styled(
+                format!(" did you mean {}?", suggestions.join(", ")),
+                theme.suggestion_style(),
+            ));
+        }
+    }
+
     spans
 }
 
@@ -656,7 +726,7 @@ mod tests {
         );
         let index = EntityIndex::build(&[]);
         let mut lines = Vec::new();
-        format_relationships(&entity, &index, &mut lines);
+        format_relationships(&entity, &index, &mut lines, &Theme::default());
 
         let text = line_text(&lines).join("\n");
         assert!(text.contains("Relationships"), "section header present");
@@ -668,12 +738,47 @@ mod tests {
         assert!(text.contains("└─ "));
     }
 
+    #[test]
+    fn missing_reference_suggests_nearest_entity() {
+        let entities = vec![EntityWithSource::new(
+            component_with_spec("type: service"),
+            "catalog-info.yaml".into(),
+        )];
+        let index = EntityIndex::build(&entities);
+        let text: String =
+            format_entity_ref("component:sv", "component", &[], &index, &Theme::default())
+                .iter()
+                .map(|s| s.content.as_ref())
+                .collect();
+        assert!(text.contains("[not found]"));
+        assert!(text.ends_with(" did you mean component:svc?"), "{text}");
+    }
+
+    #[test]
+    fn missing_reference_suggests_fallback_kinds() {
+        let mut db = component_with_spec("type: database");
+        db.kind = EntityKind::Resource;
+        db.metadata.name = "db".to_string();
+        let entities = vec![EntityWithSource::new(db, "catalog-info.yaml".into())];
+        let index = EntityIndex::build(&entities);
+        let text = |reference, fallbacks| -> String {
+            format_entity_ref(reference, "component", fallbacks, &index, &Theme::default())
+                .iter()
+                .map(|s| s.content.as_ref())
+                .collect()
+        };
+        assert!(!text("db", &["resource"]).contains("[not found]"));
+        assert!(text("dbx", &["resource"]).ends_with(" did you mean resource:db?"));
+        // Without the fallback, a Resource isn't suggested.
+        assert!(!text("dbx", &[]).contains("did you mean"));
+    }
+
     #[test]
     fn no_relationships_emits_nothing() {
         let entity = component_with_spec("type: service");
         let index = EntityIndex::build(&[]);
         let mut lines = Vec::new();
-        format_relationships(&entity, &index, &mut lines);
+        format_relationships(&entity, &index, &mut lines, &Theme::default());
         assert!(lines.is_empty(), "no relationship fields -> no output");
     }
 
@@ -683,7 +788,7 @@ mod tests {
         map.insert("tier".to_string(), "1".to_string());
         map.insert("app".to_string(), "web".to_string());
         let mut lines = Vec::new();
-        format_key_values("Labels:", &map, &mut lines);
+        format_key_values("Labels:", &map, &mut lines, &Theme::default());
         let text = line_text(&lines);
         let app_idx = text.iter().position(|l| l.contains("app:")).unwrap();
         let tier_idx = text.iter().position(|l| l.contains("tier:")).unwrap();
@@ -1,4 +1,6 @@
+use crate::actions::Action;
 use crate::docs::DocsBrowser;
+use crate::keymap::{Keymap, Mode};
 use crate::ui::theme::*;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
@@ -8,7 +10,13 @@ use ratatui::{
     Frame,
 };
 
-pub fn draw_docs_browser(frame: &mut Frame, browser: &DocsBrowser, area: Rect) {
+pub fn draw_docs_browser(
+    frame: &mut Frame,
+    browser: &DocsBrowser,
+    keymap: &Keymap,
+    area: Rect,
+    theme: &Theme,
+) {
     // Split into main content and help footer
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -17,25 +25,45 @@ pub fn draw_docs_browser(frame: &mut Frame, browser: &DocsBrowser, area: Rect) {
 
     let content_area = chunks[0];
     let help_area = chunks[1];
+    let key = |action| keymap.hint(Mode::Docs, action);
 
     if let Some(doc_content) = &browser.viewing_content {
         // Show document content
-        draw_doc_content(frame, doc_content, browser.scroll_offset, content_area);
+        draw_doc_content(
+            frame,
+            doc_content,
+            browser.scroll_offset,
+            content_area,
+            theme,
+        );
 
-        let help = Paragraph::new(" Esc: Back to list | ↑↓/jk: Scroll | PgUp/PgDn: Page scroll ")
-            .style(dimmed_style());
+        let help = Paragraph::new(format!(
+            " {}: Back to list | {}{}: Scroll | {}/{}: Page scroll ",
+            key(Action::Back),
+            key(Action::MoveUp),
+            key(Action::MoveDown),
+            key(Action::PageUp),
+            key(Action::PageDown),
+        ))
+        .style(theme.dimmed_style());
         frame.render_widget(help, help_area);
     } else {
         // Show file list
-        draw_docs_file_list(frame, browser, content_area);
-
-        let help = Paragraph::new(" Esc: Close docs | Enter: Open file | ↑↓: Navigate ")
-            .style(dimmed_style());
+        draw_docs_file_list(frame, browser, content_area, theme);
+
+        let help = Paragraph::new(format!(
+            " {}: Close docs | {}: Open file | {}{}: Navigate ",
+            key(Action::Back),
+            key(Action::Confirm),
+            key(Action::MoveUp),
+            key(Action::MoveDown),
+        ))
+        .style(theme.dimmed_style());
         frame.render_widget(help, help_area);
     }
 }
 
-fn draw_docs_file_list(frame: &mut Frame, browser: &DocsBrowser, area: Rect) {
+fn draw_docs_file_list(frame: &mut Frame, browser: &DocsBrowser, area: Rect, theme: &Theme) {
     let title = format!(
         " {} Documentation ({} files) ",
         browser.docs_ref.ref_type.label(),
@@ -50,7 +78,7 @@ fn draw_docs_file_list(frame: &mut Frame, browser: &DocsBrowser, area: Rect) {
     if browser.files.is_empty() {
         let paragraph = Paragraph::new("No markdown files found in documentation directory")
             .block(block)
-            .style(dimmed_style());
+            .style(theme.dimmed_style());
         frame.render_widget(paragraph, area);
         return;
     }
@@ -67,7 +95,7 @@ fn draw_docs_file_list(frame: &mut Frame, browser: &DocsBrowser, area: Rect) {
                     .fg(Color::White)
                     .add_modifier(Modifier::BOLD)
             } else {
-                normal_style()
+                theme.normal_style()
             };
 
             ListItem::new(Line::from(Span::styled(file.relative_path.clone(), style)))
@@ -83,6 +111,7 @@ fn draw_doc_content(
     content: &crate::docs::DocContent,
     scroll: usize,
     area: Rect,
+    theme: &Theme,
 ) {
     let title = format!(" {} ", content.file.name);
 
@@ -117,7 +146,7 @@ fn draw_doc_content(
             content.lines.len()
         );
         let scroll_len = scroll_info.len();
-        let scroll_span = Span::styled(scroll_info, dimmed_style());
+        let scroll_span = Span::styled(scroll_info, theme.dimmed_style());
         let scroll_para = Paragraph::new(Line::from(scroll_span));
         let scroll_area = Rect {
             x: area.x + area.width.saturating_sub(scroll_len as u16 + 2),
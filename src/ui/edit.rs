@@ -0,0 +1,120 @@
+use crate::app::App;
+use crate::edit::{EditField, EditForm};
+use crate::ui::theme::*;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+/// Most completion candidates listed under the field being edited.
+const MAX_CANDIDATES: usize = 8;
+
+/// Draw the field editor in place of the details panel.
+pub fn draw_edit_form(frame: &mut Frame, app: &App, form: &EditForm, area: Rect) {
+    let theme = &app.theme;
+    let block = Block::default()
+        .title(" Edit ")
+        .borders(Borders::ALL)
+        .border_style(theme.focused_border_style());
+
+    let Some(ews) = app.selected_entity() else {
+        let paragraph = Paragraph::new("No entity selected")
+            .block(block)
+            .style(theme.dimmed_style());
+        frame.render_widget(paragraph, area);
+        return;
+    };
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(format!("{}: ", ews.entity.kind), theme.label_style()),
+            Span::styled(
+                ews.entity.metadata.name.clone(),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(Span::styled(
+            format!("{}:{}", ews.source_file.display(), ews.line),
+            theme.dimmed_style(),
+        )),
+        Line::from(""),
+    ];
+
+    for (i, field) in EditField::ALL.iter().enumerate() {
+        let selected = i == form.selected;
+        let marker = if selected { "▶ " } else { "  " };
+        let label = Span::styled(
+            format!("{marker}{:<12}", field.label()),
+            theme.label_style(),
+        );
+
+        let value = match (&form.input, selected) {
+            (Some(input), true) => {
+                Span::styled(format!("{input}{SELECTED_INDICATOR}"), theme.label_style())
+            }
+            _ => {
+                let text = field.current_text(&ews.entity);
+                if text.is_empty() {
+                    Span::styled("(not set)", theme.dimmed_style())
+                } else if field.is_reference() {
+                    Span::styled(text, theme.suggestion_style())
+                } else {
+                    Span::raw(text)
+                }
+            }
+        };
+        let row_style = if selected && !form.is_editing() {
+            Style::default().add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        lines.push(Line::from(vec![label, value]).style(row_style));
+
+        if selected && form.is_editing() {
+            for (n, candidate) in form
+                .candidates(&app.catalog.index)
+                .into_iter()
+                .take(MAX_CANDIDATES)
+                .enumerate()
+            {
+                let style = if form.completion == Some(n) {
+                    theme.selected_style()
+                } else {
+                    theme.dimmed_style()
+                };
+                lines.push(Line::from(vec![
+                    Span::raw(" ".repeat(16)),
+                    Span::styled(candidate, style),
+                ]));
+            }
+        }
+    }
+
+    lines.push(Line::from(""));
+    if let Some(message) = &form.message {
+        let style = if message.starts_with("Not saved") {
+            theme.error_style()
+        } else {
+            theme.changed_style()
+        };
+        lines.push(Line::from(Span::styled(message.clone(), style)));
+    }
+    let hint = if form.is_editing() {
+        match form.field() {
+            EditField::Tags => "Comma-separated, e.g. java, backend",
+            EditField::Links => "`title | url` entries separated by `;`",
+            _ => "Leave empty to remove the field",
+        }
+    } else {
+        "Changes are written back to the source file"
+    };
+    lines.push(Line::from(Span::styled(hint, theme.dimmed_style())));
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, area);
+}
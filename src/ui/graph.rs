@@ -12,13 +12,15 @@ use ratatui::{
 /// Build the relationship lines for the selected entity, or `None` when nothing
 /// is selected. Used for rendering and to measure content height for scrolling.
 pub fn graph_lines(app: &App) -> Option<Vec<Line<'static>>> {
+    let theme = &app.theme;
     app.relationship_graph()
-        .map(|graph| format_graph(&graph, app.graph_selection))
+        .map(|graph| format_graph(&graph, app.graph_selection, theme))
 }
 
 pub fn draw_graph(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
     let border = if app.is_detail_focused() {
-        focused_border_style()
+        theme.focused_border_style()
     } else {
         Style::default().fg(Color::Magenta)
     };
@@ -36,20 +38,20 @@ pub fn draw_graph(frame: &mut Frame, app: &App, area: Rect) {
     } else {
         let paragraph = Paragraph::new("Select an entity to view relationships")
             .block(block)
-            .style(dimmed_style());
+            .style(theme.dimmed_style());
         frame.render_widget(paragraph, area);
     }
 }
 
 /// Render the graph. `selected` is the index into the navigable (existing)
 /// related entities, which is highlighted so it can be jumped to.
-fn format_graph(graph: &RelationshipGraph, selected: usize) -> Vec<Line<'static>> {
+fn format_graph(graph: &RelationshipGraph, selected: usize, theme: &Theme) -> Vec<Line<'static>> {
     let mut lines = Vec::new();
 
     // Center entity
     lines.push(Line::from(vec![
         Span::styled("◉ ", Style::default().fg(Color::Cyan)),
-        Span::styled(format!("[{}] ", graph.center.kind), dimmed_style()),
+        Span::styled(format!("[{}] ", graph.center.kind), theme.dimmed_style()),
         Span::styled(
             graph.center.display_name.clone(),
             Style::default()
@@ -85,7 +87,7 @@ fn format_graph(graph: &RelationshipGraph, selected: usize) -> Vec<Line<'static>
 
         let navigable = entry.node.exists;
         let highlighted = navigable && nav_index == selected;
-        lines.push(relationship_line(entry, highlighted));
+        lines.push(relationship_line(entry, highlighted, theme));
         if navigable {
             nav_index += 1;
         }
@@ -99,12 +101,12 @@ fn format_graph(graph: &RelationshipGraph, selected: usize) -> Vec<Line<'static>
             graph.outgoing.len(),
             graph.incoming.len()
         ),
-        dimmed_style(),
+        theme.dimmed_style(),
     )]));
     if nav_index > 0 {
         lines.push(Line::from(Span::styled(
             "↑↓ select related · Enter to jump",
-            dimmed_style(),
+            theme.dimmed_style(),
         )));
     }
 
@@ -115,7 +117,11 @@ fn section_header(text: &'static str, color: Color) -> Line<'static> {
     Line::from(Span::styled(text, Style::default().fg(color)))
 }
 
-fn relationship_line(entry: &crate::graph::RelatedEntry, highlighted: bool) -> Line<'static> {
+fn relationship_line(
+    entry: &crate::graph::RelatedEntry,
+    highlighted: bool,
+    theme: &Theme,
+) -> Line<'static> {
     let node = &entry.node;
     let (arrow, color) = if !entry.outgoing {
         ("←", Color::Blue)
@@ -127,8 +133,8 @@ fn relationship_line(entry: &crate::graph::RelatedEntry, highlighted: bool) -> L
 
     let mut spans = vec![
         Span::styled(format!("  {arrow} "), Style::default().fg(color)),
-        Span::styled(format!("{}: ", entry.label), dimmed_style()),
-        Span::styled(format!("[{}] ", node.kind), dimmed_style()),
+        Span::styled(format!("{}: ", entry.label), theme.dimmed_style()),
+        Span::styled(format!("[{}] ", node.kind), theme.dimmed_style()),
         Span::styled(node.display_name.clone(), Style::default().fg(color)),
     ];
     if !node.exists {
@@ -140,7 +146,7 @@ fn relationship_line(entry: &crate::graph::RelatedEntry, highlighted: bool) -> L
 
     let line = Line::from(spans);
     if highlighted {
-        line.style(selected_style())
+        line.style(theme.selected_style())
     } else {
         line
     }
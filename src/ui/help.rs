@@ -1,5 +1,6 @@
+use crate::actions::{Action, ACTIONS};
 use crate::app::App;
-use crate::ui::theme::{border_style, dimmed_style, label_style};
+use crate::keymap::Mode;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     text::{Line, Span},
@@ -7,62 +8,39 @@ use ratatui::{
     Frame,
 };
 
-const HELP_ROWS: &[(&str, &str)] = &[
-    ("Tab", "Switch focus between tree and detail panel"),
-    ("↑ / k, ↓ / j", "Move selection (tree) or scroll (panel)"),
-    ("PgUp / PgDn", "Move / scroll by a page"),
-    ("Home / End", "Jump to first / last (or top / bottom)"),
-    ("← / h", "Collapse node / return focus to tree"),
-    ("→ / l / Enter", "Expand node"),
-    ("e", "Expand all nodes"),
-    ("c", "Collapse all nodes"),
-    ("/", "Search (incremental, case-insensitive)"),
-    ("g", "Toggle relationship graph"),
-    (
-        "Enter (graph)",
-        "Jump to the highlighted related entity (Tab to focus graph)",
-    ),
-    ("y", "Toggle raw YAML view"),
-    ("d", "Open documentation browser (when available)"),
-    ("r", "Reload catalog from disk"),
-    (
-        "x / X",
-        "Jump to next / previous entity with validation errors",
-    ),
-    ("?", "Toggle this help"),
-    ("Esc", "Clear search / close / return focus to tree"),
-    ("q", "Quit"),
-];
-
-/// Draw the keyboard-shortcut help overlay centered over the screen.
-pub fn draw_help_overlay(frame: &mut Frame, area: Rect) {
-    let popup = centered_rect(60, 80, area);
+/// Draw the keyboard-shortcut help overlay centered over the screen, one row
+/// per registered action with its effective keys; unbound actions show their
+/// palette command.
+pub fn draw_help_overlay(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let popup = centered_rect(70, 90, area);
 
     let mut lines: Vec<Line> = vec![Line::from("")];
-    for (keys, desc) in HELP_ROWS {
+    for info in ACTIONS {
+        let keys = app.keymap.describe(info.action);
         lines.push(Line::from(vec![
             Span::raw("  "),
-            Span::styled(format!("{keys:<16}"), label_style()),
-            Span::raw(*desc),
+            Span::styled(format!("{keys:<18}"), theme.label_style()),
+            Span::raw(info.description),
         ]));
     }
     lines.push(Line::from(""));
     lines.push(Line::from(Span::styled(
         "  Press any key to close",
-        dimmed_style(),
+        theme.dimmed_style(),
     )));
 
     let block = Block::default()
         .title(" Keyboard Shortcuts ")
         .borders(Borders::ALL)
-        .border_style(border_style());
+        .border_style(theme.border_style());
 
     frame.render_widget(Clear, popup);
     frame.render_widget(Paragraph::new(lines).block(block), popup);
 }
 
 /// Compute a rectangle centered within `area`, sized as a percentage of it.
-fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+pub(super) fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
     let vertical = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -82,19 +60,25 @@ fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
 }
 
 pub fn draw_help_footer(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
     // Don't draw footer if docs browser is active (it has its own)
     if app.docs_browser.is_some() {
         return;
     }
 
-    let has_docs = !app.get_docs_refs().is_empty();
-    let docs_hint = if has_docs { " | d: Docs" } else { "" };
+    // Hints name the first key bound to each action.
+    let key = |action| app.keymap.hint(Mode::Normal, action);
+    let search_key = |action| app.keymap.hint(Mode::Search, action);
 
-    let raw_hint = if app.show_raw {
-        " | y: Details"
+    let has_docs = !app.get_docs_refs().is_empty();
+    let docs_hint = if has_docs {
+        format!(" | {}: Docs", key(Action::OpenDocs))
     } else {
-        " | y: Raw YAML"
+        String::new()
     };
+
+    let raw_view = if app.show_raw { "Details" } else { "Raw YAML" };
+    let raw_hint = format!(" | {}: {raw_view}", key(Action::ToggleRaw));
     let panel_name = if app.show_graph { "Details" } else { "Graph" };
     let warn_hint = if app.load_warnings.is_empty() {
         String::new()
@@ -102,29 +86,105 @@ pub fn draw_help_footer(frame: &mut Frame, app: &App, area: Rect) {
         format!(" | ⚠ {} warning(s)", app.load_warnings.len())
     };
     let help_text = if app.search_active {
-        " Enter: Confirm | Esc: Cancel | Type to search... ".to_string()
+        format!(
+            " {}: Jump to result | {}: Filter | {}{}: Select | {}: Cancel | Type to search... ",
+            search_key(Action::Confirm),
+            search_key(Action::Filter),
+            search_key(Action::MoveUp),
+            search_key(Action::MoveDown),
+            search_key(Action::Back),
+        )
+    } else if app.palette.is_some() {
+        " Enter: Run | Tab: Complete | ↑↓: Select | Esc: Close | Type a command... ".to_string()
+    } else if app
+        .search_picker
+        .as_ref()
+        .is_some_and(|p| p.naming.is_some())
+    {
+        " Enter: Save | Esc: Cancel | Type a name... ".to_string()
+    } else if app.search_picker.is_some() {
+        " ↑↓: Select | Enter: Apply | a: Save current filter | Esc: Close ".to_string()
+    } else if app.edit_form.as_ref().is_some_and(|f| f.is_editing()) {
+        " Enter: Save | Tab: Complete | Esc: Cancel | Type to edit... ".to_string()
+    } else if app.new_wizard.is_some() {
+        " Enter: Next | Tab: Choose | Esc: Back ".to_string()
+    } else if app.edit_form.is_some() {
+        " ↑↓: Select field | Enter: Edit | Esc: Close editor | q: Quit ".to_string()
     } else if app.is_detail_focused() && app.show_graph {
         // Graph pane focused: up/down pick a related entity, Enter jumps.
         format!(
-            " q: Quit | ?: Help | Tab: Focus tree | ↑↓: Select related | Enter: Jump | PgUp/PgDn: Scroll | g: Details{warn_hint} "
+            " {}: Quit | {}: Help | {}: Focus tree | {}{}: Select related | {}: Jump | {}/{}: Scroll | {}: Details{warn_hint} ",
+            key(Action::Quit),
+            key(Action::Help),
+            key(Action::ToggleFocus),
+            key(Action::MoveUp),
+            key(Action::MoveDown),
+            key(Action::Confirm),
+            key(Action::PageUp),
+            key(Action::PageDown),
+            key(Action::ToggleGraph),
         )
     } else if app.is_detail_focused() {
         // Detail pane focused: navigation keys scroll it.
         format!(
-            " q: Quit | ?: Help | Tab: Focus tree | ↑↓/PgUp/PgDn: Scroll | Home/End: Top/Bottom | g: {panel_name}{warn_hint} "
+            " {}: Quit | {}: Help | {}: Focus tree | {}{}/{}/{}: Scroll | {}/{}: Top/Bottom | {}: {panel_name}{warn_hint} ",
+            key(Action::Quit),
+            key(Action::Help),
+            key(Action::ToggleFocus),
+            key(Action::MoveUp),
+            key(Action::MoveDown),
+            key(Action::PageUp),
+            key(Action::PageDown),
+            key(Action::First),
+            key(Action::Last),
+            key(Action::ToggleGraph),
         )
     } else {
         let err_hint = if app.error_count() > 0 {
-            " | x: Errors"
+            format!(" | {}: Errors", key(Action::NextError))
         } else {
-            ""
+            String::new()
+        };
+        let changes_hint = if app.changes.is_empty() {
+            String::new()
+        } else {
+            format!(" | {}: Ack changes", key(Action::AcknowledgeChanges))
+        };
+        let filter_hint = if app.filter.is_some() {
+            format!(" | {}: Unfilter", key(Action::ClearFilter))
+        } else {
+            String::new()
         };
         format!(
-            " q: Quit | ?: Help | Tab: Focus | g: {panel_name}{raw_hint} | /: Search | r: Reload{docs_hint}{err_hint} | ↑↓: Nav | ←→: Expand{warn_hint} "
+            " {}: Quit | {}: Help | {}: Focus | {}: {panel_name}{raw_hint} | {}: Search | {}: Commands | {}: Saved{filter_hint} | {}: Reload{changes_hint}{docs_hint}{err_hint} | {}{}: Nav | {}{}: Expand{warn_hint} ",
+            key(Action::Quit),
+            key(Action::Help),
+            key(Action::ToggleFocus),
+            key(Action::ToggleGraph),
+            key(Action::Search),
+            key(Action::CommandPalette),
+            key(Action::SavedSearches),
+            key(Action::Reload),
+            key(Action::MoveUp),
+            key(Action::MoveDown),
+            key(Action::Collapse),
+            key(Action::Expand),
         )
     };
-    let help = Paragraph::new(help_text)
-        .style(dimmed_style())
-        .block(Block::default());
+    // The active filter leads the footer in every mode.
+    let mut spans = Vec::new();
+    if let Some(status) = &app.status {
+        spans.push(Span::styled(format!(" {status} "), theme.label_style()));
+        spans.push(Span::styled("|", theme.dimmed_style()));
+    }
+    if let Some(filter) = &app.filter {
+        spans.push(Span::styled(
+            format!(" Filter: {} ", filter.label),
+            theme.label_style(),
+        ));
+        spans.push(Span::styled("|", theme.dimmed_style()));
+    }
+    spans.push(Span::styled(help_text, theme.dimmed_style()));
+    let help = Paragraph::new(Line::from(spans)).block(Block::default());
     frame.render_widget(help, area);
 }
@@ -1,8 +1,12 @@
 mod details;
 mod docs;
+mod edit;
 mod graph;
 mod help;
-mod theme;
+mod palette;
+mod scaffold;
+mod searches;
+pub mod theme;
 mod tree;
 
 use crate::app::App;
@@ -57,7 +61,7 @@ pub fn right_panel_line_count(app: &App) -> usize {
 pub fn draw(frame: &mut Frame, app: &App) {
     // If docs browser is active, show full-screen docs view
     if let Some(docs_browser) = &app.docs_browser {
-        docs::draw_docs_browser(frame, docs_browser, frame.area());
+        docs::draw_docs_browser(frame, docs_browser, &app.keymap, frame.area(), &app.theme);
         return;
     }
 
@@ -65,14 +69,28 @@ pub fn draw(frame: &mut Frame, app: &App) {
 
     tree::draw_tree(frame, app, layout.tree);
 
-    if app.show_graph {
+    if let Some(form) = &app.edit_form {
+        edit::draw_edit_form(frame, app, form, layout.detail);
+    } else if app.show_graph {
         graph::draw_graph(frame, app, layout.detail);
     } else {
         details::draw_details(frame, app, layout.detail);
     }
 
+    if let Some(wizard) = &app.new_wizard {
+        scaffold::draw_new_entity_wizard(frame, app, wizard, frame.area());
+    }
+
+    if let Some(picker) = &app.search_picker {
+        searches::draw_search_picker(frame, app, picker, frame.area());
+    }
+
+    if let Some(palette) = &app.palette {
+        palette::draw_palette(frame, app, palette, frame.area());
+    }
+
     // The help overlay floats above everything else.
     if app.show_help {
-        help::draw_help_overlay(frame, frame.area());
+        help::draw_help_overlay(frame, app, frame.area());
     }
 }
@@ -0,0 +1,73 @@
+use crate::app::{App, CommandPalette};
+use crate::ui::help::centered_rect;
+use crate::ui::theme::*;
+use ratatui::{
+    layout::Rect,
+    style::Modifier,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Draw the command palette: the prompt, then the matching actions with
+/// their bindings.
+pub fn draw_palette(frame: &mut Frame, app: &App, palette: &CommandPalette, area: Rect) {
+    let theme = &app.theme;
+    let popup = centered_rect(70, 60, area);
+
+    let mut lines = vec![Line::from(vec![
+        Span::styled(" : ", theme.label_style()),
+        Span::styled(
+            format!("{}{SELECTED_INDICATOR}", palette.input),
+            theme.label_style().add_modifier(Modifier::BOLD),
+        ),
+    ])];
+    if let Some(error) = &palette.error {
+        lines.push(Line::from(Span::styled(
+            format!("   {error}"),
+            theme.error_style(),
+        )));
+    }
+    lines.push(Line::from(""));
+
+    let matches = app.palette_matches();
+    if matches.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "   No matching command",
+            theme.dimmed_style(),
+        )));
+    }
+    // Keep the selection in view: rows fill the popup below the prompt.
+    let rows = (popup.height as usize)
+        .saturating_sub(lines.len() + 2)
+        .max(1);
+    let skip = palette.selected.saturating_sub(rows - 1);
+    for (i, info) in matches.iter().enumerate().skip(skip).take(rows) {
+        let command = match info.param {
+            Some(param) => format!("{} {param}", info.command),
+            None => info.command.to_string(),
+        };
+        // Palette-only commands have no keys to show.
+        let keys = app.keymap.describe(info.action);
+        let keys = if keys.starts_with(':') { "" } else { &keys };
+        let (command_style, description_style) = if i == palette.selected {
+            (theme.selected_style(), theme.selected_style())
+        } else {
+            (theme.label_style(), theme.normal_style())
+        };
+        lines.push(Line::from(vec![
+            Span::raw("   "),
+            Span::styled(format!("{command:<24}"), command_style),
+            Span::styled(format!("{keys:<18}"), theme.dimmed_style()),
+            Span::styled(info.description, description_style),
+        ]));
+    }
+
+    let block = Block::default()
+        .title(" Command Palette ")
+        .borders(Borders::ALL)
+        .border_style(theme.focused_border_style());
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(Paragraph::new(lines).block(block), popup);
+}
@@ -0,0 +1,91 @@
+use crate::app::App;
+use crate::scaffold::Wizard;
+use crate::ui::help::centered_rect;
+use crate::ui::theme::*;
+use ratatui::{
+    layout::Rect,
+    style::Modifier,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// Most choices listed under the current step.
+const MAX_CHOICES: usize = 10;
+
+/// Draw the "new entity" wizard as a popup over the main view.
+pub fn draw_new_entity_wizard(frame: &mut Frame, app: &App, wizard: &Wizard, area: Rect) {
+    let theme = &app.theme;
+    let popup = centered_rect(60, 70, area);
+    let current = wizard.current();
+
+    let mut lines = vec![Line::from("")];
+    for (i, step) in wizard.steps().into_iter().enumerate() {
+        let label = Span::styled(format!("  {:<11}", step.label()), theme.label_style());
+        let value = if step == current {
+            Span::styled(
+                format!("{}{SELECTED_INDICATOR}", wizard.input),
+                theme.label_style().add_modifier(Modifier::BOLD),
+            )
+        } else if i < wizard.step {
+            match wizard.draft.value(step) {
+                v if v.is_empty() => Span::styled("(none)", theme.dimmed_style()),
+                v => Span::raw(v),
+            }
+        } else {
+            Span::styled("…", theme.dimmed_style())
+        };
+        lines.push(Line::from(vec![label, value]));
+
+        if step == current {
+            let choices = app.new_entity_choices();
+            for (n, choice) in choices.iter().take(MAX_CHOICES).enumerate() {
+                let style = if wizard.completion == Some(n) {
+                    theme.selected_style()
+                } else {
+                    theme.dimmed_style()
+                };
+                lines.push(Line::from(vec![
+                    Span::raw(" ".repeat(13)),
+                    Span::styled(choice.clone(), style),
+                ]));
+            }
+            if choices.len() > MAX_CHOICES {
+                lines.push(Line::from(Span::styled(
+                    format!("{}… {} more", " ".repeat(13), choices.len() - MAX_CHOICES),
+                    theme.dimmed_style(),
+                )));
+            }
+        }
+    }
+
+    if !wizard.errors.is_empty() {
+        lines.push(Line::from(""));
+        for error in &wizard.errors {
+            lines.push(Line::from(Span::styled(
+                format!("  {error}"),
+                theme.error_style(),
+            )));
+        }
+    }
+    lines.push(Line::from(""));
+    let hint = if current.optional() {
+        "  Leave empty to skip"
+    } else {
+        "  Enter with nothing typed takes the first choice"
+    };
+    lines.push(Line::from(Span::styled(hint, theme.dimmed_style())));
+
+    let block = Block::default()
+        .title(" New Entity ")
+        .borders(Borders::ALL)
+        .border_style(theme.focused_border_style());
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        popup,
+    );
+}
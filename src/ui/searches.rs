@@ -0,0 +1,69 @@
+use crate::app::{App, SearchPicker};
+use crate::ui::help::centered_rect;
+use crate::ui::theme::*;
+use ratatui::{
+    layout::Rect,
+    style::Modifier,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// Draw the saved-search picker as a popup over the main view.
+pub fn draw_search_picker(frame: &mut Frame, app: &App, picker: &SearchPicker, area: Rect) {
+    let theme = &app.theme;
+    let popup = centered_rect(60, 60, area);
+
+    let mut lines = vec![Line::from("")];
+    if app.saved_searches.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  No saved searches. Filter with / then Tab, and press a here to save it.",
+            theme.dimmed_style(),
+        )));
+    }
+    for (i, search) in app.saved_searches.iter().enumerate() {
+        let active = app.filter.as_ref().is_some_and(|f| f.query == search.query);
+        let marker = if active { "● " } else { "  " };
+        let (name_style, query_style) = if i == picker.selected {
+            (theme.selected_style(), theme.selected_style())
+        } else {
+            (theme.label_style(), theme.dimmed_style())
+        };
+        lines.push(Line::from(vec![
+            Span::raw(format!("  {marker}")),
+            Span::styled(search.name.clone(), name_style),
+            Span::styled(format!("  {}", search.query), query_style),
+        ]));
+    }
+
+    if let Some(name) = &picker.naming {
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::styled("  Save filter as: ", theme.label_style()),
+            Span::styled(
+                format!("{name}{SELECTED_INDICATOR}"),
+                theme.label_style().add_modifier(Modifier::BOLD),
+            ),
+        ]));
+    }
+    if let Some(error) = &picker.error {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("  {error}"),
+            theme.error_style(),
+        )));
+    }
+
+    let block = Block::default()
+        .title(" Saved Searches ")
+        .borders(Borders::ALL)
+        .border_style(theme.focused_border_style());
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        popup,
+    );
+}
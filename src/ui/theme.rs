@@ -1,50 +1,167 @@
+//! Symbols and styles shared by the views.
+//!
+//! Each [`Theme`] style takes its color from [`DEFAULT_COLORS`] unless the
+//! `[theme]` configuration table overrides it (see [`Theme::from_config`]).
+//! The `selected` color is the highlight's background; the others are
+//! foregrounds.
+
 use ratatui::style::{Color, Modifier, Style};
+use std::collections::BTreeMap;
+use std::str::FromStr;
 
 // Tree symbols
 pub const EXPANDED_SYMBOL: &str = "[-] ";
 pub const COLLAPSED_SYMBOL: &str = "[+] ";
 pub const ERROR_INDICATOR: &str = " ⚠ ";
+pub const DUPLICATE_INDICATOR: &str = " ⧉ duplicate";
+pub const ADDED_INDICATOR: &str = " + new";
+pub const MODIFIED_INDICATOR: &str = " ~ changed";
 
 // Doc browser indicators
 pub const SELECTED_INDICATOR: &str = "_";
 
-// Colors and styles
-pub fn selected_style() -> Style {
-    Style::default()
-        .bg(Color::Blue)
-        .fg(Color::White)
-        .add_modifier(Modifier::BOLD)
-}
+/// Every themeable color by name, with its default.
+pub const DEFAULT_COLORS: [(&str, Color); 12] = [
+    ("selected", Color::Blue),
+    ("error", Color::Red),
+    ("duplicate", Color::Magenta),
+    ("changed", Color::LightGreen),
+    ("category", Color::Yellow),
+    ("match", Color::Green),
+    ("normal", Color::White),
+    ("border", Color::Cyan),
+    ("focused-border", Color::Yellow),
+    ("label", Color::Yellow),
+    ("dimmed", Color::DarkGray),
+    ("suggestion", Color::Yellow),
+];
 
-pub fn error_style() -> Style {
-    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+/// The colors in use: the defaults, with any `[theme]` overrides applied.
+/// Held by the app and handed to each view when it draws.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    colors: [(&'static str, Color); DEFAULT_COLORS.len()],
 }
 
-pub fn category_style() -> Style {
-    Style::default()
-        .fg(Color::Yellow)
-        .add_modifier(Modifier::BOLD)
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            colors: DEFAULT_COLORS,
+        }
+    }
 }
 
-pub fn normal_style() -> Style {
-    Style::default().fg(Color::White)
-}
+impl Theme {
+    /// The theme from a `[theme]` table (name to color, e.g. `"red"`,
+    /// `"light-blue"`, `"#5f87af"` or a 256-color index), with a warning for
+    /// each entry that was ignored.
+    pub fn from_config(theme: &BTreeMap<String, String>) -> (Self, Vec<String>) {
+        let mut colors = DEFAULT_COLORS;
+        let mut warnings = Vec::new();
+        for (name, value) in theme {
+            let Some((_, slot)) = colors.iter_mut().find(|(n, _)| n == name) else {
+                warnings.push(format!("[theme]: unknown style '{name}'"));
+                continue;
+            };
+            match Color::from_str(value) {
+                Ok(color) => *slot = color,
+                Err(_) => warnings.push(format!("[theme] {name}: unknown color '{value}'")),
+            }
+        }
+        (Self { colors }, warnings)
+    }
 
-pub fn border_style() -> Style {
-    Style::default().fg(Color::Cyan)
-}
+    /// The configured color for `name`, or its default.
+    fn color(&self, name: &str) -> Color {
+        self.colors
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map_or(Color::Reset, |(_, c)| *c)
+    }
 
-/// Border style for the pane that currently has keyboard focus.
-pub fn focused_border_style() -> Style {
-    Style::default()
-        .fg(Color::Yellow)
-        .add_modifier(Modifier::BOLD)
-}
+    // Colors and styles
+    pub fn selected_style(&self) -> Style {
+        Style::default()
+            .bg(self.color("selected"))
+            .fg(Color::White)
+            .add_modifier(Modifier::BOLD)
+    }
+
+    pub fn error_style(&self) -> Style {
+        Style::default()
+            .fg(self.color("error"))
+            .add_modifier(Modifier::BOLD)
+    }
+
+    pub fn duplicate_style(&self) -> Style {
+        Style::default()
+            .fg(self.color("duplicate"))
+            .add_modifier(Modifier::BOLD)
+    }
+
+    pub fn changed_style(&self) -> Style {
+        Style::default().fg(self.color("changed"))
+    }
 
-pub fn label_style() -> Style {
-    Style::default().fg(Color::Yellow)
+    pub fn category_style(&self) -> Style {
+        Style::default()
+            .fg(self.color("category"))
+            .add_modifier(Modifier::BOLD)
+    }
+
+    /// Characters of a label that matched the search query.
+    pub fn match_style(&self) -> Style {
+        Style::default()
+            .fg(self.color("match"))
+            .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+    }
+
+    pub fn normal_style(&self) -> Style {
+        Style::default().fg(self.color("normal"))
+    }
+
+    pub fn border_style(&self) -> Style {
+        Style::default().fg(self.color("border"))
+    }
+
+    /// Border style for the pane that currently has keyboard focus.
+    pub fn focused_border_style(&self) -> Style {
+        Style::default()
+            .fg(self.color("focused-border"))
+            .add_modifier(Modifier::BOLD)
+    }
+
+    pub fn label_style(&self) -> Style {
+        Style::default().fg(self.color("label"))
+    }
+
+    pub fn dimmed_style(&self) -> Style {
+        Style::default().fg(self.color("dimmed"))
+    }
+
+    /// "Did you mean" suggestions after a broken reference.
+    pub fn suggestion_style(&self) -> Style {
+        Style::default()
+            .fg(self.color("suggestion"))
+            .add_modifier(Modifier::ITALIC)
+    }
 }
 
-pub fn dimmed_style() -> Style {
-    Style::default().fg(Color::DarkGray)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_overrides_defaults_and_warns_on_bad_entries() {
+        let config = BTreeMap::from([
+            ("border".to_string(), "#5f87af".to_string()),
+            ("error".to_string(), "loud".to_string()),
+            ("sparkle".to_string(), "red".to_string()),
+        ]);
+        let (theme, warnings) = Theme::from_config(&config);
+        assert_eq!(theme.border_style().fg, Some(Color::Rgb(0x5f, 0x87, 0xaf)));
+        assert_eq!(theme.error_style().fg, Some(Color::Red));
+        assert_eq!(Theme::default().border_style().fg, Some(Color::Cyan));
+        assert_eq!(warnings.len(), 2, "{warnings:?}");
+    }
 }
@@ -1,4 +1,5 @@
 use crate::app::App;
+use crate::diff::Change;
 use crate::ui::theme::*;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
@@ -9,6 +10,7 @@ use ratatui::{
 };
 
 pub fn draw_tree(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
     // Split area for search bar and tree
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -18,27 +20,28 @@ pub fn draw_tree(frame: &mut Frame, app: &App, area: Rect) {
     // Draw search bar
     draw_search(frame, app, chunks[0]);
 
-    // During search the visible list is filtered flat, so branch connectors
-    // would be meaningless; fall back to plain rows. Otherwise draw the proper
-    // tree with connector prefixes. Both paths preserve visible_nodes() order so
-    // selection and scrolling stay aligned.
-    let rows: Vec<(&_, String)> = if app.search_query.is_empty() {
+    // During search the panel lists ranked results flat, so branch connectors
+    // would be meaningless; rows carry the label characters to highlight
+    // instead. Otherwise draw the proper tree with connector prefixes. Both
+    // paths preserve visible_nodes() order so selection and scrolling stay
+    // aligned.
+    let hits = app.search_hits();
+    let rows: Vec<(&_, String, &[usize])> = if app.search_query.trim().is_empty() {
         app.tree
             .visible_rows(&app.tree_state)
             .into_iter()
-            .map(|row| (row.node, row.prefix))
+            .map(|row| (row.node, row.prefix, &[][..]))
             .collect()
     } else {
-        app.visible_nodes()
-            .into_iter()
-            .map(|node| (node, "  ".repeat(node.depth)))
+        hits.iter()
+            .map(|hit| (hit.node, String::new(), &hit.ranking.highlights[..]))
             .collect()
     };
     let row_count = rows.len();
 
     let items: Vec<ListItem> = rows
         .iter()
-        .map(|(node, tree_prefix)| {
+        .map(|(node, tree_prefix, highlights)| {
             let is_selected = node.id == app.tree_state.selected;
             let has_children = !node.children.is_empty();
             let is_expanded = app.tree_state.is_expanded(node.id);
@@ -70,19 +73,46 @@ pub fn draw_tree(frame: &mut Frame, app: &App, area: Rect) {
                 String::new()
             };
 
-            let label = format!("{tree_prefix}{marker}{}{error_indicator}", node.label);
+            // Declared more than once across the catalog.
+            let is_duplicate = node
+                .entity
+                .as_ref()
+                .is_some_and(|ews| !ews.duplicates.is_empty());
+            let duplicate_indicator = if is_duplicate {
+                DUPLICATE_INDICATOR
+            } else {
+                ""
+            };
+
+            // Added or modified by a reload, until acknowledged.
+            let change = node.entity.as_ref().and_then(|ews| app.change_of(ews));
+            let change_indicator = match change {
+                Some(Change::Added) => ADDED_INDICATOR,
+                Some(Change::Modified) => MODIFIED_INDICATOR,
+                None => "",
+            };
 
             let style = if is_selected {
-                selected_style()
+                theme.selected_style()
             } else if has_errors {
-                error_style()
+                theme.error_style()
+            } else if is_duplicate {
+                theme.duplicate_style()
+            } else if change.is_some() {
+                theme.changed_style()
             } else if node.is_category {
-                category_style()
+                theme.category_style()
             } else {
-                normal_style()
+                theme.normal_style()
             };
 
-            ListItem::new(Line::from(Span::styled(label, style)))
+            let mut spans = vec![Span::styled(format!("{tree_prefix}{marker}"), style)];
+            spans.extend(highlighted(&node.label, highlights, style, theme));
+            spans.push(Span::styled(
+                format!("{error_indicator}{duplicate_indicator}{change_indicator}"),
+                style,
+            ));
+            ListItem::new(Line::from(spans))
         })
         .collect();
 
@@ -92,23 +122,30 @@ pub fn draw_tree(frame: &mut Frame, app: &App, area: Rect) {
     } else {
         String::new()
     };
-    let title = if app.search_query.is_empty() {
-        format!(" Entities ({}){error_suffix} ", app.entity_count)
-    } else {
+    let title = if !app.search_query.is_empty() {
         format!(
             " Entities ({}/{}){error_suffix} ",
-            row_count, app.entity_count
+            row_count,
+            app.shown_count()
+        )
+    } else if app.filter.is_some() {
+        format!(
+            " Entities ({}/{}, filtered){error_suffix} ",
+            app.shown_count(),
+            app.entity_count
         )
+    } else {
+        format!(" Entities ({}){error_suffix} ", app.entity_count)
     };
 
     let tree_block = Block::default()
         .title(title)
         .borders(Borders::ALL)
-        .border_style(border_style());
+        .border_style(theme.border_style());
 
     let list = List::new(items)
         .block(tree_block)
-        .highlight_style(selected_style());
+        .highlight_style(theme.selected_style());
 
     // Drive scrolling via ListState so the selected row stays on screen.
     let mut list_state = ListState::default();
@@ -117,7 +154,44 @@ pub fn draw_tree(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_stateful_widget(list, chunks[1], &mut list_state);
 }
 
+/// Split `label` into spans, patching [`match_style`] onto the characters at
+/// `highlights` (char indices).
+fn highlighted<'a>(
+    label: &'a str,
+    highlights: &[usize],
+    style: Style,
+    theme: &Theme,
+) -> Vec<Span<'a>> {
+    if highlights.is_empty() {
+        return vec![Span::styled(label, style)];
+    }
+    let mut spans = Vec::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+    for (i, c) in label.chars().enumerate() {
+        let matched = highlights.contains(&i);
+        if matched != run_matched && !run.is_empty() {
+            let run_style = if run_matched {
+                style.patch(theme.match_style())
+            } else {
+                style
+            };
+            spans.push(Span::styled(std::mem::take(&mut run), run_style));
+        }
+        run_matched = matched;
+        run.push(c);
+    }
+    let run_style = if run_matched {
+        style.patch(theme.match_style())
+    } else {
+        style
+    };
+    spans.push(Span::styled(run, run_style));
+    spans
+}
+
 fn draw_search(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
     let (border_color, cursor) = if app.search_active {
         (Color::Yellow, SELECTED_INDICATOR)
     } else {
@@ -131,13 +205,18 @@ fn draw_search(frame: &mut Frame, app: &App, area: Rect) {
     };
 
     let style = if app.search_query.is_empty() && !app.search_active {
-        dimmed_style()
+        theme.dimmed_style()
     } else {
-        normal_style()
+        theme.normal_style()
     };
 
+    // A query that does not parse still filters, as plain text; say why.
+    let (title, border_color) = match app.search_error() {
+        Some(error) => (format!(" Search ({error}; matching as text) "), Color::Red),
+        None => (" Search ".to_string(), border_color),
+    };
     let search_block = Block::default()
-        .title(" Search ")
+        .title(title)
         .borders(Borders::ALL)
         .border_style(Style::default().fg(border_color));
 
@@ -84,22 +84,74 @@
 //! # Key Types and Functions
 //!
 //! - [`validate_entity`] - Validate an entity against the JSON Schema
+//! - [`required_spec_fields`] / [`allowed_api_versions`] - Per-kind requirements read from the schema
 //! - Schema is automatically loaded and compiled on first use
 
-use crate::entity::{Entity, ValidationError};
+use crate::entity::{Entity, EntityKind, ValidationError};
 use jsonschema::Validator;
 use once_cell::sync::Lazy;
 use serde_json::Value as JsonValue;
 
 /// Embedded Backstage catalog JSON Schema
-static SCHEMA_STR: &str = include_str!("../schema/catalog-info.json");
+pub(crate) static SCHEMA_STR: &str = include_str!("../schema/catalog-info.json");
+
+/// Parsed embedded schema (initialized once)
+static SCHEMA_JSON: Lazy<JsonValue> =
+    Lazy::new(|| serde_json::from_str(SCHEMA_STR).expect("Failed to parse embedded JSON schema"));
 
 /// Compiled JSON Schema validator (initialized once)
-static SCHEMA: Lazy<Validator> = Lazy::new(|| {
-    let schema_json: JsonValue =
-        serde_json::from_str(SCHEMA_STR).expect("Failed to parse embedded JSON schema");
-    jsonschema::validator_for(&schema_json).expect("Failed to compile JSON schema")
-});
+static SCHEMA: Lazy<Validator> =
+    Lazy::new(|| jsonschema::validator_for(&SCHEMA_JSON).expect("Failed to compile JSON schema"));
+
+/// A `spec` field the schema requires for some kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequiredField {
+    pub name: String,
+    /// Whether the field holds a list (e.g. a group's `children`).
+    pub is_array: bool,
+}
+
+/// The kind-specific part of the schema (the object declaring `kind: {enum: [..]}`).
+fn kind_schema(kind: &EntityKind) -> Option<&'static JsonValue> {
+    let kind = kind.to_string();
+    SCHEMA_JSON["anyOf"]
+        .as_array()?
+        .iter()
+        .filter_map(|variant| variant["allOf"].as_array())
+        .flatten()
+        .find(|part| {
+            part["properties"]["kind"]["enum"]
+                .as_array()
+                .is_some_and(|kinds| kinds.iter().any(|k| k.as_str() == Some(kind.as_str())))
+        })
+}
+
+/// The `spec` fields the schema requires for `kind`, in schema order.
+pub fn required_spec_fields(kind: &EntityKind) -> Vec<RequiredField> {
+    let Some(spec) = kind_schema(kind).map(|s| &s["properties"]["spec"]) else {
+        return Vec::new();
+    };
+    spec["required"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|name| name.as_str())
+        .map(|name| RequiredField {
+            name: name.to_string(),
+            is_array: spec["properties"][name]["type"].as_str() == Some("array"),
+        })
+        .collect()
+}
+
+/// The `apiVersion` values the schema accepts for `kind` (empty if unknown).
+pub fn allowed_api_versions(kind: &EntityKind) -> Vec<String> {
+    kind_schema(kind)
+        .and_then(|s| s["properties"]["apiVersion"]["enum"].as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str().map(String::from))
+        .collect()
+}
 
 /// Validate an entity against the Backstage catalog JSON Schema
 pub fn validate_entity(entity: &Entity) -> Vec<ValidationError> {
@@ -208,4 +260,30 @@ mod tests {
         let errors = validate_entity(&entity);
         assert!(!errors.is_empty(), "Invalid component should have errors");
     }
+
+    #[test]
+    fn test_requirements_read_from_schema() {
+        let names = |kind| -> Vec<String> {
+            required_spec_fields(&kind)
+                .into_iter()
+                .map(|f| f.name)
+                .collect()
+        };
+        assert_eq!(names(EntityKind::Component), ["type", "lifecycle", "owner"]);
+        assert_eq!(
+            names(EntityKind::Api),
+            ["type", "lifecycle", "owner", "definition"]
+        );
+        let group = required_spec_fields(&EntityKind::Group);
+        assert!(group.contains(&RequiredField {
+            name: "children".to_string(),
+            is_array: true
+        }));
+        assert!(required_spec_fields(&EntityKind::Unknown).is_empty());
+
+        assert_eq!(
+            allowed_api_versions(&EntityKind::Component),
+            ["backstage.io/v1alpha1", "backstage.io/v1beta1"]
+        );
+    }
 }
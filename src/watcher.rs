@@ -1,19 +1,27 @@
 //! Filesystem watching for automatic catalog reloads.
 //!
-//! [`CatalogWatcher`] watches the catalog path and signals when a relevant YAML
-//! file changes. The event loop drains it and reloads (with debouncing) so the
-//! UI reflects on-disk edits without a manual reload.
+//! [`CatalogWatcher`] watches the catalog path and reports which relevant files
+//! changed. The event loop drains it and reloads those files (with debouncing)
+//! so the UI reflects on-disk edits without a manual reload.
 
+use crate::config::PROJECT_CONFIG;
+use crate::parser::IGNORE_FILE;
 use anyhow::Result;
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use std::path::Path;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
+
+/// How long the catalog must be quiet after a change before we reload, so a
+/// burst of editor writes coalesces into a single reload.
+pub const RELOAD_DEBOUNCE: Duration = Duration::from_millis(300);
 
 /// Watches a catalog directory (or a file's directory) for changes.
 pub struct CatalogWatcher {
     // Held to keep the watch alive; dropping it stops watching.
     _watcher: RecommendedWatcher,
-    rx: Receiver<()>,
+    rx: Receiver<PathBuf>,
 }
 
 impl CatalogWatcher {
@@ -24,8 +32,10 @@ impl CatalogWatcher {
         let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
             if let Ok(event) = res {
                 if is_relevant(&event) {
-                    // Ignore send errors: the receiver may have been dropped.
-                    let _ = tx.send(());
+                    for path in event.paths {
+                        // Ignore send errors: the receiver may have been dropped.
+                        let _ = tx.send(path);
+                    }
                 }
             }
         })?;
@@ -43,20 +53,18 @@ impl CatalogWatcher {
         })
     }
 
-    /// Drain pending events, returning `true` if any relevant change occurred
-    /// since the last call.
-    pub fn drain(&self) -> bool {
-        let mut changed = false;
+    /// Drain pending events, returning the paths of the relevant changes since
+    /// the last call (empty if there were none). Paths are as the watcher
+    /// reports them, typically absolute.
+    pub fn drain(&self) -> BTreeSet<PathBuf> {
         // Stops on the first Empty/Disconnected error.
-        while let Ok(()) = self.rx.try_recv() {
-            changed = true;
-        }
-        changed
+        self.rx.try_iter().collect()
     }
 }
 
 /// Whether a filesystem event should trigger a reload: a create/modify/remove
-/// touching a YAML file.
+/// touching a YAML file, or a file that changes which files are discovered
+/// (an ignore file or the project configuration).
 fn is_relevant(event: &Event) -> bool {
     let interesting_kind = matches!(
         event.kind,
@@ -64,9 +72,14 @@ fn is_relevant(event: &Event) -> bool {
     );
     interesting_kind
         && event.paths.iter().any(|p| {
-            p.extension()
+            let is_yaml = p
+                .extension()
                 .and_then(|e| e.to_str())
-                .is_some_and(|e| e.eq_ignore_ascii_case("yaml") || e.eq_ignore_ascii_case("yml"))
+                .is_some_and(|e| e.eq_ignore_ascii_case("yaml") || e.eq_ignore_ascii_case("yml"));
+            is_yaml
+                || p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| [".gitignore", IGNORE_FILE, PROJECT_CONFIG].contains(&n))
         })
 }
 
@@ -96,6 +109,16 @@ mod tests {
         )));
     }
 
+    #[test]
+    fn ignore_and_config_changes_are_relevant() {
+        for path in ["/x/.gitignore", "/x/sub/.bsvignore", "/x/.bsv.toml"] {
+            assert!(is_relevant(&event(
+                EventKind::Modify(ModifyKind::Any),
+                path
+            )));
+        }
+    }
+
     #[test]
     fn non_yaml_changes_are_ignored() {
         assert!(!is_relevant(&event(
@@ -112,6 +135,6 @@ mod tests {
     fn watcher_starts_on_directory() {
         let watcher = CatalogWatcher::new(Path::new("testdata")).expect("watch testdata");
         // No events yet.
-        assert!(!watcher.drain());
+        assert!(watcher.drain().is_empty());
     }
 }
@@ -0,0 +1,484 @@
+//! Format-preserving edits to catalog YAML files.
+//!
+//! Re-serializing an entity through serde would drop comments and reorder keys,
+//! so edits are applied to the source text instead: [`set_field`] finds one
+//! `section.key` (e.g. `spec.owner`) inside a single document of a
+//! multi-document file and rewrites only the lines that hold its value. Every
+//! other line — comments, blank lines, key order, other documents — is kept
+//! byte-for-byte.
+//!
+//! Only block-style mappings are edited; a flow-style section (`spec: {a: 1}`)
+//! is reported as an error rather than rewritten.
+//!
+//! # Examples
+//!
+//! ```
+//! use bsv::yaml_edit::{set_field, FieldValue};
+//!
+//! let yaml = "\
+//! apiVersion: backstage.io/v1alpha1
+//! kind: Component
+//! metadata:
+//!   name: payments # the public name
+//! spec:
+//!   owner: team-a
+//!   lifecycle: production
+//! ";
+//! let edited = set_field(yaml, 1, "spec", "owner", &FieldValue::Scalar("team-b".into()))?;
+//! assert!(edited.contains("  owner: team-b\n"));
+//! assert!(edited.contains("# the public name"));
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+use anyhow::{bail, Result};
+use serde::Deserialize;
+
+/// A new value for a field. Empty values remove the key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldValue {
+    /// A single scalar, e.g. `owner: team-a`.
+    Scalar(String),
+    /// A block sequence of scalars, e.g. `tags`.
+    List(Vec<String>),
+    /// A block sequence of small mappings, e.g. `links` (`url`/`title`/`icon`).
+    Mappings(Vec<Vec<(String, String)>>),
+    /// A value written verbatim on the key's line (must already be valid
+    /// YAML), e.g. `[]` for a required list that has no entries yet.
+    Raw(String),
+}
+
+impl FieldValue {
+    fn is_empty(&self) -> bool {
+        match self {
+            FieldValue::Scalar(s) => s.trim().is_empty(),
+            FieldValue::List(items) => items.is_empty(),
+            FieldValue::Mappings(items) => items.is_empty(),
+            FieldValue::Raw(raw) => raw.trim().is_empty(),
+        }
+    }
+}
+
+/// Set (or, for an empty value, remove) `section.key` in the document that
+/// contains 1-based line `doc_line`, returning the edited file content.
+///
+/// A missing key is appended to the end of its section; a missing section is
+/// appended to the end of the document. The result is checked to still parse
+/// as YAML before it is returned.
+pub fn set_field(
+    content: &str,
+    doc_line: usize,
+    section: &str,
+    key: &str,
+    value: &FieldValue,
+) -> Result<String> {
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+    let (doc_start, doc_end) = document_range(&lines, doc_line.saturating_sub(1));
+
+    match find_key(&lines, doc_start, doc_end, 0, section) {
+        Some(section_idx) => {
+            let inline = inline_value(&lines[section_idx], section);
+            if !inline.is_empty() && inline != "{}" {
+                bail!("`{section}` is not a block mapping; edit it by hand");
+            }
+            if inline == "{}" {
+                lines[section_idx] = format!("{section}:");
+            }
+            let block_end = block_end(&lines, section_idx, doc_end, 0);
+            let indent = child_indent(&lines, section_idx + 1, block_end).unwrap_or(2);
+
+            match find_key(&lines, section_idx + 1, block_end, indent, key) {
+                Some(key_idx) => {
+                    let value_end = block_end_for_key(&lines, key_idx, block_end, indent);
+                    let comment = if value_end == key_idx + 1 {
+                        trailing_comment(&lines[key_idx])
+                    } else {
+                        None
+                    };
+                    let item_indent = (key_idx + 1..value_end)
+                        .find(|&i| lines[i].trim_start().starts_with('-'))
+                        .map_or(indent + 2, |i| indent_of(&lines[i]));
+                    let replacement = if value.is_empty() {
+                        Vec::new()
+                    } else {
+                        render(key, value, indent, item_indent, comment.as_deref())
+                    };
+                    lines.splice(key_idx..value_end, replacement);
+                }
+                None if value.is_empty() => {}
+                None => {
+                    let at = last_content_line(&lines, section_idx, block_end) + 1;
+                    let rendered = render(key, value, indent, indent + 2, None);
+                    lines.splice(at..at, rendered);
+                }
+            }
+        }
+        None if value.is_empty() => {}
+        None => {
+            let at = last_content_line(&lines, doc_start, doc_end) + 1;
+            let mut rendered = vec![format!("{section}:")];
+            rendered.extend(render(key, value, 2, 4, None));
+            lines.splice(at..at, rendered);
+        }
+    }
+
+    let mut edited = lines.join("\n");
+    if content.ends_with('\n') {
+        edited.push('\n');
+    }
+    ensure_parses(&edited)?;
+    Ok(edited)
+}
+
+/// Set a top-level scalar (e.g. `apiVersion`) in the document that contains
+/// 1-based line `doc_line`, keeping any trailing comment. A missing key is
+/// inserted as the document's first line.
+pub fn set_top_level(content: &str, doc_line: usize, key: &str, value: &str) -> Result<String> {
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+    let (doc_start, doc_end) = document_range(&lines, doc_line.saturating_sub(1));
+    match find_key(&lines, doc_start, doc_end, 0, key) {
+        Some(idx) => {
+            let comment = trailing_comment(&lines[idx]);
+            lines[idx] = render(
+                key,
+                &FieldValue::Scalar(value.to_string()),
+                0,
+                0,
+                comment.as_deref(),
+            )
+            .remove(0);
+        }
+        None => {
+            let at = (doc_start..doc_end)
+                .find(|&i| !is_blank_or_comment(&lines[i]))
+                .unwrap_or(doc_start);
+            lines.insert(at, format!("{key}: {}", scalar(value)));
+        }
+    }
+
+    let mut edited = lines.join("\n");
+    if content.ends_with('\n') {
+        edited.push('\n');
+    }
+    ensure_parses(&edited)?;
+    Ok(edited)
+}
+
+/// Whether a line opens a new document (`---`, optionally followed by content).
+pub(crate) fn is_marker(line: &str) -> bool {
+    let line = line.trim_end();
+    line == "---" || line.starts_with("--- ") || line == "..."
+}
+
+pub(crate) fn is_blank_or_comment(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty() || trimmed.starts_with('#')
+}
+
+pub(crate) fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// The `[start, end)` line range of the document containing line index `at`.
+fn document_range(lines: &[String], at: usize) -> (usize, usize) {
+    let at = at.min(lines.len());
+    let start = (0..at)
+        .rev()
+        .find(|&i| is_marker(&lines[i]))
+        .map_or(0, |i| i + 1);
+    let end = (at..lines.len())
+        .find(|&i| is_marker(&lines[i]))
+        .unwrap_or(lines.len());
+    (start, end)
+}
+
+/// Index of the line in `[start, end)` declaring `key:` at exactly `indent`.
+pub(crate) fn find_key(
+    lines: &[String],
+    start: usize,
+    end: usize,
+    indent: usize,
+    key: &str,
+) -> Option<usize> {
+    (start..end).find(|&i| {
+        let line = &lines[i];
+        if is_blank_or_comment(line) || indent_of(line) != indent {
+            return false;
+        }
+        let rest = &line[indent..];
+        let unquoted = rest
+            .strip_prefix(key)
+            .or_else(|| rest.strip_prefix(&format!("\"{key}\"")))
+            .or_else(|| rest.strip_prefix(&format!("'{key}'")));
+        unquoted.is_some_and(|r| r == ":" || r.starts_with(": ") || r.starts_with(":\t"))
+    })
+}
+
+/// The value written on a `key: value` line, without any trailing comment.
+pub(crate) fn inline_value<'a>(line: &'a str, key: &str) -> &'a str {
+    let after = line.trim_start().split_once(':').map_or("", |(k, v)| {
+        if k.trim_matches(['"', '\'']) == key {
+            v
+        } else {
+            ""
+        }
+    });
+    strip_comment(after).trim()
+}
+
+/// `text` up to an unquoted ` #` comment.
+pub(crate) fn strip_comment(text: &str) -> &str {
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut prev = ' ';
+    for (i, c) in text.char_indices() {
+        match c {
+            '\'' if !in_double => in_single = !in_single,
+            '"' if !in_single && prev != '\\' => in_double = !in_double,
+            '#' if !in_single && !in_double && prev.is_whitespace() => return &text[..i],
+            _ => {}
+        }
+        prev = c;
+    }
+    text
+}
+
+/// A trailing ` # comment` on a single-line `key: value`, kept across edits.
+pub(crate) fn trailing_comment(line: &str) -> Option<String> {
+    let (_, value) = line.split_once(':')?;
+    let code = strip_comment(value);
+    (code.len() < value.len()).then(|| value[code.len()..].trim().to_string())
+}
+
+/// First line index after the block owned by the line at `owner` (whose key
+/// sits at `indent`): the next content line indented at or below `indent`.
+pub(crate) fn block_end(lines: &[String], owner: usize, end: usize, indent: usize) -> usize {
+    (owner + 1..end)
+        .find(|&i| !is_blank_or_comment(&lines[i]) && indent_of(&lines[i]) <= indent)
+        .unwrap_or(end)
+}
+
+/// Like [`block_end`] for a key's value, but also claims a block sequence
+/// written at the key's own indentation (`tags:\n- a`), and leaves trailing
+/// blank/comment lines in place for whatever follows.
+pub(crate) fn block_end_for_key(
+    lines: &[String],
+    key_idx: usize,
+    end: usize,
+    indent: usize,
+) -> usize {
+    let mut last = key_idx;
+    for (i, line) in lines.iter().enumerate().take(end).skip(key_idx + 1) {
+        if is_blank_or_comment(line) {
+            continue;
+        }
+        let own_level_item = indent_of(line) == indent && line.trim_start().starts_with('-');
+        if indent_of(line) > indent || own_level_item {
+            last = i;
+        } else {
+            break;
+        }
+    }
+    last + 1
+}
+
+/// Indentation of the first content line in `[start, end)`.
+pub(crate) fn child_indent(lines: &[String], start: usize, end: usize) -> Option<usize> {
+    (start..end)
+        .find(|&i| !is_blank_or_comment(&lines[i]))
+        .map(|i| indent_of(&lines[i]))
+}
+
+/// Index of the last content line in `(from, end)`, or `from` if there is none.
+fn last_content_line(lines: &[String], from: usize, end: usize) -> usize {
+    (from..end)
+        .rev()
+        .find(|&i| !is_blank_or_comment(&lines[i]))
+        .unwrap_or(from)
+        .max(from)
+}
+
+/// Render `key: value` lines at `indent`, with sequence items at `item_indent`.
+fn render(
+    key: &str,
+    value: &FieldValue,
+    indent: usize,
+    item_indent: usize,
+    comment: Option<&str>,
+) -> Vec<String> {
+    let pad = " ".repeat(indent);
+    let item_pad = " ".repeat(item_indent);
+    match value {
+        FieldValue::Scalar(s) => {
+            let comment = comment.map(|c| format!(" {c}")).unwrap_or_default();
+            vec![format!("{pad}{key}: {}{comment}", scalar(s.trim()))]
+        }
+        FieldValue::Raw(raw) => {
+            let comment = comment.map(|c| format!(" {c}")).unwrap_or_default();
+            vec![format!("{pad}{key}: {}{comment}", raw.trim())]
+        }
+        FieldValue::List(items) => std::iter::once(format!("{pad}{key}:"))
+            .chain(items.iter().map(|i| format!("{item_pad}- {}", scalar(i))))
+            .collect(),
+        FieldValue::Mappings(items) => {
+            let mut out = vec![format!("{pad}{key}:")];
+            for map in items {
+                for (n, (k, v)) in map.iter().enumerate() {
+                    let lead = if n == 0 { "- " } else { "  " };
+                    out.push(format!("{item_pad}{lead}{k}: {}", scalar(v)));
+                }
+            }
+            out
+        }
+    }
+}
+
+/// A YAML scalar for `s`: plain when unambiguous, quoted otherwise.
+pub fn scalar(s: &str) -> String {
+    let yaml = serde_yaml::to_string(s).unwrap_or_default();
+    let yaml = yaml.trim_end_matches('\n');
+    if yaml.is_empty() || yaml.contains('\n') {
+        // Double-quoted JSON strings are valid YAML and stay on one line.
+        serde_json::to_string(s).unwrap_or_else(|_| format!("{s:?}"))
+    } else {
+        yaml.to_string()
+    }
+}
+
+pub(crate) fn ensure_parses(content: &str) -> Result<()> {
+    for document in serde_yaml::Deserializer::from_str(content) {
+        if let Err(e) = serde_yaml::Value::deserialize(document) {
+            bail!("edit would produce invalid YAML: {e}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MULTI: &str = "\
+# Catalog for the payments team
+---
+apiVersion: backstage.io/v1alpha1
+kind: Component
+metadata:
+  name: payments
+  # keep this description short
+  description: Takes money
+  tags:
+    - java
+    - backend
+spec:
+  type: service # deployed on k8s
+  owner: team-a
+  lifecycle: production
+---
+apiVersion: backstage.io/v1alpha1
+kind: API
+metadata:
+  name: payments-api
+spec:
+  type: openapi
+  owner: team-a
+";
+
+    fn scalar_value(s: &str) -> FieldValue {
+        FieldValue::Scalar(s.to_string())
+    }
+
+    #[test]
+    fn replaces_scalar_in_the_right_document_only() {
+        let edited = set_field(MULTI, 17, "spec", "owner", &scalar_value("team-b")).unwrap();
+        // Second document changed, first untouched.
+        assert!(edited.ends_with("  type: openapi\n  owner: team-b\n"));
+        assert!(edited.contains("  owner: team-a\n  lifecycle: production"));
+        // Everything else is preserved byte-for-byte.
+        assert_eq!(edited.lines().count(), MULTI.lines().count());
+        assert!(edited.starts_with("# Catalog for the payments team\n---\n"));
+    }
+
+    #[test]
+    fn keeps_trailing_comments_and_key_order() {
+        let edited = set_field(MULTI, 3, "spec", "type", &scalar_value("website")).unwrap();
+        assert!(edited.contains("  type: website # deployed on k8s\n  owner: team-a\n"));
+        assert!(edited.contains("  # keep this description short\n"));
+    }
+
+    #[test]
+    fn inserts_missing_key_at_end_of_section() {
+        let edited = set_field(MULTI, 3, "spec", "system", &scalar_value("billing")).unwrap();
+        assert!(edited.contains("  lifecycle: production\n  system: billing\n---\n"));
+    }
+
+    #[test]
+    fn inserts_missing_section_at_end_of_document() {
+        let yaml = "kind: Group\nmetadata:\n  name: team-a\n";
+        let edited = set_field(yaml, 1, "spec", "type", &scalar_value("team")).unwrap();
+        assert_eq!(
+            edited,
+            "kind: Group\nmetadata:\n  name: team-a\nspec:\n  type: team\n"
+        );
+    }
+
+    #[test]
+    fn replaces_lists_keeping_item_indentation() {
+        let tags = FieldValue::List(vec!["rust".into(), "payments".into()]);
+        let edited = set_field(MULTI, 3, "metadata", "tags", &tags).unwrap();
+        assert!(edited.contains("  tags:\n    - rust\n    - payments\nspec:"));
+    }
+
+    #[test]
+    fn empty_value_removes_the_key() {
+        let edited = set_field(MULTI, 3, "metadata", "description", &scalar_value("")).unwrap();
+        assert!(!edited.contains("description: "));
+        // The comment above the removed key stays.
+        assert!(edited.contains("  # keep this description short\n  tags:"));
+
+        let edited = set_field(MULTI, 3, "metadata", "tags", &FieldValue::List(vec![])).unwrap();
+        assert!(!edited.contains("- java"));
+    }
+
+    #[test]
+    fn renders_mappings_for_links() {
+        let links = FieldValue::Mappings(vec![vec![
+            ("url".into(), "https://example.com/runbook".into()),
+            ("title".into(), "Runbook: on-call".into()),
+        ]]);
+        let edited = set_field(MULTI, 3, "metadata", "links", &links).unwrap();
+        assert!(edited.contains(
+            "  links:\n    - url: https://example.com/runbook\n      title: 'Runbook: on-call'\n"
+        ));
+    }
+
+    #[test]
+    fn quotes_ambiguous_scalars() {
+        assert_eq!(scalar("team-a"), "team-a");
+        assert_eq!(scalar("true"), "'true'");
+        assert_eq!(scalar("42"), "'42'");
+        assert_eq!(scalar("a: b"), "'a: b'");
+    }
+
+    #[test]
+    fn sets_top_level_scalars() {
+        let edited = set_top_level(MULTI, 17, "apiVersion", "backstage.io/v1beta1").unwrap();
+        assert!(edited.contains("---\napiVersion: backstage.io/v1beta1\nkind: API\n"));
+        assert!(edited.contains("---\napiVersion: backstage.io/v1alpha1\nkind: Component\n"));
+
+        let edited = set_top_level("# c\nkind: Group\n", 1, "apiVersion", "v1").unwrap();
+        assert_eq!(edited, "# c\napiVersion: v1\nkind: Group\n");
+    }
+
+    #[test]
+    fn raw_values_are_written_verbatim() {
+        let yaml = "kind: Group\nspec:\n  type: team\n";
+        let edited = set_field(yaml, 1, "spec", "children", &FieldValue::Raw("[]".into())).unwrap();
+        assert_eq!(edited, "kind: Group\nspec:\n  type: team\n  children: []\n");
+    }
+
+    #[test]
+    fn flow_style_section_is_rejected() {
+        let yaml = "kind: Group\nspec: {type: team}\n";
+        assert!(set_field(yaml, 1, "spec", "type", &scalar_value("x")).is_err());
+    }
+}
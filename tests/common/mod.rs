@@ -0,0 +1,35 @@
+//! Helpers shared by the integration tests (a copy of the library's
+//! test-only `TempDir`, which integration tests can't reach).
+
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// A fresh directory under the system temp dir, removed with its contents
+/// when dropped (even if the test panics).
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Create `bsv-<name>-<pid>`, replacing any left over from an earlier
+    /// run. `name` must be unique among the tests.
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("bsv-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,119 @@
+//! End-to-end test of `--from-api` loading: a stand-in catalog server answers
+//! with `bsv::server::Catalog`, forcing one entity per page so paging is
+//! exercised, and the fetched entities come back with synthetic sources.
+
+use bsv::entity::{Entity, EntityWithSource};
+use bsv::relations::attach_relations;
+use bsv::remote::load_from_api;
+use bsv::server::Catalog;
+use serde::Deserialize;
+use std::thread;
+
+const CATALOG: &str = "\
+apiVersion: backstage.io/v1alpha1
+kind: Group
+metadata:
+  name: payments
+spec:
+  type: team
+  children: []
+---
+apiVersion: backstage.io/v1alpha1
+kind: Component
+metadata:
+  name: checkout
+spec:
+  type: service
+  lifecycle: production
+  owner: payments
+---
+apiVersion: backstage.io/v1alpha1
+kind: API
+metadata:
+  name: checkout-api
+  namespace: shop
+spec:
+  type: openapi
+  lifecycle: production
+  owner: group:default/payments
+  definition: openapi
+";
+
+/// Serve `CATALOG` on an ephemeral port, answering `requests` requests with
+/// one entity per `by-query` page. With `by_query` unset, `by-query` answers
+/// 404 like an older backend. Returns the catalog API base URL.
+fn stand_in(requests: usize, by_query: bool) -> String {
+    let mut entities: Vec<EntityWithSource> = serde_yaml::Deserializer::from_str(CATALOG)
+        .map(|doc| EntityWithSource::new(Entity::deserialize(doc).unwrap(), "c.yaml".into()))
+        .collect();
+    attach_relations(&mut entities);
+    let catalog = Catalog::new(&entities);
+    let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let base = format!("http://{}/api/catalog", server.server_addr());
+
+    thread::spawn(move || {
+        for request in server.incoming_requests().take(requests) {
+            let mut url = request.url().to_string();
+            if url.contains("by-query") {
+                url = url.replace("limit=500", "limit=1");
+            }
+            let response = if !by_query && url.contains("by-query") {
+                tiny_http::Response::from_string("{}").with_status_code(404)
+            } else {
+                let response = catalog.handle(&url);
+                tiny_http::Response::from_string(response.body.to_string())
+                    .with_status_code(response.status)
+            };
+            let _ = request.respond(response);
+        }
+    });
+    base
+}
+
+fn refs(entities: &[EntityWithSource]) -> Vec<String> {
+    entities.iter().map(|e| e.entity.ref_key()).collect()
+}
+
+#[test]
+fn pages_through_by_query() {
+    let base = stand_in(3, true);
+    let (entities, warnings) = load_from_api(&format!("{base}/")).unwrap();
+
+    assert!(warnings.is_empty(), "{warnings:?}");
+    assert_eq!(
+        refs(&entities),
+        vec![
+            "api:shop/checkout-api",
+            "component:default/checkout",
+            "group:default/payments",
+        ]
+    );
+    assert_eq!(
+        entities[0].source_file.to_str().unwrap(),
+        format!("{base}/entities/by-name/api/shop/checkout-api")
+    );
+    assert!(entities.iter().all(|e| e.validation_errors.is_empty()));
+}
+
+#[test]
+fn falls_back_to_offset_paging() {
+    // One 404 from by-query, then one page from /entities.
+    let base = stand_in(2, false);
+    let (entities, _) = load_from_api(&base).unwrap();
+    assert_eq!(
+        refs(&entities),
+        vec![
+            "group:default/payments",
+            "component:default/checkout",
+            "api:shop/checkout-api",
+        ]
+    );
+}
+
+#[test]
+fn unreachable_api_is_an_error() {
+    let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let base = format!("http://{}/api/catalog", server.server_addr());
+    drop(server);
+    assert!(load_from_api(&base).is_err());
+}
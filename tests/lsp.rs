@@ -0,0 +1,113 @@
+//! End-to-end test of `bsv lsp`: speak JSON-RPC to the binary over stdio,
+//! open a catalog file, check the published diagnostics and that closing it
+//! clears them.
+
+mod common;
+
+use bsv::config::PROJECT_CONFIG;
+use common::TempDir;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{ChildStdout, Command, Stdio};
+
+fn send(stdin: &mut impl Write, message: Value) {
+    let body = message.to_string();
+    write!(stdin, "Content-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+    stdin.flush().unwrap();
+}
+
+fn receive(stdout: &mut BufReader<ChildStdout>) -> Value {
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        stdout.read_line(&mut header).unwrap();
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = value.trim().parse().unwrap();
+        }
+    }
+    let mut body = vec![0; length];
+    stdout.read_exact(&mut body).unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[test]
+fn publishes_diagnostics_for_open_documents() {
+    let dir = TempDir::new("lsp-e2e");
+    let file = dir.join("catalog-info.yaml");
+    let text = "\
+apiVersion: backstage.io/v1alpha1
+kind: Component
+metadata:
+  name: web
+spec:
+  type: website
+  lifecycle: production
+  owner: nobody
+";
+    std::fs::write(&file, text).unwrap();
+    std::fs::write(dir.join(PROJECT_CONFIG), "[cache]\nenabled = false\n").unwrap();
+    let uri = format!("file://{}", file.display());
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_bsv"))
+        .arg("lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {
+            "capabilities": {},
+            "rootUri": format!("file://{}", dir.display()),
+        }}),
+    );
+    let initialized = receive(&mut stdout);
+    assert_eq!(
+        initialized["result"]["capabilities"]["hoverProvider"],
+        json!(true)
+    );
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "method": "initialized", "params": {}}),
+    );
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {
+            "textDocument": {"uri": uri, "languageId": "yaml", "version": 1, "text": text},
+        }}),
+    );
+    let published = receive(&mut stdout);
+    assert_eq!(published["method"], "textDocument/publishDiagnostics");
+    assert_eq!(published["params"]["uri"], json!(uri));
+    let diagnostics = published["params"]["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["message"], "owner: nobody not found");
+    assert_eq!(diagnostics[0]["range"]["start"]["line"], 7);
+
+    // Closing the file clears its diagnostics in the client.
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "method": "textDocument/didClose", "params": {
+            "textDocument": {"uri": uri},
+        }}),
+    );
+    let cleared = receive(&mut stdout);
+    assert_eq!(cleared["method"], "textDocument/publishDiagnostics");
+    assert_eq!(cleared["params"]["uri"], json!(uri));
+    assert_eq!(cleared["params"]["diagnostics"], json!([]));
+
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "id": 2, "method": "shutdown"}),
+    );
+    assert_eq!(receive(&mut stdout)["id"], 2);
+    send(&mut stdin, json!({"jsonrpc": "2.0", "method": "exit"}));
+    assert!(child.wait().unwrap().success());
+}
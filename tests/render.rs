@@ -61,3 +61,53 @@ fn graph_view_renders() {
     let text = render(&app, 120, 40);
     assert!(text.contains("Relationships"), "graph panel title present");
 }
+
+#[test]
+fn search_lists_ranked_results() {
+    let mut app = test_app();
+    app.start_search();
+    for c in "pmt-gtw".chars() {
+        app.search_input(c);
+    }
+    let text = render(&app, 120, 40);
+    assert!(text.contains("payment-gateway"), "best match listed");
+    assert!(text.contains("Entities (1/"), "title counts the results");
+}
+
+#[test]
+fn filter_and_saved_search_picker_render() {
+    let mut app = test_app();
+    app.set_filter("APIs", "kind:api");
+    app.open_search_picker();
+    let text = render(&app, 120, 40);
+    assert!(
+        text.contains(&format!("Entities (20/{}, filtered)", app.entity_count)),
+        "tree title shows the filtered count"
+    );
+    assert!(text.contains("Saved Searches"), "picker popup is visible");
+}
+
+#[test]
+fn command_palette_and_registry_help_render() {
+    let mut app = test_app();
+    app.open_palette();
+    app.palette_input('g');
+    let text = render(&app, 120, 40);
+    assert!(text.contains("Command Palette"), "palette popup is visible");
+    assert!(text.contains("goto REF"), "commands list their parameter");
+
+    app.close_palette();
+    app.show_help = true;
+    let text = render(&app, 120, 40);
+    assert!(text.contains(":export"), "help lists palette-only commands");
+}
+
+#[test]
+fn help_shows_the_effective_key_bindings() {
+    let mut app = test_app();
+    app.keymap = bsv::keymap::Keymap::preset("emacs").unwrap();
+    app.show_help = true;
+    let text = render(&app, 120, 40);
+    assert!(text.contains("↓ / Ctrl-N"), "emacs bindings are listed");
+    assert!(!text.contains("↓ / j"), "default bindings are gone");
+}
@@ -1,17 +1,16 @@
 //! End-to-end test of the filesystem watcher: a real edit to a catalog file
-//! under the watched directory should produce a change signal.
+//! under the watched directory should be reported with its path.
+
+mod common;
 
 use bsv::watcher::CatalogWatcher;
+use common::TempDir;
 use std::fs;
 use std::time::{Duration, Instant};
 
 #[test]
 fn detects_catalog_file_change() {
-    // Unique temp directory (avoids Date/rand; uses pid + a nonce file).
-    let mut dir = std::env::temp_dir();
-    dir.push(format!("bsv-watch-test-{}", std::process::id()));
-    let _ = fs::remove_dir_all(&dir);
-    fs::create_dir_all(&dir).unwrap();
+    let dir = TempDir::new("watch-test");
 
     let file = dir.join("catalog-info.yaml");
     fs::write(
@@ -30,17 +29,25 @@ fn detects_catalog_file_change() {
     )
     .unwrap();
 
-    // Poll for the change signal with a generous timeout.
+    // Poll with a generous timeout for the batch reporting the file;
+    // directory or temp-file events may arrive in earlier batches.
     let deadline = Instant::now() + Duration::from_secs(5);
-    let mut seen = false;
+    let mut reported = None;
     while Instant::now() < deadline {
-        if watcher.drain() {
-            seen = true;
+        let changed = watcher.drain();
+        if changed.iter().any(|p| p.ends_with("catalog-info.yaml")) {
+            reported = Some(changed);
             break;
         }
         std::thread::sleep(Duration::from_millis(50));
     }
 
-    let _ = fs::remove_dir_all(&dir);
-    assert!(seen, "watcher should observe the catalog file change");
+    let changed = reported.expect("watcher should report the changed catalog file");
+    let file = file.canonicalize().unwrap();
+    assert!(
+        changed
+            .iter()
+            .any(|p| p.canonicalize().is_ok_and(|p| p == file)),
+        "{changed:?}"
+    );
 }